@@ -0,0 +1,718 @@
+-- Baseline, the schema as created by load_from_schema from schema/*.sql when migrations were introduced.
+--   DBs built before this have no user_version set, and are stamped with this version. Everything is only
+--   created when it is missing, so that the later migrations can also be applied to a new, empty, DB.
+
+CREATE TABLE IF NOT EXISTS beat_type (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS chp_beat_class (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS chp_beat_type (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS chp_shift (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS chp_vehtype (id CHAR(2) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS cnty_city_loc (id VARCHAR(4) PRIMARY KEY, county VARCHAR(256), city VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS collision_severity (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS control_device (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS day_of_week (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS dir_of_travel (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS direction (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS finan_respons (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS hit_and_run (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS lighting (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS location_type (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS move_pre_acc (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS mviw (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS oaf (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS oaf_viol_cat (id CHAR(2) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS oaf_violation_code (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS party_drug_physical (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS party_safety_equip (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS party_sex (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS party_sobriety (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS party_type (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS pcf_code_of_viol (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS pcf_viol_category (id CHAR(2) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS ped_action (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS population (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS primary_coll_factor (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS race (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS ramp (id VARCHAR2(2) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS ramp_intersection (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS road_cond (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS road_surface (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS side_of_hwy (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS sp_info_1 (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS sp_info_2 (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS sp_info_3 (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS special_cond (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS statewide_vehicle_type (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS stwd_vehtype_at_fault (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS type_of_collision (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS victim_age (id INTEGER PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS victim_degree_of_injury (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS victim_ejected (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS victim_role (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS victim_safety_equip (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS victim_seating_position (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS victim_sex (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS weather (id CHAR(1) PRIMARY KEY, name VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS ca_bike_lane_types (id VARCHAR(4) PRIMARY KEY, name VARCHAR(256), description VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS berkeley_road_typos (correct_rd VARCHAR(256), normalized_rd VARCHAR(256));
+
+CREATE TABLE IF NOT EXISTS corrected_roads (
+    case_id VARCHAR2(19), -- matches the case_id in collisions
+    primary_rd VARCHAR2(50), -- Primary Road
+    secondary_rd VARCHAR2(50), -- Secondary Road 
+    PRIMARY KEY(case_id)
+);
+
+CREATE TABLE IF NOT EXISTS improvement_types (id VARCHAR(4) PRIMARY KEY, name VARCHAR(256), link VARCHAR(2048));
+
+CREATE TABLE IF NOT EXISTS collisions (
+    case_id VARCHAR2(19), -- Case Id: the unique identifier of the collision report (barcode beginning 2002; 19 digit code prior to 2002)
+    accident_year INTEGER, -- Collision Year: the year when the collision occurred
+    proc_date TEXT, -- Process Date: (YYYYMMDD)
+    juris INTEGER, -- Jurisdiction: Four numerics assigned by DOJ
+    collision_date TEXT, -- Collision Date: the date when the collision occurred (YYYYMMDD)	
+    collision_time TEXT, -- Collision Time: the time when the collision occurred (24 hour time)	Data may appear with no leading zero(s).
+    officer_id VARCHAR2(8), -- Officer Id
+    reporting_district VARCHAR2(5), -- Reporting District
+    day_of_week CHAR(1), -- Day of Week: the code for the day of the week when the collision occurred (see lookup-tables/DAY_OF_WEEK.csv)
+    chp_shift CHAR(1), -- CHP Shift (see lookup-tables/CHP_SHIFT.csv)
+    population CHAR(1), -- Population (see lookup-tables/POPULATION.csv)
+    cnty_city_loc VARCHAR2(4), -- County City Location: the location code of where the collision occurred. Data may appear with no leading zero.
+    special_cond CHAR(1), -- Special Condition (see lookup-tables/SPECIAL_CONDITION.csv)
+    beat_type CHAR(1), -- Beat Type (see lookup-tables/BEAT_TYPE.csv)
+    chp_beat_type CHAR(1), -- CHP Beat Type (see lookup-tables/CHP_BEAT_TYPE.csv)
+    city_division_lapd CHAR(1), -- City Division LAPD: Includes blanks and dashes as not stated.
+    chp_beat_class CHAR(1), -- CHP Beat Class
+    beat_number VARCHAR2(6), -- Beat Number
+    primary_rd VARCHAR2(50), -- Primary Rd
+    secondary_rd VARCHAR2(50), -- Secondary Rd
+    distance DECIMAL(9,2), -- Distance: distance converted to feet
+    direction CHAR(1), -- Direction (see lookup-tables/DIRECTION.csv)
+    intersection CHAR(1), -- Intersection: Y - Intersection, N - Not Intersection, Blank - Not stated
+    weather_1 CHAR(1), -- Weather 1: the weather condition at the time of the collision (see lookup-tables/WEATHER_1.csv)
+    weather_2 CHAR(1), -- Weather 2: the weather condition at the time of the collision, if a second description is necessary (see lookup-tables/WEATHER_1.csv)
+    state_hwy_ind CHAR(1), -- State Highway Indicator: Y - State Highway, N - Not State Highway, Blank - Not stated
+    caltrans_county CHAR(3), -- Caltrans County: Includes blanks and nulls
+    caltrans_district INTEGER, -- Caltrans District
+    state_route INTEGER, -- State Route: 0 = Not State Highway
+    route_suffix CHAR(1), -- Route Suffix
+    postmile_prefix Char(1), -- Postmile Prefix
+    postmile DECIMAL(6,3), -- Postmile
+    location_type CHAR(1), -- Location Type (see lookup-tables/LOCATION_TYPE.csv)
+    ramp_intersection CHAR(1), -- Ramp Intersection (see lookup-tables/RAMP_INTERSECTION.csv)
+    side_of_hwy CHAR(1), -- Side Of Highway: Code provided by Caltrans Coders; applies to divided highway, based on nominal direction of route; for single vehicle is same as nominal direction of travel, overruled by impact with second vehicle after crossing median (see lookup-tables/SIDE_OF_HIGHWAY.csv)
+    tow_away CHAR(1), -- Tow Away: Y - Yes, N - No
+    collision_severity CHAR(1), -- Collision Severity (see lookup-tables/COLLISION_SEVERITY.csv)
+    number_killed INTEGER, -- Killed victims: counts victims in the collision with degree of injury of 1 0 to N for each collision
+    number_injured INTEGER, -- Injured victims: counts victims in the collision with degree of injury of 2, 3, or 4	0 to N for each collision
+    party_count INTEGER, -- Party Count: counts total parties in the collision 1 to N for each collision
+    primary_coll_factor CHAR(1), -- Primary Collision Factor (see lookup-tables/PRIMARY_COLLISION_FACTOR.csv)
+    pcf_code_of_viol CHAR(1), -- PCF Violation Code (see lookup-tables/PCF_VIOLATION_CODE.csv)
+    pcf_viol_category CHAR(2), -- PCF Violation Category (see lookup-tables/PCF_VIOLATION_CATEGORY.csv)
+    pcf_violation INTEGER, -- PCF Violation
+    pcf_viol_subsection CHAR(1), -- PCF Violation Subsection: Blank if no subsection.
+    hit_and_run CHAR(1), -- Hit And Run (see lookup-tables/HIT_AND_RUN.csv)
+    type_of_collision CHAR(1), -- Type of Collision (see lookup-tables/TYPE_OF_COLLISION.csv)
+    mviw CHAR(1), -- Motor Vehicle Involved With (see lookup-tables/MOTOR_VEHICLE_INVOLVED_WITH.csv)
+    ped_action CHAR(1), -- Ped Action (see lookup-tables/PED_ACTION.csv)
+    road_surface CHAR(1), -- Road Surface (see lookup-tables/ROAD_SURFACE.csv)
+    road_cond_1 CHAR(1), -- Road Condition 1 (see lookup-tables/ROAD_CONDITION_1.csv)
+    road_cond_2 CHAR(1), -- Road Condition 2 same as road condition 1 above (see lookup-tables/ROAD_CONDITION_1.csv)
+    lighting CHAR(1), -- Lighting (see lookup-tables/LIGHTNING.csv)
+    control_device CHAR(1), -- Control Device (see lookup-tables/CONTROL_DEVICE.csv)
+    chp_road_type CHAR(1), -- CHP Road Type: May be blank
+    pedestrian_accident CHAR(1), -- Pedestrian Collision: indicates whether the collision involved a pedestrian, Y or blank
+    bicycle_accident CHAR(1), -- Bicycle Collision: indicates whether the collision involved a bicycle, Y or blank
+    motorcycle_accident CHAR(1), -- Motorcycle Collision: indicates whether the collision involved a motorcycle, Y or blank
+    truck_accident CHAR(1), -- Truck Collision: indicates whether the collision involved a big truck, Y or blank
+    not_private_property CHAR(1), -- Not Private Property: indicates whether the collision occurred on private property, Y or blank
+    alcohol_involved CHAR(1), -- Alcohol Involved: indicates whether the collision involved a party that had been drinking, Y or blank
+    stwd_vehtype_at_fault CHAR(1), -- Statewide Vehicle Type At Fault: indicates the Statewide Vehicle Type of the party who is at fault, see Party folder Statewide Vehicle Type item (see lookup-tables/STWD_VEHTYPE_AT_FAULT.csv)
+    chp_vehtype_at_fault CHAR(2), -- CHP Vehicle Type At Fault: indicates the CHP Vehicle Type of the party who is at fault, see Party folder CHP Vehicle Type Towing item (see lookup-tables/CHP_VEHTYPE_AT_FAULT.csv)
+    count_severe_inj INTEGER, -- Severe Injury count: counts victims in the collision with degree of injury of 2, 0 to N for each collision
+    count_visible_inj INTEGER, -- Other Visible Injury count: counts victims in the collision with degree of injury of 3, 0 to N for each collision
+    count_complaint_pain INTEGER, -- Complaint of Pain Injury count: counts victims in the collision with degree of injury of 4, 0 to N for each collision
+    count_ped_killed INTEGER, -- Pedestrian Killed count: Counts the victims in the collision with party type of 2 and degree of injury is 1, 0 or 1 for each collision
+    count_ped_injured INTEGER, -- Pedestrian Injured count: Counts the victims in the collision with party type of 2 and degree of injury is 2, 3, or 4. 0 or 1 for each collision
+    count_bicyclist_killed INTEGER, -- Bicyclist Killed count: Counts the victims in the collision with party type of 4 and degree of injury is 1. 0 to N for each collision
+    count_bicyclist_injured INTEGER, -- Bicyclist Injured count: Counts the victims in the collision with party type of 4 and degree of injury is 2, 3, or 4. 0 to N for each collision
+    count_mc_killed INTEGER, -- Motorcyclist Killed count: counts victims in the collision with statewide vehicle type of C or O and degree of injury of 1. 0 to N for each collision
+    count_mc_injured INTEGER, -- Motorcyclist Injured count: counts victims in the collision with statewide vehicle type of C or O and degree of injury of 2, 3, or 4. 0 to N for each collision
+    primary_ramp VARCHAR2(2), -- Primary Ramp: NO-NB On Ramp, NF-NB Off Ramp, SO-SB On Ramp, SF-SB Off Ramp, EO-EB On Ramp, EF-EB Off Ramp, WO-WB On Ramp, WF-WB Off Ramp, To, From, Transition, Collector, Connector & blank (see lookup-tables/PRIMARY_RAMP.csv)
+    secondary_ramp, -- Same as above (see lookup-tables/PRIMARY_RAMP.csv)
+    latitude FLOAT,
+    longitude FLOAT,
+    PRIMARY KEY(case_id)
+    -- all foreign keys
+    FOREIGN KEY(day_of_week) REFERENCES day_of_week(id)
+    FOREIGN KEY(chp_shift) REFERENCES chp_shift(id)
+    FOREIGN KEY(population) REFERENCES population(id)
+    FOREIGN KEY(cnty_city_loc) REFERENCES cnty_city_loc(id)
+    FOREIGN KEY(special_cond) REFERENCES special_cond(id)
+    FOREIGN KEY(beat_type) REFERENCES beat_type(id)
+    FOREIGN KEY(chp_beat_type) REFERENCES chp_beat_type(id)
+    FOREIGN KEY(direction) REFERENCES direction(id)
+    FOREIGN KEY(weather_1) REFERENCES weather(id)
+    FOREIGN KEY(weather_2) REFERENCES weather(id)
+    FOREIGN KEY(location_type) REFERENCES location_type(id)
+    FOREIGN KEY(ramp_intersection) REFERENCES ramp_intersection(id)
+    FOREIGN KEY(side_of_hwy) REFERENCES side_of_hwy(id)
+    FOREIGN KEY(collision_severity) REFERENCES collision_severity(id)
+    FOREIGN KEY(primary_coll_factor) REFERENCES primary_coll_factor(id)
+    FOREIGN KEY(pcf_code_of_viol) REFERENCES pcf_code_of_viol(id)
+    FOREIGN KEY(pcf_viol_category) REFERENCES pcf_viol_category(id)
+    FOREIGN KEY(hit_and_run) REFERENCES hit_and_run(id)
+    FOREIGN KEY(type_of_collision) REFERENCES type_of_collision(id)
+    FOREIGN KEY(mviw) REFERENCES mviw(id)
+    FOREIGN KEY(ped_action) REFERENCES ped_action(id)
+    FOREIGN KEY(road_surface) REFERENCES road_surface(id)
+    FOREIGN KEY(road_cond_1) REFERENCES road_cond(id)
+    FOREIGN KEY(road_cond_2) REFERENCES road_cond(id)
+    FOREIGN KEY(lighting) REFERENCES lighting(id)
+    FOREIGN KEY(control_device) REFERENCES control_device(id)
+    FOREIGN KEY(stwd_vehtype_at_fault) REFERENCES stwd_vehtype_at_fault(id)
+    FOREIGN KEY(chp_vehtype_at_fault) REFERENCES chp_vehtype(id)
+    FOREIGN KEY(primary_ramp) REFERENCES ramp(id)
+    FOREIGN KEY(secondary_ramp) REFERENCES ramp(id)
+);
+
+CREATE VIEW IF NOT EXISTS collisions_view (
+    case_id,
+    address,
+    proc_date,
+    collision_datetime,
+    primary_rd,
+    secondary_rd,
+    state_route,
+    pedestrian_accident,
+    bicycle_accident,
+    number_killed,
+    number_injured,
+    party_count,
+    count_severe_inj,
+    count_visible_inj,
+    count_complaint_pain,
+    count_ped_killed,
+    count_ped_injured,
+    count_bicyclist_killed,
+    count_bicyclist_injured,
+    latitude,
+    longitude,
+    -- joined table names
+    day_name,
+    chp_shift_name,
+    population_name,
+    city_name,
+    special_cond_name,
+    beat_type_name,
+    chp_beat_type_name,
+    direction_name,
+    weather_1_name,
+    weather_2_name,
+    location_type_name,
+    ramp_intersection_name,
+    side_of_hwy_name,
+    collision_severity_name,
+    primary_coll_factor_name,
+    pcf_code_of_viol_name,
+    pcf_viol_category_name,
+    hit_and_run_name,
+    type_of_collision_name,
+    mviw_name,
+    ped_action_name,
+    road_surface_name,
+    road_cond_1_name,
+    road_cond_2_name,
+    lighting_name,
+    control_device_name,
+    stwd_vehtype_at_fault_name,
+    chp_vehtype_at_fault_name,
+    primary_ramp_name,
+    secondary_ramp_name,
+    corrected_primary_rd,
+    corrected_secondary_rd
+) AS SELECT 
+    c.case_id,
+    printf("%s %s%s, CA", c.primary_rd, iif(c.secondary_rd IS NOT NULL, printf("and %s ", c.secondary_rd), ""), cnty_city_loc.city),
+    printf("%s-%s-%s", substr(proc_date,1,4), substr(proc_date,5,2), substr(proc_date,7,2)),
+    printf("%s-%s-%sT%s:%s", substr(collision_date,1,4), substr(collision_date,5,2), substr(collision_date,7,2), substr(collision_time,1,2), substr(collision_time,3,2)),
+    c.primary_rd,
+    c.secondary_rd,
+    c.state_route,
+    c.pedestrian_accident,
+    c.bicycle_accident,
+    c.number_killed,
+    c.number_injured,
+    c.party_count,
+    c.count_severe_inj,
+    c.count_visible_inj,
+    c.count_complaint_pain,
+    c.count_ped_killed,
+    c.count_ped_injured,
+    c.count_bicyclist_killed,
+    c.count_bicyclist_injured,
+    latitude,
+    longitude * (-1),
+    -- joined table names
+    day_of_week.name,
+    chp_shift.name,
+    population.name,
+    cnty_city_loc.city,
+    special_cond.name,
+    beat_type.name,
+    chp_beat_type.name,
+    direction.name,
+    weather_1.name,
+    weather_2.name,
+    location_type.name,
+    ramp_intersection.name,
+    side_of_hwy.name,
+    collision_severity.name,
+    primary_coll_factor.name,
+    pcf_code_of_viol.name,
+    pcf_viol_category.name,
+    hit_and_run.name,
+    type_of_collision.name,
+    mviw.name,
+    ped_action.name,
+    road_surface.name,
+    road_cond_1.name,
+    road_cond_2.name,
+    lighting.name,
+    control_device.name,
+    stwd_vehtype_at_fault.name,
+    chp_vehtype.name,
+    primary_ramp.name,
+    secondary_ramp.name,
+    corrected_roads.primary_rd,
+    corrected_roads.secondary_rd
+FROM collisions AS c
+-- join all the foreign key tables
+LEFT JOIN day_of_week ON c.day_of_week = day_of_week.id
+LEFT JOIN chp_shift ON c.chp_shift = chp_shift.id
+LEFT JOIN population ON c.population = population.id
+LEFT JOIN cnty_city_loc ON c.cnty_city_loc = cnty_city_loc.id
+LEFT JOIN special_cond ON c.special_cond = special_cond.id
+LEFT JOIN beat_type ON c.beat_type = beat_type.id
+LEFT JOIN chp_beat_type ON c.chp_beat_type = chp_beat_type.id
+LEFT JOIN direction ON c.direction = direction.id
+LEFT JOIN weather AS weather_1 ON c.weather_1 = weather_1.id
+LEFT JOIN weather AS weather_2 ON c.weather_2 = weather_2.id
+LEFT JOIN location_type ON c.location_type = location_type.id
+LEFT JOIN ramp_intersection ON c.ramp_intersection = ramp_intersection.id
+LEFT JOIN side_of_hwy ON c.side_of_hwy = side_of_hwy.id
+LEFT JOIN collision_severity ON c.collision_severity = collision_severity.id
+LEFT JOIN primary_coll_factor ON c.primary_coll_factor = primary_coll_factor.id
+LEFT JOIN pcf_code_of_viol ON c.pcf_code_of_viol = pcf_code_of_viol.id
+LEFT JOIN pcf_viol_category ON c.pcf_viol_category = pcf_viol_category.id
+LEFT JOIN hit_and_run ON c.hit_and_run = hit_and_run.id
+LEFT JOIN type_of_collision ON c.type_of_collision = type_of_collision.id
+LEFT JOIN mviw ON c.mviw = mviw.id
+LEFT JOIN ped_action ON c.ped_action = ped_action.id
+LEFT JOIN road_surface ON c.road_surface = road_surface.id
+LEFT JOIN road_cond AS road_cond_1 ON c.road_cond_1 = road_cond_1.id
+LEFT JOIN road_cond AS road_cond_2 ON c.road_cond_2 = road_cond_2.id
+LEFT JOIN lighting ON c.lighting = lighting.id
+LEFT JOIN control_device ON c.control_device = control_device.id
+LEFT JOIN stwd_vehtype_at_fault ON c.stwd_vehtype_at_fault = stwd_vehtype_at_fault.id
+LEFT JOIN chp_vehtype ON c.chp_vehtype_at_fault = chp_vehtype.id
+LEFT JOIN ramp primary_ramp ON c.primary_ramp = primary_ramp.id
+LEFT JOIN ramp secondary_ramp ON c.secondary_ramp = secondary_ramp.id
+LEFT JOIN corrected_roads ON c.case_id = corrected_roads.case_id
+WHERE 
+c.cnty_city_loc IN ("0102", "0103") -- see lookup-tables/CNTY_CITY_LOC.csv
+;
+
+CREATE VIEW IF NOT EXISTS version_view (
+    first_proc_date,
+    last_proc_date,
+    first_collision_datetime,
+    last_collision_datetime
+) AS SELECT
+    (SELECT c.proc_date FROM collisions_view c ORDER BY c.proc_date LIMIT 1),
+    (SELECT c.proc_date FROM collisions_view c ORDER BY c.proc_date DESC LIMIT 1),
+    (SELECT c.collision_datetime FROM collisions_view c ORDER BY c.collision_datetime LIMIT 1),
+    (SELECT c.collision_datetime FROM collisions_view c ORDER BY c.collision_datetime DESC LIMIT 1);
+
+CREATE TABLE IF NOT EXISTS parties (
+    case_id VARCHAR2(19), -- Case Id: the unique identifier of the collision report (barcode beginning 2002; 19 digit code prior to 2002)
+    party_number INTEGER, -- Party Number: 1 to 999
+    party_type CHAR(1), -- Party Type (see lookup-tables/party-tables/PARTY_TYPE.csv)
+    at_fault CHAR(1), -- At Fault: indicates whether the party was at fault in the collision, Y
+    party_sex CHAR(1),-- Party Sex: the code of the sex of the party (see lookup-tables/party-tables/PARTY_SEX.csv)
+    party_age INTEGER, -- Party Age: the age of the party at the time of the collision, 0 to 100+ (0 & blank = Not Stated)
+    party_sobriety CHAR(1), -- Party Sobriety (see lookup-tables/party-tables/PARTY_SOBRIETY.csv)
+    party_drug_physical CHAR(1), -- Party Drug Physical (see lookup-tables/party-tables/PARTY_DRUG_PHYSICAL.csv)
+    dir_of_travel CHAR(1), -- Direction Of Travel (see lookup-tables/party-tables/DIRECTION_OF_TRAVEL.csv)
+    party_safety_equip_1 CHAR(1), -- Party Safety Equipment 1 (see lookup-tables/party-tables/PARTY_SAFETY_EQUIPMENT.csv)
+    party_safety_equip_2 CHAR(1), -- Party Safety Equipment 2: same as Party Safety Equipment 1 above (see lookup-tables/party-tables/PARTY_SAFETY_EQUIPMENT.csv)
+    finan_respons CHAR(1), -- Financial Responsibility (see lookup-tables/party-tables/FINANCIAL_RESPONSIBILITY.csv)
+    sp_info_1 CHAR(1), -- Special Information 1 (see lookup-tables/party-tables/SPECIAL_INFORMATION_1.csv)
+    sp_info_2 CHAR(1), -- Special Information 2 (see lookup-tables/party-tables/SPECIAL_INFORMATION_2.csv)
+    sp_info_3 CHAR(1), -- Special Information 3 (see lookup-tables/party-tables/SPECIAL_INFORMATION_3.csv)
+    oaf_violation_code CHAR(2), -- OAF Violation Code (see lookup-tables/party-tables/OAF_VIOLATION_CODE.csv)
+    oaf_viol_cat CHAR(2), -- OAF Violation Category (see, lookup-tables/party-tables/OAF_VIOLATION_CATEGORY.csv)
+    oaf_viol_section INTEGER, -- OAF Violation Section
+    oaf_violation_suffix CHAR(1), -- OAF Violation Suffix: Blank may appear if no suffix.
+    oaf_1 CHAR(1), -- Other Associated Factor 1 (see lookup-tables/party-tables/OTHER_ASSOCIATED_FACTOR.csv)
+    oaf_2 CHAR(1), -- Other Associated Factor 2: same as OAF 1 above (see lookup-tables/party-tables/OTHER_ASSOCIATED_FACTOR.csv)
+    party_number_killed INTEGER, -- Party Number Killed: counts victims in the party with degree of injury of 1. 0 to N for each party
+    party_number_injured INTEGER, -- Party Number Injured: counts victims in the party with degree of injury of 2, 3, or 4. 0 to N for each party
+    move_pre_acc CHAR(1), -- Movement Preceding Collision (see lookup-tables/party-tables/MOVEMENT_PRECEDING_COLLISION.csv)
+    vehicle_year INTEGER, -- Vehicle Year: the model year of the party's vehicle, 9999 or blank = not stated
+    vehicle_make VARCHAR2(50), -- Vehicle Make	Varchar2(50)	the full description of the make of the party's vehicle	
+    stwd_vehicle_type CHAR(1), -- Statewide Vehicle Type, TODO: is this STWD_VEHTYPE_AT_FAULT?
+    chp_veh_type_towing CHAR(2), -- CHP Vehicle Type Towing (see lookup-tables/party-tables/CHP_VEHICLE_TYPE_TOWING.csv)
+    chp_veh_type_towed CHAR(2), -- CHP Vehicle Type Towed (see lookup-tables/party-tables/CHP_VEHICLE_TYPE_TOWED.csv)
+    race CHAR(1), -- Party Race (see lookup-tables/party-tables/PARTY_RACE.csv)
+    inattention, -- Undocumented, unused?
+    special_info_f, -- Undocumented, unused?
+    special_info_g, -- Undocumented, unused?
+    PRIMARY KEY(case_id, party_number) -- Multiple parties in each case
+    -- add foreign keys
+    FOREIGN KEY(case_id) REFERENCES collisions(case_id)
+    FOREIGN KEY(party_type) REFERENCES party_type(id)
+    FOREIGN KEY(party_sex) REFERENCES party_sex(id)
+    FOREIGN KEY(party_sobriety) REFERENCES party_sobriety(id)
+    FOREIGN KEY(party_drug_physical) REFERENCES party_drug_physical(id)
+    FOREIGN KEY(dir_of_travel) REFERENCES dir_of_travel(id)
+    FOREIGN KEY(party_safety_equip_1) REFERENCES party_safety_equip(id)
+    FOREIGN KEY(party_safety_equip_2) REFERENCES party_safety_equip(id)
+    FOREIGN KEY(finan_respons) REFERENCES finan_respons(id)
+    FOREIGN KEY(sp_info_1) REFERENCES sp_info_1(id)
+    FOREIGN KEY(sp_info_2) REFERENCES sp_info_2(id)
+    FOREIGN KEY(sp_info_3) REFERENCES sp_info_3(id)
+    FOREIGN KEY(oaf_violation_code) REFERENCES oaf_violation_code(id)
+    FOREIGN KEY(oaf_viol_cat) REFERENCES oaf_viol_cat(id)
+    FOREIGN KEY(oaf_1) REFERENCES oaf(id)
+    FOREIGN KEY(oaf_2) REFERENCES oaf(id)
+    FOREIGN KEY(move_pre_acc) REFERENCES move_pre_acc(id)
+    FOREIGN KEY(chp_veh_type_towing) REFERENCES chp_vehtype(id)
+    FOREIGN KEY(chp_veh_type_towed) REFERENCES chp_vehtype(id)
+    FOREIGN KEY(race) REFERENCES race(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_parties_case_id ON parties(case_id);
+
+CREATE VIEW IF NOT EXISTS parties_view (
+    case_id,
+    party_number,
+    party_type,
+    at_fault,
+    party_age,
+    oaf_viol_section,
+    oaf_violation_suffix,
+    party_number_killed,
+    party_number_injured,
+    vehicle_year,
+    -- joined table names
+    party_type_name,
+    party_sex_name,
+    party_sobriety_name,
+    party_drug_physical_name,
+    dir_of_travel_name,
+    party_safety_equip_1_name,
+    party_safety_equip_2_name,
+    finan_respons_name,
+    sp_info_1_name,
+    sp_info_2_name,
+    sp_info_3_name,
+    oaf_violation_code_name,
+    oaf_viol_cat_name,
+    oaf_1_name,
+    oaf_2_name,
+    move_pre_acc_name,
+    chp_veh_type_towing_name,
+    chp_veh_type_towed_name,
+    race_name
+) AS SELECT 
+    p.case_id,
+    p.party_number,
+    p.party_type,
+    p.at_fault,
+    p.party_age,
+    p.oaf_viol_section,
+    p.oaf_violation_suffix,
+    p.party_number_killed,
+    p.party_number_injured,
+    p.vehicle_year,
+    -- joined table names
+    party_type.name,
+    party_sex.name,
+    party_sobriety.name,
+    party_drug_physical.name,
+    dir_of_travel.name,
+    party_safety_equip_1.name,
+    party_safety_equip_2.name,
+    finan_respons.name,
+    sp_info_1.name,
+    sp_info_2.name,
+    sp_info_3.name,
+    oaf_violation_code.name,
+    oaf_viol_cat.name,
+    oaf_1.name,
+    oaf_2.name,
+    move_pre_acc.name,
+    chp_veh_type_towing.name,
+    chp_veh_type_towed.name,
+    race.name
+FROM parties AS p
+-- join all the foreign key tables
+LEFT JOIN party_type ON p.party_type = party_type.id
+LEFT JOIN party_sex ON p.party_sex = party_sex.id
+LEFT JOIN party_sobriety ON p.party_sobriety = party_sobriety.id
+LEFT JOIN party_drug_physical ON p.party_drug_physical = party_drug_physical.id
+LEFT JOIN dir_of_travel ON p.dir_of_travel = dir_of_travel.id
+LEFT JOIN party_safety_equip party_safety_equip_1 ON p.party_safety_equip_1 = party_safety_equip_1.id
+LEFT JOIN party_safety_equip party_safety_equip_2 ON p.party_safety_equip_2 = party_safety_equip_2.id
+LEFT JOIN finan_respons ON p.finan_respons = finan_respons.id
+LEFT JOIN sp_info_1 ON p.sp_info_1 = sp_info_1.id
+LEFT JOIN sp_info_2 ON p.sp_info_2 = sp_info_2.id
+LEFT JOIN sp_info_3 ON p.sp_info_3 = sp_info_3.id
+LEFT JOIN oaf_violation_code ON p.oaf_violation_code = oaf_violation_code.id
+LEFT JOIN oaf_viol_cat ON p.oaf_viol_cat = oaf_viol_cat.id
+LEFT JOIN oaf oaf_1 ON p.oaf_1 = oaf_1.id
+LEFT JOIN oaf oaf_2 ON p.oaf_2 = oaf_2.id
+LEFT JOIN move_pre_acc ON p.move_pre_acc = move_pre_acc.id
+LEFT JOIN chp_vehtype chp_veh_type_towing ON p.chp_veh_type_towing = chp_veh_type_towing.id
+LEFT JOIN chp_vehtype chp_veh_type_towed ON p.chp_veh_type_towed = chp_veh_type_towed.id
+LEFT JOIN race ON p.race = race.id;
+
+CREATE TABLE IF NOT EXISTS victims (
+    case_id VARCHAR2(19), -- Case Id: the unique identifier of the collision report (barcode beginning 2002; 19 digit code prior to 2002)
+    party_number INTEGER, -- Party Number: 1 to 999
+    victim_role CHAR(1), -- Victim Role (see lookup-tables/victim-tables/VICTIM_ROLE.csv)
+    victim_sex CHAR(1), -- Victim Sex (see lookup-tables/victim-tables/VICTIM_SEX.csv)
+    victim_age INTEGER, -- Victim Age: the age of the victim at the time of the collision. 0 – 125, 998 – Not Stated, 999 – Fatal Fetus
+    victim_degree_of_injury CHAR(1), -- Victim Degree of Injury (see lookup-tables/victim-tables/VICTIM_DEGREE_OF_INJURY.csv)
+    victim_seating_position CHAR(1), -- Victim Seating Position (see lookup-tables/victim-tables/VICTIM_SEATING_POSITION.csv)
+    victim_safety_equip_1 CHAR(1), -- Victim Safety Equipment 1 (see lookup-tables/victim-tables/VICTIM_SAFETY_EQUIPMENT.csv)
+    victim_safety_equip_2 CHAR(1), -- Victim Safety Equipment 2, same as Victim Safety Equipment 1 above (eff. Jan 2002) (see lookup-tables/victim-tables/VICTIM_SAFETY_EQUIPMENT.csv)
+    victim_ejected CHAR(1), -- Victim Ejected (see lookup-tables/victim-tables/VICTIM_EJECTED.csv)
+    -- add foreign keys
+    FOREIGN KEY(case_id, party_number) REFERENCES parties(case_id, party_number)
+    FOREIGN KEY(victim_role) REFERENCES victim_role(id)
+    FOREIGN KEY(victim_sex) REFERENCES victim_sex(id)
+    FOREIGN KEY(victim_degree_of_injury) REFERENCES victim_degree_of_injury(id)
+    FOREIGN KEY(victim_seating_position) REFERENCES victim_seating_position(id)
+    FOREIGN KEY(victim_safety_equip_1) REFERENCES victim_safety_equip(id)
+    FOREIGN KEY(victim_safety_equip_2) REFERENCES victim_safety_equip(id)
+    FOREIGN KEY(victim_ejected) REFERENCES victim_ejected(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_victims_case_id ON victims(case_id);
+
+CREATE INDEX IF NOT EXISTS idx_victims_case_id_party_number ON victims(case_id, party_number);
+
+CREATE VIEW IF NOT EXISTS victims_view (
+    case_id,
+    party_number,
+    victim_age,
+    -- joined table names
+    victim_role_name,
+    victim_sex_name,
+    victim_degree_of_injury_name,
+    victim_seating_position_name,
+    victim_safety_equip_1_name,
+    victim_safety_equip_2_name,
+    victim_ejected_name
+) AS SELECT 
+    v.case_id,
+    v.party_number,
+    v.victim_age,
+    -- joined table names
+    victim_role.name,
+    victim_sex.name,
+    victim_degree_of_injury.name,
+    victim_seating_position.name,
+    victim_safety_equip_1.name,
+    victim_safety_equip_2.name,
+    victim_ejected.name
+FROM victims AS v
+-- join all the foreign key tables
+LEFT JOIN victim_role ON v.victim_role = victim_role.id
+LEFT JOIN victim_sex ON v.victim_sex = victim_sex.id
+LEFT JOIN victim_degree_of_injury ON v.victim_degree_of_injury = victim_degree_of_injury.id
+LEFT JOIN victim_seating_position ON v.victim_seating_position = victim_seating_position.id
+LEFT JOIN victim_safety_equip victim_safety_equip_1 ON v.victim_safety_equip_1 = victim_safety_equip_1.id
+LEFT JOIN victim_safety_equip victim_safety_equip_2 ON v.victim_safety_equip_2 = victim_safety_equip_2.id
+LEFT JOIN victim_ejected ON v.victim_ejected = victim_ejected.id;
+
+CREATE TABLE IF NOT EXISTS intersection_improvements (
+    id INTEGER PRIMARY KEY,
+    primary_rd VARCHAR2(50),   -- primary road where the infrastructure was installed
+    secondary_rd VARCHAR2(50), -- secondary or cross road of the intersection
+    date_completed TEXT,       -- date, YYYY-MM-DD, when the infrastructure was completed
+    improvement_type INTEGER,  -- type of intersection installed
+    FOREIGN KEY(improvement_type) REFERENCES improvement_types(id)
+);
+
+CREATE VIEW IF NOT EXISTS intersection_improvements_view (
+    id,
+    primary_rd,
+    secondary_rd,
+    date_completed,
+    improvement_type,
+    -- joined table names
+    improvement_name
+) AS SELECT 
+    i.id,
+    i.primary_rd,
+    i.secondary_rd,
+    i.date_completed,
+    i.improvement_type,
+    -- joined table names
+    improvement_types.name
+FROM intersection_improvements AS i
+-- join all the foreign key tables
+LEFT JOIN improvement_types ON i.improvement_type = improvement_types.id;
+
+CREATE VIEW IF NOT EXISTS intersection_performance_view (
+    id,
+    primary_rd,
+    secondary_rd,
+    date_completed,
+    improvement_type,
+    -- joined table names
+    improvement_name,
+    case_id,
+    party_count,
+    before_improvement,
+    collision_datetime,
+    pedestrian_accident,
+    bicycle_accident,
+    number_killed,
+    number_injured,
+    count_ped_killed,
+    count_ped_injured,
+    count_bicyclist_killed,
+    count_bicyclist_injured
+) AS SELECT 
+    i.id,
+    i.primary_rd,
+    i.secondary_rd,
+    i.date_completed,
+    i.improvement_type,
+    -- joined table names
+    improvement_types.name,
+    c.case_id,
+    c.party_count,
+    c.collision_datetime < i.date_completed,
+    c.collision_datetime,
+    c.pedestrian_accident,
+    c.bicycle_accident,
+    c.number_killed,
+    c.number_injured,
+    c.count_ped_killed,
+    c.count_ped_injured,
+    c.count_bicyclist_killed,
+    c.count_bicyclist_injured
+FROM intersection_improvements AS i
+-- join all the foreign key tables
+LEFT JOIN improvement_types ON i.improvement_type = improvement_types.id
+LEFT JOIN collisions_view as c ON (c.corrected_primary_rd = i.primary_rd AND c.corrected_secondary_rd = i.secondary_rd)
+                               OR (c.corrected_secondary_rd = i.primary_rd AND c.corrected_primary_rd = i.secondary_rd);
+
+CREATE TABLE IF NOT EXISTS street_improvements (
+    id INTEGER PRIMARY KEY,
+    primary_rd VARCHAR2(50),   -- primary road where the infrastructure was installed
+    start_intersection VARCHAR2(50), -- beginning, first cross street, of the improvements
+    end_intersection VARCHAR2(50), -- end, last cross street, of the improvements
+    date_completed TEXT,       -- date, YYYY-MM-DD, when the infrastructure was completed
+    improvement_type INTEGER,  -- type of intersection installed
+    ca_bike_lane_type INTEGER, -- California classification of bike infrastructure, 0 for none
+    FOREIGN KEY(improvement_type) REFERENCES improvement_types(id)
+    FOREIGN KEY(ca_bike_lane_type) REFERENCES ca_bike_lane_types(id)
+);
+
+CREATE VIEW IF NOT EXISTS street_improvements_view (
+    id,
+    primary_rd,
+    start_intersection,
+    end_intersection,
+    date_completed,
+    improvement_type,
+    ca_bike_lane_type,
+    -- joined table names
+    improvement_name,
+    ca_bike_lane_name
+) AS SELECT 
+    s.id,
+    s.primary_rd,
+    s.start_intersection,
+    s.end_intersection,
+    s.date_completed,
+    s.improvement_type,
+    s.ca_bike_lane_type,
+    -- joined table names
+    improvement_types.name,
+    ca_bike_lane_types.name
+FROM street_improvements AS s
+-- join all the foreign key tables
+LEFT JOIN improvement_types ON s.improvement_type = improvement_types.id
+LEFT JOIN ca_bike_lane_types ON s.ca_bike_lane_type = ca_bike_lane_types.id;
+
+CREATE TABLE IF NOT EXISTS normalized_roads (
+    case_id VARCHAR2(19), -- matches the case_id in collisions
+    primary_rd VARCHAR2(50), -- Primary Road
+    primary_rd_address VARCHAR2(10), -- address if one exists on the road where the collision occured
+    primary_rd_block VARCHAR2(10), -- block (i.e. address at the corner) on the road where the collision occured
+    primary_rd_direction VARCHAR2(10), -- direction of travel when the collision occured
+    secondary_rd VARCHAR2(50), -- Secondary Road 
+    secondary_rd_address VARCHAR2(10), -- address if one exists on the road where the collision occured
+    secondary_rd_block VARCHAR2(10), -- block (i.e. address at the corner) on the road where the collision occured
+    seconardy_rd_direction VARCHAR2(10), -- direction of travel when the collision occured
+    PRIMARY KEY(case_id)
+);
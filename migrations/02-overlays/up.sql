@@ -0,0 +1,167 @@
+-- berkeley-tables became the "berkeley" overlay, all the overlay tables are tagged with the name of their overlay
+
+-- the views are recreated after the tables are changed
+DROP VIEW intersection_performance_view;
+DROP VIEW intersection_improvements_view;
+DROP VIEW street_improvements_view;
+
+-- berkeley_road_typos is now road_typos
+CREATE TABLE road_typos (correct_rd VARCHAR(256), normalized_rd VARCHAR(256), overlay VARCHAR(64));
+INSERT INTO road_typos (correct_rd, normalized_rd, overlay) SELECT correct_rd, normalized_rd, 'berkeley' FROM berkeley_road_typos;
+DROP TABLE berkeley_road_typos;
+
+-- corrected_roads is keyed by overlay and case, so that overlays with overlapping jurisdictions each keep their own
+--   corrections. SQLite can't change the primary key of a table, so it is recreated. collisions_view is recreated
+--   for the new key in migrations.rs, it is created from the jurisdictions in Schemas.toml
+CREATE TEMP TABLE corrected_roads_backup AS SELECT * FROM corrected_roads;
+DROP TABLE corrected_roads;
+CREATE TABLE corrected_roads (
+    case_id VARCHAR2(19), -- matches the case_id in collisions
+    primary_rd VARCHAR2(50), -- Primary Road
+    secondary_rd VARCHAR2(50), -- Secondary Road 
+    overlay VARCHAR(64), -- name of the overlay the correction comes from
+    PRIMARY KEY(overlay, case_id)
+);
+CREATE INDEX idx_corrected_roads_case_id ON corrected_roads(case_id);
+INSERT INTO corrected_roads (case_id, primary_rd, secondary_rd, overlay)
+    SELECT case_id, primary_rd, secondary_rd, 'berkeley' FROM temp.corrected_roads_backup;
+DROP TABLE temp.corrected_roads_backup;
+
+-- the improvement ids are now only unique within each overlay
+CREATE TABLE intersection_improvements_overlay (
+    id INTEGER,
+    primary_rd VARCHAR2(50),   -- primary road where the infrastructure was installed
+    secondary_rd VARCHAR2(50), -- secondary or cross road of the intersection
+    date_completed TEXT,       -- date, YYYY-MM-DD, when the infrastructure was completed
+    improvement_type INTEGER,  -- type of intersection installed
+    overlay VARCHAR(64),       -- name of the overlay the improvement comes from
+    PRIMARY KEY(overlay, id)
+    FOREIGN KEY(improvement_type) REFERENCES improvement_types(id)
+);
+INSERT INTO intersection_improvements_overlay (id, primary_rd, secondary_rd, date_completed, improvement_type, overlay)
+    SELECT id, primary_rd, secondary_rd, date_completed, improvement_type, 'berkeley' FROM intersection_improvements;
+DROP TABLE intersection_improvements;
+ALTER TABLE intersection_improvements_overlay RENAME TO intersection_improvements;
+
+CREATE TABLE street_improvements_overlay (
+    id INTEGER,
+    primary_rd VARCHAR2(50),   -- primary road where the infrastructure was installed
+    start_intersection VARCHAR2(50), -- beginning, first cross street, of the improvements
+    end_intersection VARCHAR2(50), -- end, last cross street, of the improvements
+    date_completed TEXT,       -- date, YYYY-MM-DD, when the infrastructure was completed
+    improvement_type INTEGER,  -- type of intersection installed
+    ca_bike_lane_type INTEGER, -- California classification of bike infrastructure, 0 for none
+    overlay VARCHAR(64),       -- name of the overlay the improvement comes from
+    PRIMARY KEY(overlay, id)
+    FOREIGN KEY(improvement_type) REFERENCES improvement_types(id)
+    FOREIGN KEY(ca_bike_lane_type) REFERENCES ca_bike_lane_types(id)
+);
+INSERT INTO street_improvements_overlay (id, primary_rd, start_intersection, end_intersection, date_completed, improvement_type, ca_bike_lane_type, overlay)
+    SELECT id, primary_rd, start_intersection, end_intersection, date_completed, improvement_type, ca_bike_lane_type, 'berkeley' FROM street_improvements;
+DROP TABLE street_improvements;
+ALTER TABLE street_improvements_overlay RENAME TO street_improvements;
+
+CREATE VIEW intersection_improvements_view (
+    id,
+    primary_rd,
+    secondary_rd,
+    date_completed,
+    improvement_type,
+    overlay,
+    -- joined table names
+    improvement_name
+) AS SELECT 
+    i.id,
+    i.primary_rd,
+    i.secondary_rd,
+    i.date_completed,
+    i.improvement_type,
+    i.overlay,
+    -- joined table names
+    improvement_types.name
+FROM intersection_improvements AS i
+-- join all the foreign key tables
+LEFT JOIN improvement_types ON i.improvement_type = improvement_types.id
+;
+
+CREATE VIEW intersection_performance_view (
+    id,
+    primary_rd,
+    secondary_rd,
+    date_completed,
+    improvement_type,
+    overlay,
+    -- joined table names
+    improvement_name,
+    case_id,
+    party_count,
+    before_improvement,
+    collision_datetime,
+    pedestrian_accident,
+    bicycle_accident,
+    number_killed,
+    number_injured,
+    count_ped_killed,
+    count_ped_injured,
+    count_bicyclist_killed,
+    count_bicyclist_injured
+) AS SELECT 
+    i.id,
+    i.primary_rd,
+    i.secondary_rd,
+    i.date_completed,
+    i.improvement_type,
+    i.overlay,
+    -- joined table names
+    improvement_types.name,
+    c.case_id,
+    c.party_count,
+    c.collision_datetime < i.date_completed,
+    c.collision_datetime,
+    c.pedestrian_accident,
+    c.bicycle_accident,
+    c.number_killed,
+    c.number_injured,
+    c.count_ped_killed,
+    c.count_ped_injured,
+    c.count_bicyclist_killed,
+    c.count_bicyclist_injured
+FROM intersection_improvements AS i
+-- join all the foreign key tables
+LEFT JOIN improvement_types ON i.improvement_type = improvement_types.id
+-- only collisions with roads corrected by the same overlay as the improvement
+LEFT JOIN (collisions_view AS c JOIN corrected_roads AS cr ON c.case_id = cr.case_id)
+    ON cr.overlay = i.overlay
+    AND ((c.corrected_primary_rd = i.primary_rd AND c.corrected_secondary_rd = i.secondary_rd)
+      OR (c.corrected_secondary_rd = i.primary_rd AND c.corrected_primary_rd = i.secondary_rd))
+;
+
+CREATE VIEW street_improvements_view (
+    id,
+    primary_rd,
+    start_intersection,
+    end_intersection,
+    date_completed,
+    improvement_type,
+    ca_bike_lane_type,
+    overlay,
+    -- joined table names
+    improvement_name,
+    ca_bike_lane_name
+) AS SELECT 
+    s.id,
+    s.primary_rd,
+    s.start_intersection,
+    s.end_intersection,
+    s.date_completed,
+    s.improvement_type,
+    s.ca_bike_lane_type,
+    s.overlay,
+    -- joined table names
+    improvement_types.name,
+    ca_bike_lane_types.name
+FROM street_improvements AS s
+-- join all the foreign key tables
+LEFT JOIN improvement_types ON s.improvement_type = improvement_types.id
+LEFT JOIN ca_bike_lane_types ON s.ca_bike_lane_type = ca_bike_lane_types.id
+;
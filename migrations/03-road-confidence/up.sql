@@ -0,0 +1,5 @@
+-- corrected roads record how confident the correction is, the existing corrections were all exact
+ALTER TABLE corrected_roads ADD COLUMN primary_rd_confidence REAL;
+ALTER TABLE corrected_roads ADD COLUMN secondary_rd_confidence REAL;
+UPDATE corrected_roads SET primary_rd_confidence = 1 WHERE primary_rd IS NOT NULL AND primary_rd != '';
+UPDATE corrected_roads SET secondary_rd_confidence = 1 WHERE secondary_rd IS NOT NULL AND secondary_rd != '';
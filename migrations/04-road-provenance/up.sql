@@ -0,0 +1,88 @@
+-- corrected roads record where each correction came from and the road as reported. The source of the existing
+--   exact corrections is derived again, they are verified when the reported road is already a known road, typos
+--   when the normalized road is in road_typos, and only manual when they differ from both
+DROP VIEW intersection_performance_view;
+
+ALTER TABLE corrected_roads ADD COLUMN primary_rd_source VARCHAR(16);
+ALTER TABLE corrected_roads ADD COLUMN secondary_rd_source VARCHAR(16);
+ALTER TABLE corrected_roads ADD COLUMN original_primary_rd VARCHAR2(50);
+ALTER TABLE corrected_roads ADD COLUMN original_secondary_rd VARCHAR2(50);
+UPDATE corrected_roads SET primary_rd_source = CASE
+    WHEN primary_rd IS NULL OR primary_rd = '' THEN 'unresolved'
+    WHEN primary_rd_confidence < 1 THEN 'fuzzy'
+    WHEN primary_rd IN (SELECT c.primary_rd FROM collisions AS c WHERE c.case_id = corrected_roads.case_id)
+        AND primary_rd IN (SELECT t.correct_rd FROM road_typos AS t WHERE t.overlay = corrected_roads.overlay) THEN 'verified'
+    WHEN primary_rd IN (SELECT t.correct_rd FROM road_typos AS t JOIN normalized_roads AS n ON t.normalized_rd = n.primary_rd
+        WHERE n.case_id = corrected_roads.case_id AND t.overlay = corrected_roads.overlay) THEN 'typo'
+    ELSE 'manual' END;
+UPDATE corrected_roads SET secondary_rd_source = CASE
+    WHEN secondary_rd IS NULL OR secondary_rd = '' THEN 'unresolved'
+    WHEN secondary_rd_confidence < 1 THEN 'fuzzy'
+    WHEN secondary_rd IN (SELECT c.secondary_rd FROM collisions AS c WHERE c.case_id = corrected_roads.case_id)
+        AND secondary_rd IN (SELECT t.correct_rd FROM road_typos AS t WHERE t.overlay = corrected_roads.overlay) THEN 'verified'
+    WHEN secondary_rd IN (SELECT t.correct_rd FROM road_typos AS t JOIN normalized_roads AS n ON t.normalized_rd = n.secondary_rd
+        WHERE n.case_id = corrected_roads.case_id AND t.overlay = corrected_roads.overlay) THEN 'typo'
+    ELSE 'manual' END;
+UPDATE corrected_roads SET
+    original_primary_rd = (SELECT c.primary_rd FROM collisions AS c WHERE c.case_id = corrected_roads.case_id),
+    original_secondary_rd = (SELECT c.secondary_rd FROM collisions AS c WHERE c.case_id = corrected_roads.case_id);
+
+CREATE VIEW intersection_performance_view (
+    id,
+    primary_rd,
+    secondary_rd,
+    date_completed,
+    improvement_type,
+    overlay,
+    -- joined table names
+    improvement_name,
+    case_id,
+    party_count,
+    before_improvement,
+    collision_datetime,
+    pedestrian_accident,
+    bicycle_accident,
+    number_killed,
+    number_injured,
+    count_ped_killed,
+    count_ped_injured,
+    count_bicyclist_killed,
+    count_bicyclist_injured,
+    -- provenance of the collision's corrected roads
+    primary_rd_source,
+    secondary_rd_source,
+    road_confidence
+) AS SELECT 
+    i.id,
+    i.primary_rd,
+    i.secondary_rd,
+    i.date_completed,
+    i.improvement_type,
+    i.overlay,
+    -- joined table names
+    improvement_types.name,
+    c.case_id,
+    c.party_count,
+    c.collision_datetime < i.date_completed,
+    c.collision_datetime,
+    c.pedestrian_accident,
+    c.bicycle_accident,
+    c.number_killed,
+    c.number_injured,
+    c.count_ped_killed,
+    c.count_ped_injured,
+    c.count_bicyclist_killed,
+    c.count_bicyclist_injured,
+    cr.primary_rd_source,
+    cr.secondary_rd_source,
+    min(cr.primary_rd_confidence, cr.secondary_rd_confidence)
+FROM intersection_improvements AS i
+-- join all the foreign key tables
+LEFT JOIN improvement_types ON i.improvement_type = improvement_types.id
+-- only collisions with roads corrected by the same overlay as the improvement
+LEFT JOIN (collisions_view AS c JOIN corrected_roads AS cr ON c.case_id = cr.case_id)
+    ON cr.overlay = i.overlay
+    AND ((c.corrected_primary_rd = i.primary_rd AND c.corrected_secondary_rd = i.secondary_rd)
+      OR (c.corrected_secondary_rd = i.primary_rd AND c.corrected_primary_rd = i.secondary_rd))
+;
+
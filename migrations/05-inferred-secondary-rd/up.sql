@@ -0,0 +1,4 @@
+-- missing secondary roads are taken from primary roads which describe two roads, the existing normalized roads
+--   were never inferred, `roads fix` reruns the normalization
+ALTER TABLE normalized_roads ADD COLUMN secondary_rd_inferred BOOLEAN;
+UPDATE normalized_roads SET secondary_rd_inferred = 0;
@@ -0,0 +1,43 @@
+-- where each collision occurred, at an intersection or midblock along the primary road. Midblock collisions are
+--   `distance` feet in `direction` from the secondary road, e.g. 150 ft north of CEDAR ST on SHATTUCK AVE
+CREATE VIEW collision_locations_view (
+    case_id,
+    location_type,
+    on_rd,
+    from_rd,
+    distance,
+    direction,
+    direction_name,
+    description
+) AS SELECT
+    l.case_id,
+    l.location_type,
+    l.on_rd,
+    l.from_rd,
+    l.distance,
+    l.direction,
+    l.direction_name,
+    CASE l.location_type
+        WHEN 'intersection' THEN printf("%s and %s", l.on_rd, l.from_rd)
+        WHEN 'midblock' THEN printf("%g ft %s of %s on %s", l.distance, lower(l.direction_name), l.from_rd, l.on_rd)
+        ELSE l.on_rd
+    END
+FROM (
+    SELECT
+        c.case_id,
+        CASE
+            WHEN c.intersection = 'Y' THEN 'intersection'
+            WHEN c.intersection IS NOT 'N' AND c.distance = 0 THEN 'intersection'
+            WHEN c.distance > 0 AND direction.id IN ('N', 'E', 'S', 'W') THEN 'midblock'
+            ELSE 'unknown'
+        END AS location_type,
+        coalesce(cv.corrected_primary_rd, trim(c.primary_rd)) AS on_rd,
+        coalesce(cv.corrected_secondary_rd, trim(c.secondary_rd)) AS from_rd,
+        c.distance,
+        c.direction,
+        direction.name AS direction_name
+    FROM collisions AS c
+    JOIN collisions_view AS cv ON cv.case_id = c.case_id
+    LEFT JOIN direction ON c.direction = direction.id
+) AS l
+;
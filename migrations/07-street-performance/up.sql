@@ -0,0 +1,204 @@
+-- street_performance_view relates collisions to the stretch of street_improvements, using the ordered cross streets
+--   of each corridor in the new corridor_cross_streets overlay table
+
+-- collision_locations_view no longer joins collisions_view, it is recreated with the corrected roads joined directly
+DROP VIEW collision_locations_view;
+
+-- where each collision occurred, at an intersection or midblock along the primary road. Midblock collisions are
+--   `distance` feet in `direction` from the secondary road, e.g. 150 ft north of CEDAR ST on SHATTUCK AVE
+CREATE VIEW collision_locations_view (
+    case_id,
+    location_type,
+    on_rd,
+    from_rd,
+    distance,
+    direction,
+    direction_name,
+    description
+) AS SELECT
+    l.case_id,
+    l.location_type,
+    l.on_rd,
+    l.from_rd,
+    l.distance,
+    l.direction,
+    l.direction_name,
+    CASE l.location_type
+        WHEN 'intersection' THEN printf("%s and %s", l.on_rd, l.from_rd)
+        WHEN 'midblock' THEN printf("%g ft %s of %s on %s", l.distance, lower(l.direction_name), l.from_rd, l.on_rd)
+        ELSE l.on_rd
+    END
+FROM (
+    SELECT
+        c.case_id,
+        CASE
+            WHEN c.intersection = 'Y' THEN 'intersection'
+            WHEN c.intersection IS NOT 'N' AND c.distance = 0 THEN 'intersection'
+            WHEN c.distance > 0 AND direction.id IN ('N', 'E', 'S', 'W') THEN 'midblock'
+            ELSE 'unknown'
+        END AS location_type,
+        coalesce(cr.primary_rd, trim(c.primary_rd)) AS on_rd,
+        coalesce(cr.secondary_rd, trim(c.secondary_rd)) AS from_rd,
+        c.distance,
+        c.direction,
+        direction.name AS direction_name
+    FROM collisions AS c
+    -- the corrected roads are joined directly rather than through collisions_view, so that the views built on this one
+    --   stay under SQLite's limit of 64 tables in a join. It is the same correction as in collisions_view
+    LEFT JOIN corrected_roads AS cr ON cr.case_id = c.case_id
+        AND cr.overlay = (SELECT min(o.overlay) FROM corrected_roads AS o WHERE o.case_id = c.case_id)
+    LEFT JOIN direction ON c.direction = direction.id
+) AS l
+;
+
+-- view of the data included in this DB
+
+-- table of the cross streets along each corridor with street improvements, in order from one end of the corridor
+--   to the other, so that collisions can be placed between two cross streets
+CREATE TABLE corridor_cross_streets (
+    corridor_rd VARCHAR2(50), -- road of the corridor, as in street_improvements.primary_rd
+    position INTEGER,         -- order of the cross street along the corridor, starting from 1
+    cross_rd VARCHAR2(50),    -- cross street, as in street_improvements.start_intersection and end_intersection
+    heading CHAR(1),          -- direction, N, E, S or W, from the cross street to the next one, empty for the last
+    overlay VARCHAR(64),      -- name of the overlay the corridor comes from
+    PRIMARY KEY(overlay, corridor_rd, position)
+);
+
+-- collisions on the corridors, placed between the positions of two cross streets. Collisions at an intersection
+--   with a cross street are at its position, midblock collisions are between the cross street they are measured
+--   from and the next cross street in their direction
+CREATE VIEW corridor_collisions_view (
+    overlay,
+    corridor_rd,
+    case_id,
+    location_type,
+    from_position,
+    to_position
+) AS SELECT
+    x.overlay,
+    x.corridor_rd,
+    l.case_id,
+    l.location_type,
+    CASE
+        WHEN l.location_type = 'intersection' OR l.direction = x.heading THEN x.position
+        ELSE x.position - 1
+    END,
+    CASE
+        WHEN l.location_type = 'intersection' OR l.direction IS NOT x.heading THEN x.position
+        ELSE x.position + 1
+    END
+FROM corridor_cross_streets AS x
+-- only collisions with roads corrected by the same overlay as the corridor
+JOIN (collision_locations_view AS l JOIN corrected_roads AS cr ON l.case_id = cr.case_id)
+    ON cr.overlay = x.overlay
+    AND ((l.on_rd = x.corridor_rd AND l.from_rd = x.cross_rd)
+      OR (l.location_type = 'intersection' AND l.from_rd = x.corridor_rd AND l.on_rd = x.cross_rd))
+WHERE l.location_type IN ('intersection', 'midblock')
+;
+
+-- the cross streets of the berkeley overlay, as in berkeley-tables/CORRIDOR_CROSS_STREETS.csv, other overlays are
+--   loaded on the next build
+INSERT INTO corridor_cross_streets (corridor_rd, position, cross_rd, heading, overlay)
+    SELECT column1, column2, column3, column4, 'berkeley' FROM (VALUES
+    ('MONTEREY AVE', 1, 'HOPKINS ST', 'N'),
+    ('MONTEREY AVE', 2, 'COLUSA AVE', NULL),
+    ('BANCROFT WAY', 1, 'TELEGRAPH AVE', 'W'),
+    ('BANCROFT WAY', 2, 'DANA ST', 'W'),
+    ('BANCROFT WAY', 3, 'ELLSWORTH ST', 'W'),
+    ('BANCROFT WAY', 4, 'FULTON ST', 'W'),
+    ('BANCROFT WAY', 5, 'SHATTUCK AVE', 'W'),
+    ('BANCROFT WAY', 6, 'MILVIA ST', NULL),
+    ('FULTON ST', 1, 'KITTREDGE ST', 'S'),
+    ('FULTON ST', 2, 'BANCROFT WAY', 'S'),
+    ('FULTON ST', 3, 'DURANT AVE', 'S'),
+    ('FULTON ST', 4, 'CHANNING WAY', NULL),
+    ('HEARST AVE', 1, 'MILVIA ST', 'E'),
+    ('HEARST AVE', 2, 'SHATTUCK AVE', 'E'),
+    ('HEARST AVE', 3, 'WALNUT ST', 'E'),
+    ('HEARST AVE', 4, 'OXFORD ST', 'E'),
+    ('HEARST AVE', 5, 'ARCH ST', 'E'),
+    ('HEARST AVE', 6, 'EUCLID AVE', 'E'),
+    ('HEARST AVE', 7, 'LE ROY AVE', 'E'),
+    ('HEARST AVE', 8, 'LE CONTE AVE', NULL),
+    ('MILVIA ST', 1, 'HEARST AVE', 'S'),
+    ('MILVIA ST', 2, 'BERKELEY WAY', 'S'),
+    ('MILVIA ST', 3, 'UNIVERSITY AVE', 'S'),
+    ('MILVIA ST', 4, 'ADDISON ST', 'S'),
+    ('MILVIA ST', 5, 'CENTER ST', 'S'),
+    ('MILVIA ST', 6, 'ALLSTON WAY', 'S'),
+    ('MILVIA ST', 7, 'KITTREDGE ST', 'S'),
+    ('MILVIA ST', 8, 'BANCROFT WAY', 'S'),
+    ('MILVIA ST', 9, 'DURANT AVE', 'S'),
+    ('MILVIA ST', 10, 'CHANNING WAY', 'S'),
+    ('MILVIA ST', 11, 'DWIGHT WAY', 'S'),
+    ('MILVIA ST', 12, 'BLAKE ST', NULL)
+    )
+    WHERE EXISTS (SELECT 1 FROM street_improvements WHERE overlay = 'berkeley');
+
+-- collisions on the improved stretch of each street, between the positions of its start and end intersections in
+--   corridor_cross_streets, including the collisions at the start and end intersections
+CREATE VIEW street_performance_view (
+    id,
+    primary_rd,
+    start_intersection,
+    end_intersection,
+    date_completed,
+    improvement_type,
+    ca_bike_lane_type,
+    overlay,
+    -- joined table names
+    improvement_name,
+    ca_bike_lane_name,
+    case_id,
+    location_type,
+    party_count,
+    before_improvement,
+    collision_datetime,
+    pedestrian_accident,
+    bicycle_accident,
+    number_killed,
+    number_injured,
+    count_ped_killed,
+    count_ped_injured,
+    count_bicyclist_killed,
+    count_bicyclist_injured
+) AS SELECT
+    s.id,
+    s.primary_rd,
+    s.start_intersection,
+    s.end_intersection,
+    s.date_completed,
+    s.improvement_type,
+    s.ca_bike_lane_type,
+    s.overlay,
+    -- joined table names
+    improvement_types.name,
+    ca_bike_lane_types.name,
+    c.case_id,
+    cc.location_type,
+    c.party_count,
+    c.collision_datetime < s.date_completed,
+    c.collision_datetime,
+    c.pedestrian_accident,
+    c.bicycle_accident,
+    c.number_killed,
+    c.number_injured,
+    c.count_ped_killed,
+    c.count_ped_injured,
+    c.count_bicyclist_killed,
+    c.count_bicyclist_injured
+FROM street_improvements AS s
+-- join all the foreign key tables
+LEFT JOIN improvement_types ON s.improvement_type = improvement_types.id
+LEFT JOIN ca_bike_lane_types ON s.ca_bike_lane_type = ca_bike_lane_types.id
+-- positions of the start and end of the improvements along the corridor
+LEFT JOIN corridor_cross_streets AS start_x
+    ON start_x.overlay = s.overlay AND start_x.corridor_rd = s.primary_rd AND start_x.cross_rd = s.start_intersection
+LEFT JOIN corridor_cross_streets AS end_x
+    ON end_x.overlay = s.overlay AND end_x.corridor_rd = s.primary_rd AND end_x.cross_rd = s.end_intersection
+LEFT JOIN (corridor_collisions_view AS cc JOIN collisions_view AS c ON cc.case_id = c.case_id)
+    ON cc.overlay = s.overlay
+    AND cc.corridor_rd = s.primary_rd
+    AND cc.from_position >= min(start_x.position, end_x.position)
+    AND cc.to_position <= max(start_x.position, end_x.position)
+;
@@ -0,0 +1,16 @@
+-- collision_points is a new R*Tree spatial index of the collisions, filled from the existing collisions
+
+-- spatial index of the collisions with a latitude and longitude, so that the collisions near a point can be found
+--   without scanning all of them. The id is the rowid of the collision in collisions, and the longitude is negated
+--   as in collisions_view. The R*Tree stores 32 bit floats, so the matches are refined against the collisions
+CREATE VIRTUAL TABLE collision_points USING rtree(
+    id,
+    min_latitude,
+    max_latitude,
+    min_longitude,
+    max_longitude
+);
+
+INSERT INTO collision_points (id, min_latitude, max_latitude, min_longitude, max_longitude)
+    SELECT rowid, latitude, latitude, -longitude, -longitude FROM collisions
+    WHERE latitude != 0 AND longitude != 0;
@@ -0,0 +1,72 @@
+-- collisions without a reported latitude and longitude are geocoded from the new intersections overlay table, a
+--   gazetteer of the intersections of the corrected roads. The coordinates of every located collision are in the new
+--   collision_coordinates table, only the reported ones are filled here, `roads fix` reruns the geocoding
+
+-- gazetteer of the intersections of the overlay's roads, to geocode the collisions without a reported latitude and
+--   longitude. The roads are the corrected road names, and are matched in either order
+CREATE TABLE intersections (
+    primary_rd VARCHAR2(50),   -- one of the roads, as in corrected_roads
+    secondary_rd VARCHAR2(50), -- the cross road, as in corrected_roads
+    latitude FLOAT,            -- center of the intersection
+    longitude FLOAT,           -- center of the intersection, negative as in collisions_view
+    overlay VARCHAR(64),       -- name of the overlay the intersection comes from
+    PRIMARY KEY(overlay, primary_rd, secondary_rd)
+);
+
+-- the intersections of the berkeley overlay, as in berkeley-tables/INTERSECTIONS.csv, other overlays are loaded on
+--   the next build
+INSERT INTO intersections (primary_rd, secondary_rd, latitude, longitude, overlay)
+    SELECT column1, column2, column3, column4, 'berkeley' FROM (VALUES
+    ('ADELINE ST', 'OREGON ST', 37.85746, -122.26809),
+    ('ADELINE ST', 'STUART ST', 37.85842, -122.26773),
+    ('ALCATRAZ AVE', 'SACRAMENTO ST', 37.8479, -122.27774),
+    ('ASHBY AVE', 'DOHR ST', 37.85344, -122.28039),
+    ('ASHBY AVE', 'MARTIN LUTHER KING JR WAY', 37.85432, -122.27115),
+    ('ASHBY AVE', 'SAN PABLO AVE', 37.85214, -122.28668),
+    ('ASHBY AVE', 'SHATTUCK AVE', 37.85497, -122.26767),
+    ('BANCROFT WAY', 'DANA ST', 37.86832, -122.26227),
+    ('CEDAR ST', 'SACRAMENTO ST', 37.87669, -122.28265),
+    ('CEDAR ST', 'SHATTUCK AVE', 37.87842, -122.2691),
+    ('CEDAR ST', 'WEST ST', 37.87628, -122.28521),
+    ('DURANT AVE', 'BOWDITCH ST', 37.86787, -122.25828),
+    ('DWIGHT WAY', 'TELEGRAPH AVE', 37.86513, -122.25841),
+    ('HASTE ST', 'ATHERTON ST', 37.86506, -122.26433),
+    ('HEARST AVE', 'GRANT ST', 37.87299, -122.27562),
+    ('HEARST AVE', 'SHATTUCK AVE', 37.87314, -122.2693),
+    ('HEARST AVE', 'SIXTH ST', 37.86995, -122.29849),
+    ('MADERA ST', 'MERCED ST', 37.89371, -122.27325),
+    ('MARIN AVE', 'MARIN CIR', 37.89005, -122.27249),
+    ('MARTIN LUTHER KING JR WAY', 'WARD ST', 37.85919, -122.27188),
+    ('MATHEWS ST', 'OREGON ST', 37.85543, -122.28612),
+    ('MILVIA ST', 'ADDISON ST', 37.87057, -122.27119),
+    ('MILVIA ST', 'CHANNING WAY', 37.86545, -122.27),
+    ('MILVIA ST', 'KITTREDGE ST', 37.86816, -122.27035),
+    ('OREGON ST', 'PARK ST', 37.85598, -122.28285),
+    ('SACRAMENTO ST', 'DWIGHT WAY', 37.8622, -122.28109),
+    ('SACRAMENTO ST', 'UNIVERSITY AVE', 37.87041, -122.28194),
+    ('SAN PABLO AVE', 'ADDISON ST', 37.86815, -122.29151),
+    ('SAN PABLO AVE', 'GILMAN ST', 37.88006, -122.29623),
+    ('SHATTUCK AVE', 'BERKELEY SQ', 37.87298, -122.26848),
+    ('SHATTUCK AVE', 'DERBY ST', 37.85927, -122.26752),
+    ('SHATTUCK AVE', 'VINE ST', 37.88022, -122.26933),
+    ('TELEGRAPH AVE', 'PARKER ST', 37.86326, -122.25869),
+    ('TELEGRAPH AVE', 'STUART ST', 37.85954, -122.25921),
+    ('TENTH ST', 'DELAWARE ST', 37.87146, -122.29441),
+    ('VIRGINIA ST', 'SPRUCE ST', 37.87785, -122.26654),
+    ('WARRING ST', 'PARKER ST', 37.86231, -122.2501)
+    )
+    WHERE EXISTS (SELECT 1 FROM corrected_roads WHERE overlay = 'berkeley');
+
+-- coordinates of every located collision, either as reported or imputed from the overlay's intersections for the
+--   collisions without a reported latitude and longitude. Rebuilt on every build, update and `roads fix`
+CREATE TABLE collision_coordinates (
+    case_id VARCHAR2(19),           -- matches the case_id in collisions
+    latitude FLOAT,
+    longitude FLOAT,                -- negative, as in collisions_view
+    coordinate_source VARCHAR(16),  -- reported, or imputed from the intersection of the corrected roads
+    PRIMARY KEY(case_id)
+);
+
+INSERT INTO collision_coordinates (case_id, latitude, longitude, coordinate_source)
+    SELECT case_id, latitude, -longitude, 'reported' FROM collisions
+    WHERE latitude != 0 AND longitude != 0;
@@ -0,0 +1,4 @@
+-- the coordinates of the collisions are checked against the boundary of their city and their corrected
+--   intersection, the existing coordinates are unchecked until `roads fix` reruns the checks
+ALTER TABLE collision_coordinates ADD COLUMN coordinate_quality VARCHAR(32);
+UPDATE collision_coordinates SET coordinate_quality = 'unchecked';
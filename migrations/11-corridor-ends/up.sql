@@ -0,0 +1,47 @@
+-- midblock collisions past either end of a corridor, or in a direction across it, were placed on the corridor
+DROP VIEW corridor_collisions_view;
+
+-- collisions on the corridors, placed between the positions of two cross streets. Collisions at an intersection
+--   with a cross street are at its position, midblock collisions are between the cross street they are measured
+--   from and the next cross street in their direction: forward when it is the heading of the cross street, back
+--   when it is the opposite of the heading of the previous cross street. Midblock collisions past either end of the
+--   corridor, or in a direction across it, are not on the corridor
+CREATE VIEW corridor_collisions_view (
+    overlay,
+    corridor_rd,
+    case_id,
+    location_type,
+    from_position,
+    to_position
+) AS SELECT
+    m.overlay,
+    m.corridor_rd,
+    m.case_id,
+    m.location_type,
+    CASE m.toward WHEN 'previous' THEN m.position - 1 ELSE m.position END,
+    CASE m.toward WHEN 'next' THEN m.position + 1 ELSE m.position END
+FROM (
+    SELECT
+        x.overlay,
+        x.corridor_rd,
+        x.position,
+        l.case_id,
+        l.location_type,
+        CASE
+            WHEN l.location_type = 'intersection' THEN 'at'
+            WHEN l.direction = x.heading THEN 'next'
+            WHEN l.direction = CASE prev.heading WHEN 'N' THEN 'S' WHEN 'E' THEN 'W' WHEN 'S' THEN 'N' WHEN 'W' THEN 'E' END
+                THEN 'previous'
+        END AS toward
+    FROM corridor_cross_streets AS x
+    LEFT JOIN corridor_cross_streets AS prev
+        ON prev.overlay = x.overlay AND prev.corridor_rd = x.corridor_rd AND prev.position = x.position - 1
+    -- only collisions with roads corrected by the same overlay as the corridor
+    JOIN (collision_locations_view AS l JOIN corrected_roads AS cr ON l.case_id = cr.case_id)
+        ON cr.overlay = x.overlay
+        AND ((l.on_rd = x.corridor_rd AND l.from_rd = x.cross_rd)
+          OR (l.location_type = 'intersection' AND l.from_rd = x.corridor_rd AND l.on_rd = x.cross_rd))
+    WHERE l.location_type IN ('intersection', 'midblock')
+) AS m
+WHERE m.toward IS NOT NULL
+;
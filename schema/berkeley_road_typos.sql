@@ -1 +0,0 @@
-CREATE TABLE berkeley_road_typos (correct_rd VARCHAR(256), normalized_rd VARCHAR(256));
\ No newline at end of file
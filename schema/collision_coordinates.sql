@@ -0,0 +1,11 @@
+-- coordinates of every located collision, either as reported or imputed from the overlay's intersections for the
+--   collisions without a reported latitude and longitude, and whether the point passed the checks against the
+--   boundary of its city and its corrected intersection. Rebuilt on every build, update and `roads fix`
+CREATE TABLE collision_coordinates (
+    case_id VARCHAR2(19),           -- matches the case_id in collisions
+    latitude FLOAT,
+    longitude FLOAT,                -- negative, as in collisions_view
+    coordinate_source VARCHAR(16),  -- reported, or imputed from the intersection of the corrected roads
+    coordinate_quality VARCHAR(32), -- ok, outside_jurisdiction, far_from_intersection or unchecked
+    PRIMARY KEY(case_id)
+);
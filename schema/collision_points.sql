@@ -0,0 +1,10 @@
+-- spatial index of the collision_coordinates, reported or imputed, so that the collisions near a point can be found
+--   without scanning all of them. The id is the rowid of the collision in collisions, and the longitude is negative
+--   as in collisions_view. The R*Tree stores 32 bit floats, so the matches are refined against collision_coordinates
+CREATE VIRTUAL TABLE collision_points USING rtree(
+    id,
+    min_latitude,
+    max_latitude,
+    min_longitude,
+    max_longitude
+);
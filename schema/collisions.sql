@@ -251,9 +251,58 @@ LEFT JOIN stwd_vehtype_at_fault ON c.stwd_vehtype_at_fault = stwd_vehtype_at_fau
 LEFT JOIN chp_vehtype ON c.chp_vehtype_at_fault = chp_vehtype.id
 LEFT JOIN ramp primary_ramp ON c.primary_ramp = primary_ramp.id
 LEFT JOIN ramp secondary_ramp ON c.secondary_ramp = secondary_ramp.id
+-- each overlay has its own corrections, when the jurisdictions of overlays overlap the first overlay by name is used
 LEFT JOIN corrected_roads ON c.case_id = corrected_roads.case_id
+    AND corrected_roads.overlay = (SELECT min(o.overlay) FROM corrected_roads AS o WHERE o.case_id = c.case_id)
 WHERE 
-c.cnty_city_loc IN ("0102", "0103") -- see lookup-tables/CNTY_CITY_LOC.csv
+c.cnty_city_loc IN ({cnty_city_locs}) -- from [jurisdictions] in Schemas.toml, see lookup-tables/CNTY_CITY_LOC.csv
+;
+
+-- where each collision occurred, at an intersection or midblock along the primary road. Midblock collisions are
+--   `distance` feet in `direction` from the secondary road, e.g. 150 ft north of CEDAR ST on SHATTUCK AVE
+CREATE VIEW collision_locations_view (
+    case_id,
+    location_type,
+    on_rd,
+    from_rd,
+    distance,
+    direction,
+    direction_name,
+    description
+) AS SELECT
+    l.case_id,
+    l.location_type,
+    l.on_rd,
+    l.from_rd,
+    l.distance,
+    l.direction,
+    l.direction_name,
+    CASE l.location_type
+        WHEN 'intersection' THEN printf("%s and %s", l.on_rd, l.from_rd)
+        WHEN 'midblock' THEN printf("%g ft %s of %s on %s", l.distance, lower(l.direction_name), l.from_rd, l.on_rd)
+        ELSE l.on_rd
+    END
+FROM (
+    SELECT
+        c.case_id,
+        CASE
+            WHEN c.intersection = 'Y' THEN 'intersection'
+            WHEN c.intersection IS NOT 'N' AND c.distance = 0 THEN 'intersection'
+            WHEN c.distance > 0 AND direction.id IN ('N', 'E', 'S', 'W') THEN 'midblock'
+            ELSE 'unknown'
+        END AS location_type,
+        coalesce(cr.primary_rd, trim(c.primary_rd)) AS on_rd,
+        coalesce(cr.secondary_rd, trim(c.secondary_rd)) AS from_rd,
+        c.distance,
+        c.direction,
+        direction.name AS direction_name
+    FROM collisions AS c
+    -- the corrected roads are joined directly rather than through collisions_view, so that the views built on this one
+    --   stay under SQLite's limit of 64 tables in a join. It is the same correction as in collisions_view
+    LEFT JOIN corrected_roads AS cr ON cr.case_id = c.case_id
+        AND cr.overlay = (SELECT min(o.overlay) FROM corrected_roads AS o WHERE o.case_id = c.case_id)
+    LEFT JOIN direction ON c.direction = direction.id
+) AS l
 ;
 
 -- view of the data included in this DB
@@ -2,5 +2,13 @@ CREATE TABLE corrected_roads (
     case_id VARCHAR2(19), -- matches the case_id in collisions
     primary_rd VARCHAR2(50), -- Primary Road
     secondary_rd VARCHAR2(50), -- Secondary Road 
-    PRIMARY KEY(case_id)
-)
\ No newline at end of file
+    overlay VARCHAR(64), -- name of the overlay the correction comes from
+    primary_rd_confidence REAL, -- 1 for exact corrections, lower for fuzzy matches, NULL when unresolved
+    secondary_rd_confidence REAL, -- 1 for exact corrections, lower for fuzzy matches, NULL when unresolved
+    primary_rd_source VARCHAR(16), -- where the correction came from: manual, verified, typo, fuzzy or unresolved
+    secondary_rd_source VARCHAR(16), -- where the correction came from: manual, verified, typo, fuzzy or unresolved
+    original_primary_rd VARCHAR2(50), -- Primary Road as reported in collisions
+    original_secondary_rd VARCHAR2(50), -- Secondary Road as reported in collisions
+    PRIMARY KEY(overlay, case_id)
+);
+CREATE INDEX idx_corrected_roads_case_id ON corrected_roads(case_id);
@@ -1,10 +1,12 @@
--- table of intersection improvements from across the city
+-- table of intersection improvements from across the city, ids are unique within each overlay
 CREATE TABLE intersection_improvements (
-    id INTEGER PRIMARY KEY,
+    id INTEGER,
     primary_rd VARCHAR2(50),   -- primary road where the infrastructure was installed
     secondary_rd VARCHAR2(50), -- secondary or cross road of the intersection
     date_completed TEXT,       -- date, YYYY-MM-DD, when the infrastructure was completed
     improvement_type INTEGER,  -- type of intersection installed
+    overlay VARCHAR(64),       -- name of the overlay the improvement comes from
+    PRIMARY KEY(overlay, id)
     FOREIGN KEY(improvement_type) REFERENCES improvement_types(id)
 );
 
@@ -14,6 +16,7 @@ CREATE VIEW intersection_improvements_view (
     secondary_rd,
     date_completed,
     improvement_type,
+    overlay,
     -- joined table names
     improvement_name
 ) AS SELECT 
@@ -22,6 +25,7 @@ CREATE VIEW intersection_improvements_view (
     i.secondary_rd,
     i.date_completed,
     i.improvement_type,
+    i.overlay,
     -- joined table names
     improvement_types.name
 FROM intersection_improvements AS i
@@ -35,6 +39,7 @@ CREATE VIEW intersection_performance_view (
     secondary_rd,
     date_completed,
     improvement_type,
+    overlay,
     -- joined table names
     improvement_name,
     case_id,
@@ -48,13 +53,18 @@ CREATE VIEW intersection_performance_view (
     count_ped_killed,
     count_ped_injured,
     count_bicyclist_killed,
-    count_bicyclist_injured
+    count_bicyclist_injured,
+    -- provenance of the collision's corrected roads
+    primary_rd_source,
+    secondary_rd_source,
+    road_confidence
 ) AS SELECT 
     i.id,
     i.primary_rd,
     i.secondary_rd,
     i.date_completed,
     i.improvement_type,
+    i.overlay,
     -- joined table names
     improvement_types.name,
     c.case_id,
@@ -68,11 +78,17 @@ CREATE VIEW intersection_performance_view (
     c.count_ped_killed,
     c.count_ped_injured,
     c.count_bicyclist_killed,
-    c.count_bicyclist_injured
+    c.count_bicyclist_injured,
+    cr.primary_rd_source,
+    cr.secondary_rd_source,
+    min(cr.primary_rd_confidence, cr.secondary_rd_confidence)
 FROM intersection_improvements AS i
 -- join all the foreign key tables
 LEFT JOIN improvement_types ON i.improvement_type = improvement_types.id
-LEFT JOIN collisions_view as c ON (c.corrected_primary_rd = i.primary_rd AND c.corrected_secondary_rd = i.secondary_rd)
-                               OR (c.corrected_secondary_rd = i.primary_rd AND c.corrected_primary_rd = i.secondary_rd)
+-- only collisions with roads corrected by the same overlay as the improvement
+LEFT JOIN (collisions_view AS c JOIN corrected_roads AS cr ON c.case_id = cr.case_id)
+    ON cr.overlay = i.overlay
+    AND ((c.corrected_primary_rd = i.primary_rd AND c.corrected_secondary_rd = i.secondary_rd)
+      OR (c.corrected_secondary_rd = i.primary_rd AND c.corrected_primary_rd = i.secondary_rd))
 ;
 
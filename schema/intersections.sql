@@ -0,0 +1,10 @@
+-- gazetteer of the intersections of the overlay's roads, to geocode the collisions without a reported latitude and
+--   longitude. The roads are the corrected road names, and are matched in either order
+CREATE TABLE intersections (
+    primary_rd VARCHAR2(50),   -- one of the roads, as in corrected_roads
+    secondary_rd VARCHAR2(50), -- the cross road, as in corrected_roads
+    latitude FLOAT,            -- center of the intersection
+    longitude FLOAT,           -- center of the intersection, negative as in collisions_view
+    overlay VARCHAR(64),       -- name of the overlay the intersection comes from
+    PRIMARY KEY(overlay, primary_rd, secondary_rd)
+);
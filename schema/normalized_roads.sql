@@ -8,5 +8,6 @@ CREATE TABLE normalized_roads (
     secondary_rd_address VARCHAR2(10), -- address if one exists on the road where the collision occured
     secondary_rd_block VARCHAR2(10), -- block (i.e. address at the corner) on the road where the collision occured
     seconardy_rd_direction VARCHAR2(10), -- direction of travel when the collision occured
+    secondary_rd_inferred BOOLEAN, -- 1 when the Secondary Road was missing and was taken from the Primary Road, e.g. "UNIVERSITY AVE AT SAN PABLO"
     PRIMARY KEY(case_id)
 )
\ No newline at end of file
@@ -0,0 +1 @@
+CREATE TABLE road_typos (correct_rd VARCHAR(256), normalized_rd VARCHAR(256), overlay VARCHAR(64));
\ No newline at end of file
@@ -1,12 +1,15 @@
--- table of street improvements from across the city, like bike lanes that stretch over a range of intersections
+-- table of street improvements from across the city, like bike lanes that stretch over a range of intersections,
+--   ids are unique within each overlay
 CREATE TABLE street_improvements (
-    id INTEGER PRIMARY KEY,
+    id INTEGER,
     primary_rd VARCHAR2(50),   -- primary road where the infrastructure was installed
     start_intersection VARCHAR2(50), -- beginning, first cross street, of the improvements
     end_intersection VARCHAR2(50), -- end, last cross street, of the improvements
     date_completed TEXT,       -- date, YYYY-MM-DD, when the infrastructure was completed
     improvement_type INTEGER,  -- type of intersection installed
     ca_bike_lane_type INTEGER, -- California classification of bike infrastructure, 0 for none
+    overlay VARCHAR(64),       -- name of the overlay the improvement comes from
+    PRIMARY KEY(overlay, id)
     FOREIGN KEY(improvement_type) REFERENCES improvement_types(id)
     FOREIGN KEY(ca_bike_lane_type) REFERENCES ca_bike_lane_types(id)
 );
@@ -19,6 +22,7 @@ CREATE VIEW street_improvements_view (
     date_completed,
     improvement_type,
     ca_bike_lane_type,
+    overlay,
     -- joined table names
     improvement_name,
     ca_bike_lane_name
@@ -30,6 +34,7 @@ CREATE VIEW street_improvements_view (
     s.date_completed,
     s.improvement_type,
     s.ca_bike_lane_type,
+    s.overlay,
     -- joined table names
     improvement_types.name,
     ca_bike_lane_types.name
@@ -37,4 +42,71 @@ FROM street_improvements AS s
 -- join all the foreign key tables
 LEFT JOIN improvement_types ON s.improvement_type = improvement_types.id
 LEFT JOIN ca_bike_lane_types ON s.ca_bike_lane_type = ca_bike_lane_types.id
-;
\ No newline at end of file
+;
+-- collisions on the improved stretch of each street, between the positions of its start and end intersections in
+--   corridor_cross_streets, including the collisions at the start and end intersections
+CREATE VIEW street_performance_view (
+    id,
+    primary_rd,
+    start_intersection,
+    end_intersection,
+    date_completed,
+    improvement_type,
+    ca_bike_lane_type,
+    overlay,
+    -- joined table names
+    improvement_name,
+    ca_bike_lane_name,
+    case_id,
+    location_type,
+    party_count,
+    before_improvement,
+    collision_datetime,
+    pedestrian_accident,
+    bicycle_accident,
+    number_killed,
+    number_injured,
+    count_ped_killed,
+    count_ped_injured,
+    count_bicyclist_killed,
+    count_bicyclist_injured
+) AS SELECT
+    s.id,
+    s.primary_rd,
+    s.start_intersection,
+    s.end_intersection,
+    s.date_completed,
+    s.improvement_type,
+    s.ca_bike_lane_type,
+    s.overlay,
+    -- joined table names
+    improvement_types.name,
+    ca_bike_lane_types.name,
+    c.case_id,
+    cc.location_type,
+    c.party_count,
+    c.collision_datetime < s.date_completed,
+    c.collision_datetime,
+    c.pedestrian_accident,
+    c.bicycle_accident,
+    c.number_killed,
+    c.number_injured,
+    c.count_ped_killed,
+    c.count_ped_injured,
+    c.count_bicyclist_killed,
+    c.count_bicyclist_injured
+FROM street_improvements AS s
+-- join all the foreign key tables
+LEFT JOIN improvement_types ON s.improvement_type = improvement_types.id
+LEFT JOIN ca_bike_lane_types ON s.ca_bike_lane_type = ca_bike_lane_types.id
+-- positions of the start and end of the improvements along the corridor
+LEFT JOIN corridor_cross_streets AS start_x
+    ON start_x.overlay = s.overlay AND start_x.corridor_rd = s.primary_rd AND start_x.cross_rd = s.start_intersection
+LEFT JOIN corridor_cross_streets AS end_x
+    ON end_x.overlay = s.overlay AND end_x.corridor_rd = s.primary_rd AND end_x.cross_rd = s.end_intersection
+LEFT JOIN (corridor_collisions_view AS cc JOIN collisions_view AS c ON cc.case_id = c.case_id)
+    ON cc.overlay = s.overlay
+    AND cc.corridor_rd = s.primary_rd
+    AND cc.from_position >= min(start_x.position, end_x.position)
+    AND cc.to_position <= max(start_x.position, end_x.position)
+;
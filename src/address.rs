@@ -0,0 +1,456 @@
+//! Parsing of the free form road descriptions in SWITRS, like `ADDISON ST. WESTBOUND, 1500 BLOCK`
+//!
+//! The description is split into tokens, and the address, block, direction of travel and any parenthetical notes
+//!   are taken off of its ends, leaving the name of the road. Descriptions of two roads, like
+//!   `I-80 WB TO UNIVERSITY AVE`, are split into the road and its secondary road.
+
+/// Every spelling of the street types, and the abbreviation used in the correct road names
+pub(crate) const STREET_TYPES: &[(&str, &[&str])] = &[
+    ("AVE", &["AV", "AVE", "AVENUE"]),
+    ("ST", &["ST", "STR", "STREET"]),
+    ("BLVD", &["BL", "BLV", "BLVD", "BOULEVARD"]),
+    ("RD", &["RD", "ROAD"]),
+    ("DR", &["DR", "DRIVE"]),
+    ("WAY", &["WY", "WAY"]),
+    ("PL", &["PL", "PLACE"]),
+    ("CT", &["CT", "COURT"]),
+    ("LN", &["LN", "LANE"]),
+    ("TER", &["TER", "TERR", "TERRACE"]),
+    ("CIR", &["CIR", "CIRCLE"]),
+    ("PKWY", &["PKWY", "PARKWAY"]),
+    ("HWY", &["HWY", "HIGHWAY"]),
+];
+
+/// Directions of travel, as they are written in the road descriptions
+const DIRECTIONS: &[&str] = &[
+    "NORTHBOUND",
+    "EASTBOUND",
+    "WESTBOUND",
+    "SOUTHBOUND",
+    "NORTH",
+    "EAST",
+    "WEST",
+    "SOUTH",
+    "N/B",
+    "E/B",
+    "W/B",
+    "S/B",
+    "NB",
+    "EB",
+    "WB",
+    "SB",
+    "N",
+    "E",
+    "W",
+    "S",
+];
+
+/// Words joining the descriptions of two roads, e.g. `I-80 WB TO UNIVERSITY AVE`
+const JOINERS: &[&str] = &["TO", "AND", "AT", "@", "&"];
+
+/// Prefixes of the highway numbers, e.g. `I-80`, `RT 13` or `SR-24`
+const HIGHWAYS: &[&str] = &[
+    "I",
+    "INTERSTATE",
+    "RT",
+    "ROUTE",
+    "SR",
+    "HWY",
+    "HIGHWAY",
+    "US",
+    "CA",
+];
+
+/// Words marking the number of a block, e.g. `1400 BLOCK`
+const BLOCKS: &[&str] = &["BLOCK", "BLK"];
+
+/// A road description, split into the name of the road and the location details around it
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NormalizedRoad {
+    /// Name of the road, as it was written with the extra whitespace and punctuation removed
+    pub road: String,
+    /// Abbreviation of the road's street type, e.g. `ST` for `NINTH STREET`
+    pub street_type: Option<&'static str>,
+    /// True if the road is a highway, e.g. `I-80` or `RT 13`
+    pub highway: bool,
+    /// Street address on the road, e.g. `1201` in `1201 2ND ST`
+    pub address: Option<String>,
+    /// Block on the road, e.g. `1400` in `8TH STREET, 1400 BLOCK`
+    pub block: Option<String>,
+    /// Direction of travel, e.g. `WB` in `I-80 WB`
+    pub direction: Option<String>,
+    /// Second road in the description, e.g. `UNIVERSITY AVE` in `I-80 WB TO UNIVERSITY AVE`
+    pub secondary_rd: Option<String>,
+    /// Parenthetical notes, e.g. `PARKING LOT` in `1313 NINTH STREET (PARKING LOT)`
+    pub note: Option<String>,
+}
+
+impl NormalizedRoad {
+    /// The road with its street type abbreviated, e.g. `NINTH ST` for `NINTH STREET`
+    pub fn canonical_road(&self) -> String {
+        match (self.street_type, self.road.rsplit_once(' ')) {
+            (Some(street_type), Some((name, _))) => format!("{name} {street_type}"),
+            _ => self.road.clone(),
+        }
+    }
+}
+
+/// Parse the road description into the road and its location details
+pub fn normalize_road(description: &str) -> NormalizedRoad {
+    let (text, notes) = take_notes(description);
+    let tokens = tokenize(&text);
+
+    let (primary, secondary) = split_roads(&tokens);
+    let mut normalized = parse_road(primary);
+    normalized.secondary_rd = secondary
+        .map(|secondary| parse_road(secondary).road)
+        .filter(|road| !road.is_empty());
+
+    // notes like `(600 BLOCK)` are the block, any others are kept as the note
+    let mut other_notes = Vec::new();
+    for note in notes {
+        let note_tokens = tokenize(&note);
+        match block(&note_tokens) {
+            Some(block) if normalized.block.is_none() => normalized.block = Some(block),
+            _ => other_notes.push(note_tokens.join(" ")),
+        }
+    }
+    if !other_notes.is_empty() {
+        normalized.note = Some(other_notes.join("; "));
+    }
+
+    // nothing was left of the road, e.g. only an address, so the description is the road
+    if normalized.road.is_empty() {
+        normalized.road = description.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+
+    normalized
+}
+
+/// Remove the parenthetical notes from the description, returns the remaining text and the notes
+fn take_notes(description: &str) -> (String, Vec<String>) {
+    let mut text = String::with_capacity(description.len());
+    let mut notes = Vec::new();
+    let mut note: Option<String> = None;
+
+    for c in description.chars() {
+        match (c, &mut note) {
+            ('(', None) => note = Some(String::new()),
+            (')', Some(_)) => notes.extend(note.take()),
+            (c, Some(note)) => note.push(c),
+            (c, None) => text.push(c),
+        }
+    }
+
+    // an unclosed parenthesis runs to the end of the description
+    notes.extend(note);
+    notes.retain(|note| !note.trim().is_empty());
+
+    (text, notes)
+}
+
+/// Split the text into uppercase tokens, on whitespace and commas, without any trailing periods
+fn tokenize(text: &str) -> Vec<String> {
+    text.replace(',', " ")
+        .replace('@', " @ ")
+        .replace('&', " & ")
+        .split_whitespace()
+        .map(|token| token.trim_end_matches('.').to_uppercase())
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// Split the tokens into the first road and the second road at the first joining word, like `TO`. The tokens
+///   are not split when either side is too short to be a road, e.g. `D AND E DOCK RD`
+fn split_roads(tokens: &[String]) -> (&[String], Option<&[String]>) {
+    for (i, token) in tokens.iter().enumerate() {
+        if !JOINERS.contains(&token.as_str()) {
+            continue;
+        }
+
+        let (primary, secondary) = (&tokens[..i], &tokens[i + 1..]);
+        let is_road = |tokens: &[String]| tokens.iter().map(String::len).sum::<usize>() > 1;
+        if is_road(primary) && is_road(secondary) {
+            return (primary, Some(secondary));
+        }
+    }
+
+    (tokens, None)
+}
+
+/// Parse the tokens of a single road, taking the address, block and direction off of either end
+fn parse_road(tokens: &[String]) -> NormalizedRoad {
+    let mut normalized = NormalizedRoad::default();
+    let mut road = tokens;
+
+    // a leading address, `1201 2ND ST`
+    if let [address, rest @ ..] = road {
+        if is_number(address) && has_name(rest) {
+            normalized.address = Some(address.clone());
+            road = rest;
+        }
+    }
+
+    // the block, address and direction can follow the road in any order, `ADDISON ST WESTBOUND 1500 BLOCK`
+    loop {
+        if normalized.block.is_none() && road.len() > 2 {
+            let (rest, end) = road.split_at(road.len() - 2);
+            if let Some(block) = block(end) {
+                normalized.block = Some(block);
+                road = rest;
+                continue;
+            }
+        }
+
+        let Some((last, rest)) = road.split_last() else {
+            break;
+        };
+        if normalized.direction.is_none() && is_direction(last) && has_name(rest) {
+            normalized.direction = Some(last.clone());
+            road = rest;
+        } else if normalized.address.is_none()
+            && is_number(last)
+            && has_name(rest)
+            && !is_highway(road)
+        {
+            normalized.address = Some(last.clone());
+            road = rest;
+        } else {
+            break;
+        }
+    }
+
+    // a leading direction, `W COLUSA AV`
+    if let [direction, rest @ ..] = road {
+        if normalized.direction.is_none() && is_direction(direction) && has_name(rest) {
+            normalized.direction = Some(direction.clone());
+            road = rest;
+        }
+    }
+
+    normalized.street_type = road
+        .split_last()
+        .filter(|(_, rest)| !rest.is_empty())
+        .and_then(|(last, _)| street_type(last));
+    normalized.highway = is_highway(road);
+    normalized.road = road.join(" ");
+    normalized
+}
+
+/// The block number from the tokens `1400 BLOCK` or `BLOCK 1400`
+fn block(tokens: &[String]) -> Option<String> {
+    match tokens {
+        [number, word] | [word, number] if is_number(number) && BLOCKS.contains(&word.as_str()) => {
+            Some(number.clone())
+        }
+        _ => None,
+    }
+}
+
+/// True if the token is all digits
+fn is_number(token: &str) -> bool {
+    !token.is_empty() && token.chars().all(|c| c.is_ascii_digit())
+}
+
+/// True if the token is a direction of travel
+fn is_direction(token: &str) -> bool {
+    DIRECTIONS.contains(&token)
+}
+
+/// The abbreviation of the street type, if the token is one
+fn street_type(token: &str) -> Option<&'static str> {
+    STREET_TYPES
+        .iter()
+        .find(|(_, spellings)| spellings.contains(&token))
+        .map(|(abbreviation, _)| *abbreviation)
+}
+
+/// True if the tokens can be a road on their own, they have a word that isn't a number or a direction and are
+///   more than just a street type
+fn has_name(tokens: &[String]) -> bool {
+    let is_word = |token: &String| !is_number(token) && !is_direction(token);
+    match tokens {
+        [] => false,
+        [only] => is_word(only) && street_type(only).is_none(),
+        _ => tokens.iter().any(is_word),
+    }
+}
+
+/// True if the tokens are a highway number, e.g. `I-80`, `RT 13` or `SR-24`
+fn is_highway(tokens: &[String]) -> bool {
+    match tokens {
+        [highway] => highway
+            .split_once('-')
+            .is_some_and(|(prefix, number)| HIGHWAYS.contains(&prefix) && is_number(number)),
+        [prefix, number] => HIGHWAYS.contains(&prefix.as_str()) && is_number(number),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_road() {
+        let test = |raw, road, address, block, direction| {
+            let normalized = normalize_road(raw);
+            assert_eq!(
+                (road, address, block, direction),
+                (
+                    normalized.road.as_str(),
+                    normalized.address.as_deref(),
+                    normalized.block.as_deref(),
+                    normalized.direction.as_deref()
+                ),
+                "{raw}"
+            )
+        };
+
+        test("GRANT", "GRANT", None, None, None);
+        test("1201 2ND ST", "2ND ST", Some("1201"), None, None);
+        test("WARD 1403", "WARD", Some("1403"), None, None);
+        test("RT 123", "RT 123", None, None, None);
+        test("RT 13", "RT 13", None, None, None);
+        test("RT 80 E", "RT 80", None, None, Some("E"));
+        test("RT1805", "RT1805", None, None, None);
+        test("SAN PABLO 1229", "SAN PABLO", Some("1229"), None, None);
+        test("6TH  ST", "6TH ST", None, None, None);
+        test("7 GAUSS WAY", "GAUSS WAY", Some("7"), None, None);
+        test("ASHBY AVE.", "ASHBY AVE", None, None, None);
+        test(
+            "EUCLID AVE (600 BLOCK)",
+            "EUCLID AVE",
+            None,
+            Some("600"),
+            None,
+        );
+        test("RT 80 E/B", "RT 80", None, None, Some("E/B"));
+        test("I-80 WB TO UNIVERSITY AVE", "I-80", None, None, Some("WB"));
+        test("I-80 E/B TO I-580 W/B", "I-80", None, None, Some("E/B"));
+        test(
+            "1313 NINTH STREET (PARKING LOT)",
+            "NINTH STREET",
+            Some("1313"),
+            None,
+            None,
+        );
+        test(
+            "85 EL CAMINO REAL RD",
+            "EL CAMINO REAL RD",
+            Some("85"),
+            None,
+            None,
+        );
+        test(
+            "8TH STREET, 1400 BLOCK",
+            "8TH STREET",
+            None,
+            Some("1400"),
+            None,
+        );
+        test(
+            "ADDISON ST. WESTBOUND, 1500 BLOCK",
+            "ADDISON ST",
+            None,
+            Some("1500"),
+            Some("WESTBOUND"),
+        );
+        test(
+            "CEDAR ST. (2200 BLOCK)",
+            "CEDAR ST",
+            None,
+            Some("2200"),
+            None,
+        );
+        test(
+            "CEDAR STREET, 1800 BLOCK",
+            "CEDAR STREET",
+            None,
+            Some("1800"),
+            None,
+        );
+        test(
+            "CHANNING WAY E/B  800 BLOCK",
+            "CHANNING WAY",
+            None,
+            Some("800"),
+            Some("E/B"),
+        );
+        test(
+            "CAMPUS DR, 1400 BLOCK",
+            "CAMPUS DR",
+            None,
+            Some("1400"),
+            None,
+        );
+        test(
+            "CAMPUS DR., 1400 BLOCK",
+            "CAMPUS DR",
+            None,
+            Some("1400"),
+            None,
+        );
+        test("W COLUSA AV", "COLUSA AV", None, None, Some("W"));
+        test("EAST ASHBY AVE", "ASHBY AVE", None, None, Some("EAST"));
+        test("EAST ST", "EAST ST", None, None, None);
+        test("SR-24 NB", "SR-24", None, None, Some("NB"));
+        test(
+            "SHATTUCK AVE BLOCK 2100",
+            "SHATTUCK AVE",
+            None,
+            Some("2100"),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_compound_roads() {
+        let normalized = normalize_road("I-80 WB TO UNIVERSITY AVE");
+        assert_eq!("I-80", normalized.road);
+        assert!(normalized.highway);
+        assert_eq!(Some("UNIVERSITY AVE"), normalized.secondary_rd.as_deref());
+
+        let normalized = normalize_road("I-80 E/B TO I-580 W/B");
+        assert_eq!(Some("I-580"), normalized.secondary_rd.as_deref());
+
+        let normalized = normalize_road("INTERSTATE 80 WESTBOUND TO ASHBY AVENUE");
+        assert_eq!("INTERSTATE 80", normalized.road);
+        assert!(normalized.highway);
+        assert_eq!(Some("ASHBY AVENUE"), normalized.secondary_rd.as_deref());
+
+        let normalized = normalize_road("SHATTUCK AVE @ CEDAR ST");
+        assert_eq!("SHATTUCK AVE", normalized.road);
+        assert_eq!(Some("CEDAR ST"), normalized.secondary_rd.as_deref());
+
+        let normalized = normalize_road("DWIGHT WAY AND FULTON ST");
+        assert_eq!("DWIGHT WAY", normalized.road);
+        assert_eq!(Some("FULTON ST"), normalized.secondary_rd.as_deref());
+
+        // roads with a joining word in their name are not split
+        let normalized = normalize_road("D AND E DOCK RD");
+        assert_eq!("D AND E DOCK RD", normalized.road);
+        assert_eq!(None, normalized.secondary_rd);
+        assert_eq!("ASHBY AVE TO I", normalize_road("ASHBY AVE TO I").road);
+    }
+
+    #[test]
+    fn test_street_types_and_notes() {
+        let normalized = normalize_road("1313 NINTH STREET (PARKING LOT)");
+        assert_eq!(Some("ST"), normalized.street_type);
+        assert_eq!("NINTH ST", normalized.canonical_road());
+        assert_eq!(Some("PARKING LOT"), normalized.note.as_deref());
+        assert!(!normalized.highway);
+
+        assert_eq!(
+            "SAN PABLO AVE",
+            normalize_road("SAN PABLO AV").canonical_road()
+        );
+        assert_eq!("GRANT", normalize_road("GRANT").canonical_road());
+        assert_eq!(None, normalize_road("GRANT").street_type);
+
+        // only an address is kept as the road
+        assert_eq!("1400", normalize_road("1400").road);
+        assert_eq!("80 E", normalize_road("80 E").road);
+    }
+}
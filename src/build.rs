@@ -0,0 +1,216 @@
+//! Building a new DB file from the raw data, either in memory or streamed directly to disk
+//!
+//! In both modes the DB is first written to a temporary file next to the target, which is only renamed over
+//!   the target once the build succeeds. A failed build never leaves a half written DB in place of the old one.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use rusqlite::{Connection, DatabaseName};
+
+use crate::{
+    integrity::IntegrityDB,
+    schema::{NewDB, Schema},
+};
+
+/// Raw data larger than this, in bytes, is built on disk when the mode isn't chosen explicitly
+pub const ON_DISK_THRESHOLD: u64 = 1024 * 1024 * 1024;
+
+/// Where the DB is built before it's written to the target file
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BuildMode {
+    /// Build the whole DB in memory, and then back it up to disk. This is the fastest, but the entire DB
+    ///   must fit in RAM
+    InMemory,
+    /// Build the DB directly in the file, for statewide exports that don't fit comfortably in RAM
+    OnDisk,
+}
+
+impl BuildMode {
+    /// Choose the mode based on the size of the raw data, larger than `ON_DISK_THRESHOLD` is built on disk
+    pub fn for_data(schemas: &Schema, data: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let size = schemas.raw_data_size(data)?;
+
+        if size > ON_DISK_THRESHOLD {
+            Ok(Self::OnDisk)
+        } else {
+            Ok(Self::InMemory)
+        }
+    }
+}
+
+/// Temporary file, in the same directory as the target so that it can be atomically renamed over it
+fn temp_path(sqlite_file: &Path) -> PathBuf {
+    let file_name = sqlite_file
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+
+    sqlite_file.with_file_name(format!(".{file_name}.{pid}.tmp", pid = process::id()))
+}
+
+/// Sync the directory containing the file, so that a rename into it is durable
+fn sync_dir(file: &Path) -> std::io::Result<()> {
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    // only unix can open a directory to sync it
+    if cfg!(unix) {
+        fs::File::open(dir)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+/// Removes the temporary file when dropped, unless the build was completed
+struct TempFile {
+    path: PathBuf,
+    completed: bool,
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.completed {
+            // the file may never have been created
+            fs::remove_file(&self.path).ok();
+        }
+    }
+}
+
+/// Check the foreign keys of the loaded data and print the report, with `strict` any violation is an error
+pub fn check_integrity(
+    connection: &Connection,
+    schemas: &Schema,
+    strict: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("CHECKING foreign keys");
+    let report = connection.check_foreign_keys(schemas)?;
+    println!("INTEGRITY {report}");
+
+    if strict && !report.is_empty() {
+        return Err(format!(
+            "{rows} rows reference codes missing from the lookup tables",
+            rows = report.rows()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Create and load all the tables in the Schema, and then replace `sqlite_file` with the new DB.
+///
+/// With `strict` the build fails, leaving any existing `sqlite_file` in place, when the data references codes
+///   missing from the lookup tables.
+pub fn build_db(
+    schemas: &Schema,
+    data: &Path,
+    sqlite_file: &Path,
+    mode: BuildMode,
+    strict: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut temp = TempFile {
+        path: temp_path(sqlite_file),
+        completed: false,
+    };
+    if temp.path.exists() {
+        fs::remove_file(&temp.path)?;
+    }
+
+    match mode {
+        BuildMode::InMemory => {
+            let connection = Connection::open_in_memory()?;
+            connection.load_from_schema(schemas, data)?;
+            check_integrity(&connection, schemas, strict)?;
+
+            println!("WRITING {temp}", temp = temp.path.display());
+            connection.backup(DatabaseName::Main, &temp.path, None)?;
+        }
+        BuildMode::OnDisk => {
+            println!("BUILDING {temp}", temp = temp.path.display());
+            let connection = Connection::open(&temp.path)?;
+            connection.load_from_schema(schemas, data)?;
+            check_integrity(&connection, schemas, strict)?;
+
+            // make sure everything is flushed and the file is closed before it's synced and renamed
+            connection.close().map_err(|(_, e)| e)?;
+        }
+    }
+
+    // the bulk load doesn't sync its writes, so the file is synced before the rename, and the directory after it,
+    //   otherwise a crash could leave a truncated DB under the target's name
+    fs::OpenOptions::new()
+        .write(true)
+        .open(&temp.path)?
+        .sync_all()?;
+    fs::rename(&temp.path, sqlite_file)?;
+    temp.completed = true;
+    sync_dir(sqlite_file)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_path() {
+        let temp = temp_path(Path::new("target/switrs.sqlite"));
+
+        assert_eq!(Some(Path::new("target")), temp.parent());
+        assert_eq!(
+            format!(".switrs.sqlite.{pid}.tmp", pid = process::id()),
+            temp.file_name().unwrap().to_string_lossy()
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_sync_dir() {
+        let dir = std::env::temp_dir().join(format!("switrs-db-sync-{pid}", pid = process::id()));
+        fs::create_dir_all(&dir).expect("failed to create dir");
+
+        sync_dir(&dir.join("switrs.sqlite")).expect("failed to sync dir");
+        // a file name without a directory is in the current directory
+        sync_dir(Path::new("switrs.sqlite")).expect("failed to sync current dir");
+        assert!(sync_dir(&dir.join("missing/switrs.sqlite")).is_err());
+
+        fs::remove_dir_all(&dir).expect("failed to remove dir");
+    }
+
+    #[test]
+    fn test_build_mode() {
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+
+        // the raw data files are named in Schemas.toml, tests/data doesn't have them
+        assert!(BuildMode::for_data(&schemas, Path::new("tests/data")).is_err());
+    }
+
+    #[test]
+    fn test_failed_build_keeps_old_db() {
+        let dir = std::env::temp_dir().join(format!("switrs-db-build-{pid}", pid = process::id()));
+        fs::create_dir_all(&dir).expect("failed to create dir");
+        let sqlite_file = dir.join("switrs.sqlite");
+        fs::write(&sqlite_file, "old db").expect("failed to write");
+
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        for mode in [BuildMode::InMemory, BuildMode::OnDisk] {
+            build_db(&schemas, &dir.join("missing"), &sqlite_file, mode, false)
+                .expect_err("raw data is missing");
+
+            assert_eq!(
+                "old db",
+                fs::read_to_string(&sqlite_file).expect("failed to read")
+            );
+            assert!(!temp_path(&sqlite_file).exists());
+        }
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
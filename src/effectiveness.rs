@@ -0,0 +1,683 @@
+//! Before and after safety effectiveness of the road safety improvements
+//!
+//! The collisions of each improvement, from `intersection_performance_view` and `street_performance_view`, are
+//!   counted in a window before and a window after its `date_completed`. The windows are clipped to the dates
+//!   covered by the DB, so the counts are compared as collisions per year, with 95% confidence intervals assuming
+//!   the collisions are Poisson distributed.
+
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    fmt,
+    io::Write,
+};
+
+use rusqlite::Connection;
+use time::{Date, Duration};
+
+use crate::{query::parse_date, records::CollisionSeverity, schema::NewDB};
+
+/// Average days in a year, for converting the windows to years
+const DAYS_PER_YEAR: f64 = 365.25;
+
+/// Standard normal quantile for the 95% confidence intervals
+const Z_95: f64 = 1.959964;
+
+/// Header of the CSV written by `EffectivenessReport::write_csv`
+const CSV_HEADER: [&str; 25] = [
+    "improvement",
+    "id",
+    "overlay",
+    "location",
+    "improvement_name",
+    "date_completed",
+    "before_start",
+    "before_end",
+    "before_years",
+    "before_collisions",
+    "before_rate",
+    "before_rate_low",
+    "before_rate_high",
+    "before_weighted_rate",
+    "after_start",
+    "after_end",
+    "after_years",
+    "after_collisions",
+    "after_rate",
+    "after_rate_low",
+    "after_rate_high",
+    "after_weighted_rate",
+    "rate_ratio",
+    "rate_ratio_low",
+    "rate_ratio_high",
+];
+
+/// Kind of road safety improvement
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum ImprovementKind {
+    /// An improvement of an intersection, from `intersection_improvements`
+    Intersection,
+    /// An improvement along a stretch of street, from `street_improvements`
+    Street,
+}
+
+impl ImprovementKind {
+    /// Name of the kind, as written to the CSV
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Intersection => "intersection",
+            Self::Street => "street",
+        }
+    }
+}
+
+/// Weight of each collision severity for the severity weighted rates
+///
+/// The defaults are simple weights emphasizing the fatal and severe injury collisions, they are not a standard.
+///   To match the weights used by the city, for example the crash costs of the Caltrans Local Roadway Safety
+///   Manual, set them relative to a property damage only collision.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeverityWeights {
+    /// Weight of a fatal collision
+    pub fatal: f64,
+    /// Weight of a severe injury collision
+    pub severe_injury: f64,
+    /// Weight of an other visible injury collision
+    pub other_visible_injury: f64,
+    /// Weight of a complaint of pain collision
+    pub complaint_of_pain: f64,
+    /// Weight of a property damage only collision, also used for unknown severities
+    pub property_damage_only: f64,
+}
+
+impl SeverityWeights {
+    /// Weights from a list in order of severity, fatal first and property damage only last
+    pub fn from_slice(weights: &[f64]) -> Result<Self, Box<dyn std::error::Error>> {
+        let [fatal, severe_injury, other_visible_injury, complaint_of_pain, property_damage_only] =
+            weights
+        else {
+            return Err(format!(
+                "expected 5 severity weights, fatal to property damage only, got {len}",
+                len = weights.len()
+            )
+            .into());
+        };
+
+        Ok(Self {
+            fatal: *fatal,
+            severe_injury: *severe_injury,
+            other_visible_injury: *other_visible_injury,
+            complaint_of_pain: *complaint_of_pain,
+            property_damage_only: *property_damage_only,
+        })
+    }
+
+    /// Weight of a collision of the severity
+    pub fn weight(&self, severity: &CollisionSeverity) -> f64 {
+        match severity {
+            CollisionSeverity::Fatal => self.fatal,
+            CollisionSeverity::SevereInjury => self.severe_injury,
+            CollisionSeverity::OtherVisibleInjury => self.other_visible_injury,
+            CollisionSeverity::ComplaintOfPain => self.complaint_of_pain,
+            CollisionSeverity::PropertyDamageOnly | CollisionSeverity::Unknown(_) => {
+                self.property_damage_only
+            }
+        }
+    }
+}
+
+impl Default for SeverityWeights {
+    fn default() -> Self {
+        Self {
+            fatal: 10.0,
+            severe_injury: 10.0,
+            other_visible_injury: 3.0,
+            complaint_of_pain: 3.0,
+            property_damage_only: 1.0,
+        }
+    }
+}
+
+/// Windows around the completion of each improvement in which the collisions are compared
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EffectivenessOptions {
+    /// Length of the window before the improvement, ending at the start of construction
+    pub before: Duration,
+    /// Length of the window after the improvement, starting on `date_completed`
+    pub after: Duration,
+    /// Length of the construction period before `date_completed`, which is left out of the before window
+    pub construction: Duration,
+    /// Weights for the severity weighted rates
+    pub weights: SeverityWeights,
+}
+
+impl EffectivenessOptions {
+    /// Windows of the given number of years before and after, and days of construction
+    pub fn new(before_years: f64, after_years: f64, construction_days: u32) -> Self {
+        Self {
+            before: years(before_years),
+            after: years(after_years),
+            construction: Duration::days(construction_days.into()),
+            weights: SeverityWeights::default(),
+        }
+    }
+
+    /// Use the weights for the severity weighted rates
+    pub fn weights(mut self, weights: SeverityWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+}
+
+impl Default for EffectivenessOptions {
+    fn default() -> Self {
+        Self::new(3.0, 3.0, 0)
+    }
+}
+
+/// Duration of the number of years, rounded to days
+fn years(years: f64) -> Duration {
+    Duration::days((years * DAYS_PER_YEAR).round() as i64)
+}
+
+/// Collisions per year, with a 95% confidence interval
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rate {
+    /// Collisions per year
+    pub rate: f64,
+    /// Lower bound of the 95% confidence interval
+    pub low: f64,
+    /// Upper bound of the 95% confidence interval
+    pub high: f64,
+}
+
+impl Rate {
+    /// Rate of the Poisson count over the years, the interval uses Byar's approximation of the exact interval
+    pub fn poisson(count: usize, years: f64) -> Self {
+        let x = count as f64;
+        let x1 = x + 1.0;
+        let (low, high) = if count == 0 {
+            // the exact interval is simple without any collisions
+            (0.0, -(0.025f64).ln())
+        } else {
+            (
+                x * (1.0 - 1.0 / (9.0 * x) - Z_95 / (3.0 * x.sqrt())).powi(3),
+                x1 * (1.0 - 1.0 / (9.0 * x1) + Z_95 / (3.0 * x1.sqrt())).powi(3),
+            )
+        };
+
+        Self {
+            rate: x / years,
+            low: low / years,
+            high: high / years,
+        }
+    }
+
+    /// Ratio of the rates, after over before, with the interval of the log rate ratio
+    ///
+    /// When either count is 0, 0.5 is added to both so that the ratio and its interval are finite, None when
+    ///   there are no collisions to compare.
+    pub fn ratio(after: (usize, f64), before: (usize, f64)) -> Option<Self> {
+        if after.0 == 0 && before.0 == 0 {
+            return None;
+        }
+
+        let (mut a, after_years) = (after.0 as f64, after.1);
+        let (mut b, before_years) = (before.0 as f64, before.1);
+        if a == 0.0 || b == 0.0 {
+            a += 0.5;
+            b += 0.5;
+        }
+
+        let ratio = (a / after_years) / (b / before_years);
+        let spread = Z_95 * (1.0 / a + 1.0 / b).sqrt();
+        Some(Self {
+            rate: ratio,
+            low: ratio * (-spread).exp(),
+            high: ratio * spread.exp(),
+        })
+    }
+}
+
+/// Collisions of an improvement in one window, which is from `start` up to, but not including, `end`
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowEffect {
+    /// First day of the window
+    pub start: Date,
+    /// Day after the window
+    pub end: Date,
+    /// Length of the window in years
+    pub years: f64,
+    /// Number of collisions in the window
+    pub collisions: usize,
+    /// Collisions per year
+    pub rate: Rate,
+    /// Sum of the severity weights of the collisions, per year
+    pub weighted_rate: f64,
+}
+
+impl WindowEffect {
+    /// The collisions within the window, None if the window is empty
+    fn new(
+        start: Date,
+        end: Date,
+        collisions: &[(Date, CollisionSeverity)],
+        weights: &SeverityWeights,
+    ) -> Option<Self> {
+        if end <= start {
+            return None;
+        }
+
+        let years = (end - start).whole_days() as f64 / DAYS_PER_YEAR;
+        let (count, weight) = collisions
+            .iter()
+            .filter(|(date, _)| (start..end).contains(date))
+            .fold((0, 0.0), |(count, weight), (_, severity)| {
+                (count + 1, weight + weights.weight(severity))
+            });
+
+        Some(Self {
+            start,
+            end,
+            years,
+            collisions: count,
+            rate: Rate::poisson(count, years),
+            weighted_rate: weight / years,
+        })
+    }
+}
+
+/// Before and after effectiveness of a single improvement
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImprovementEffect {
+    /// Intersection or street improvement
+    pub improvement: ImprovementKind,
+    /// Id of the improvement, unique within its overlay
+    pub id: i64,
+    /// Overlay the improvement comes from
+    pub overlay: String,
+    /// Intersection, or stretch of street, of the improvement
+    pub location: String,
+    /// Name of the improvement type
+    pub improvement_name: Option<String>,
+    /// Date the improvement was completed
+    pub date_completed: Date,
+    /// Collisions before the improvement, None if the DB has no collisions before it
+    pub before: Option<WindowEffect>,
+    /// Collisions after the improvement, None if the DB has no collisions after it
+    pub after: Option<WindowEffect>,
+    /// Ratio of the after rate to the before rate, below 1 is fewer collisions per year after the improvement
+    pub rate_ratio: Option<Rate>,
+}
+
+/// Effectiveness of all the improvements in the DB
+#[derive(Clone, Debug, PartialEq)]
+pub struct EffectivenessReport {
+    /// First day of the collisions in the DB
+    pub first_date: Date,
+    /// Last day of the collisions in the DB
+    pub last_date: Date,
+    /// Each improvement, intersections first
+    pub effects: Vec<ImprovementEffect>,
+}
+
+/// Format an optional number for the CSV, empty for None
+fn csv_number(value: Option<f64>) -> String {
+    value.map(|value| format!("{value:.3}")).unwrap_or_default()
+}
+
+/// Format a rate and its interval for the text report
+fn rate_text(rate: Option<&Rate>) -> String {
+    rate.map(|r| format!("{:.2} ({:.2}-{:.2})", r.rate, r.low, r.high))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+impl fmt::Display for EffectivenessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "collisions from {first} to {last}, rates are collisions per year with 95% intervals",
+            first = self.first_date,
+            last = self.last_date
+        )?;
+        write!(
+            f,
+            "{improvement:<12} {id:>4} {location:<48} {completed:<10} {before:>6} {before_rate:<20} {after:>6} {after_rate:<20} rate ratio",
+            improvement = "improvement",
+            id = "id",
+            location = "location",
+            completed = "completed",
+            before = "before",
+            before_rate = "",
+            after = "after",
+            after_rate = "",
+        )?;
+
+        for effect in &self.effects {
+            write!(
+                f,
+                "\n{improvement:<12} {id:>4} {location:<48} {completed:<10} {before:>6} {before_rate:<20} {after:>6} {after_rate:<20} {ratio}",
+                improvement = effect.improvement.as_str(),
+                id = effect.id,
+                location = effect.location,
+                completed = effect.date_completed,
+                before = effect
+                    .before
+                    .as_ref()
+                    .map(|w| w.collisions.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                before_rate = rate_text(effect.before.as_ref().map(|w| &w.rate)),
+                after = effect
+                    .after
+                    .as_ref()
+                    .map(|w| w.collisions.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                after_rate = rate_text(effect.after.as_ref().map(|w| &w.rate)),
+                ratio = rate_text(effect.rate_ratio.as_ref()),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl EffectivenessReport {
+    /// Write every improvement as CSV, with one row per improvement and empty fields for the missing windows
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(CSV_HEADER)?;
+
+        for effect in &self.effects {
+            let mut record = vec![
+                effect.improvement.as_str().to_string(),
+                effect.id.to_string(),
+                effect.overlay.clone(),
+                effect.location.clone(),
+                effect.improvement_name.clone().unwrap_or_default(),
+                effect.date_completed.to_string(),
+            ];
+            for window in [&effect.before, &effect.after] {
+                record.extend([
+                    window
+                        .as_ref()
+                        .map(|w| w.start.to_string())
+                        .unwrap_or_default(),
+                    // the CSV has the last day of the window, which reads better than the day after it
+                    window
+                        .as_ref()
+                        .and_then(|w| w.end.previous_day())
+                        .map(|end| end.to_string())
+                        .unwrap_or_default(),
+                    csv_number(window.as_ref().map(|w| w.years)),
+                    window
+                        .as_ref()
+                        .map(|w| w.collisions.to_string())
+                        .unwrap_or_default(),
+                    csv_number(window.as_ref().map(|w| w.rate.rate)),
+                    csv_number(window.as_ref().map(|w| w.rate.low)),
+                    csv_number(window.as_ref().map(|w| w.rate.high)),
+                    csv_number(window.as_ref().map(|w| w.weighted_rate)),
+                ]);
+            }
+            record.extend([
+                csv_number(effect.rate_ratio.map(|r| r.rate)),
+                csv_number(effect.rate_ratio.map(|r| r.low)),
+                csv_number(effect.rate_ratio.map(|r| r.high)),
+            ]);
+            csv.write_record(&record)?;
+        }
+
+        csv.flush()?;
+        Ok(self.effects.len())
+    }
+}
+
+/// An improvement and the dates and severities of its collisions
+type ImprovementCollisions = (ImprovementEffect, Vec<(Date, CollisionSeverity)>);
+
+/// Extensions to the DB Connection for the effectiveness of the improvements
+pub trait EffectivenessDB: NewDB {
+    /// The before and after effectiveness of every intersection and street improvement
+    fn effectiveness_report(
+        &self,
+        options: &EffectivenessOptions,
+    ) -> Result<EffectivenessReport, Box<dyn std::error::Error>> {
+        let (first, last): (Option<String>, Option<String>) = self.connection().query_row(
+            "SELECT first_collision_datetime, last_collision_datetime FROM version_view",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let (Some(first), Some(last)) = (first, last) else {
+            return Err("no collisions in the DB".into());
+        };
+        let first_date = parse_date(&first)?;
+        let last_date = parse_date(&last)?;
+        let data_end = last_date
+            .next_day()
+            .ok_or("last collision date out of range")?;
+
+        let mut improvements =
+            BTreeMap::<(ImprovementKind, String, i64), ImprovementCollisions>::new();
+        for (kind, query) in [
+            (
+                ImprovementKind::Intersection,
+                "SELECT p.id, p.overlay, printf('%s & %s', p.primary_rd, p.secondary_rd), p.improvement_name,
+                    p.date_completed, p.collision_datetime, c.collision_severity
+                FROM intersection_performance_view AS p
+                LEFT JOIN collisions AS c ON c.case_id = p.case_id",
+            ),
+            (
+                ImprovementKind::Street,
+                "SELECT p.id, p.overlay, printf('%s from %s to %s', p.primary_rd, p.start_intersection, p.end_intersection),
+                    p.improvement_name, p.date_completed, p.collision_datetime, c.collision_severity
+                FROM street_performance_view AS p
+                LEFT JOIN collisions AS c ON c.case_id = p.case_id",
+            ),
+        ] {
+            let mut stmt = self.connection().prepare(query)?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let id: i64 = row.get(0)?;
+                let overlay: String = row.get(1)?;
+                let (_, collisions) = match improvements.entry((kind, overlay.clone(), id)) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let date_completed: String = row.get(4)?;
+                        entry.insert((
+                            ImprovementEffect {
+                                improvement: kind,
+                                id,
+                                overlay,
+                                location: row.get(2)?,
+                                improvement_name: row.get(3)?,
+                                date_completed: parse_date(&date_completed)?,
+                                before: None,
+                                after: None,
+                                rate_ratio: None,
+                            },
+                            Vec::new(),
+                        ))
+                    }
+                };
+
+                // improvements without any collisions have a single row without a collision
+                if let Some(datetime) = row.get::<_, Option<String>>(5)? {
+                    let severity: Option<String> = row.get(6)?;
+                    collisions.push((
+                        parse_date(&datetime)?,
+                        CollisionSeverity::from(severity.unwrap_or_default()),
+                    ));
+                }
+            }
+        }
+
+        let effects = improvements
+            .into_values()
+            .map(|(mut effect, collisions)| {
+                // both windows are clipped to the days covered by the DB
+                let window = |start: Date, end: Date| {
+                    WindowEffect::new(
+                        start.max(first_date),
+                        end.min(data_end),
+                        &collisions,
+                        &options.weights,
+                    )
+                };
+                let construction_start = effect.date_completed - options.construction;
+                effect.before = window(construction_start - options.before, construction_start);
+                effect.after = window(effect.date_completed, effect.date_completed + options.after);
+                if let (Some(before), Some(after)) = (&effect.before, &effect.after) {
+                    effect.rate_ratio = Rate::ratio(
+                        (after.collisions, after.years),
+                        (before.collisions, before.years),
+                    );
+                }
+                effect
+            })
+            .collect();
+
+        Ok(EffectivenessReport {
+            first_date,
+            last_date,
+            effects,
+        })
+    }
+}
+
+impl EffectivenessDB for Connection {}
+
+#[cfg(test)]
+mod tests {
+    use time::Month;
+
+    use super::*;
+    use crate::schema::test_db;
+
+    /// Assert the two floats are within 0.001 of each other
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 0.001,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_rates() {
+        // the exact interval for 10 collisions is 4.795 to 18.390
+        let rate = Rate::poisson(10, 2.0);
+        assert_close(5.0, rate.rate);
+        assert!((rate.low * 2.0 - 4.795).abs() < 0.01);
+        assert!((rate.high * 2.0 - 18.390).abs() < 0.01);
+
+        let rate = Rate::poisson(0, 1.0);
+        assert_close(0.0, rate.low);
+        assert_close(3.689, rate.high);
+
+        let ratio = Rate::ratio((5, 1.0), (20, 2.0)).expect("no ratio");
+        assert_close(0.5, ratio.rate);
+        assert_close(0.5 * (-1.959964 * 0.5f64).exp(), ratio.low);
+        assert_close(0.5 * (1.959964 * 0.5f64).exp(), ratio.high);
+
+        // 0.5 is added to both counts
+        let ratio = Rate::ratio((0, 1.0), (3, 1.0)).expect("no ratio");
+        assert_close(0.5 / 3.5, ratio.rate);
+        assert_eq!(None, Rate::ratio((0, 1.0), (0, 1.0)));
+
+        assert!(SeverityWeights::from_slice(&[1.0, 2.0]).is_err());
+        let weights = SeverityWeights::from_slice(&[5.0, 4.0, 3.0, 2.0, 1.0]).expect("bad weights");
+        assert_close(4.0, weights.weight(&CollisionSeverity::SevereInjury));
+        assert_close(
+            1.0,
+            weights.weight(&CollisionSeverity::Unknown("9".to_string())),
+        );
+    }
+
+    #[test]
+    fn test_effectiveness_report() {
+        let connection = test_db(&[
+            "corrected_roads",
+            "collisions",
+            "intersection_improvements",
+            "corridor_cross_streets",
+            "street_improvements",
+        ]);
+        connection
+            .execute_batch(
+                "INSERT INTO corrected_roads (case_id, primary_rd, secondary_rd, overlay)
+                    VALUES('9629920', 'UNIVERSITY AVE', 'SACRAMENTO ST', 'berkeley');
+                INSERT INTO intersection_improvements (id, primary_rd, secondary_rd, date_completed, overlay)
+                    VALUES(1, 'UNIVERSITY AVE', 'SACRAMENTO ST', '2023-09-01', 'berkeley');
+                INSERT INTO street_improvements (id, primary_rd, start_intersection, end_intersection, date_completed, overlay)
+                    VALUES(1, 'MILVIA ST', 'HEARST AVE', 'BLAKE ST', '2022-01-01', 'berkeley');",
+            )
+            .expect("failed to insert");
+
+        // the collision on 2023-08-08 is before the two weeks of construction
+        let weights =
+            SeverityWeights::from_slice(&[10.0, 10.0, 3.0, 3.0, 2.0]).expect("bad weights");
+        let report = connection
+            .effectiveness_report(&EffectivenessOptions::new(1.0, 1.0, 14).weights(weights))
+            .expect("failed to report");
+        assert_eq!(
+            Date::from_calendar_date(2023, Month::May, 9).expect("bad date"),
+            report.first_date
+        );
+        assert_eq!(
+            Date::from_calendar_date(2023, Month::October, 31).expect("bad date"),
+            report.last_date
+        );
+        assert_eq!(2, report.effects.len());
+
+        let intersection = &report.effects[0];
+        assert_eq!(ImprovementKind::Intersection, intersection.improvement);
+        assert_eq!("UNIVERSITY AVE & SACRAMENTO ST", intersection.location);
+        let before = intersection.before.as_ref().expect("no before");
+        assert_eq!(report.first_date, before.start);
+        assert_eq!(
+            Date::from_calendar_date(2023, Month::August, 18).expect("bad date"),
+            before.end
+        );
+        assert_eq!(1, before.collisions);
+        assert_close(1.0 / before.years, before.rate.rate);
+        // property damage only
+        assert_close(2.0 / before.years, before.weighted_rate);
+        let after = intersection.after.as_ref().expect("no after");
+        assert_eq!(0, after.collisions);
+        assert_eq!(
+            Date::from_calendar_date(2023, Month::November, 1).expect("bad date"),
+            after.end
+        );
+        assert!(intersection.rate_ratio.expect("no ratio").rate < 1.0);
+
+        // a longer construction period leaves out the collision
+        let report = connection
+            .effectiveness_report(&EffectivenessOptions::new(1.0, 1.0, 30))
+            .expect("failed to report");
+        let intersection = &report.effects[0];
+        assert_eq!(
+            0,
+            intersection.before.as_ref().expect("no before").collisions
+        );
+        assert_eq!(None, intersection.rate_ratio);
+
+        // the street improvement is long before the collisions in the DB
+        let street = &report.effects[1];
+        assert_eq!(ImprovementKind::Street, street.improvement);
+        assert_eq!("MILVIA ST from HEARST AVE to BLAKE ST", street.location);
+        assert_eq!(None, street.before);
+        assert_eq!(None, street.after);
+
+        let mut out = Vec::new();
+        assert_eq!(2, report.write_csv(&mut out).expect("failed to write"));
+        let out = String::from_utf8(out).expect("not utf8");
+        let mut lines = out.lines();
+        assert_eq!(Some(CSV_HEADER.join(",").as_str()), lines.next());
+        assert!(lines
+            .next()
+            .expect("no intersection")
+            .starts_with("intersection,1,berkeley,UNIVERSITY AVE & SACRAMENTO ST,,2023-09-01,2023-05-09,2023-08-01,"));
+        assert_eq!(
+            Some("street,1,berkeley,MILVIA ST from HEARST AVE to BLAKE ST,,2022-01-01,,,,,,,,,,,,,,,,,,,"),
+            lines.next()
+        );
+    }
+}
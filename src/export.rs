@@ -0,0 +1,404 @@
+//! Exports of the tables and views of a built DB, for tools that don't read SQLite
+
+use std::io::Write;
+
+use rusqlite::{params, params_from_iter, types::ValueRef, Connection, Params, Statement};
+use serde_json::{json, Map, Number, Value as JsonValue};
+
+use crate::{query::CollisionQuery, schema::NewDB};
+
+/// Quote the name of a table or view for use in SQL
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{name}\"", name = name.replace('"', "\"\""))
+}
+
+/// Columns of `collisions_view` in the GeoJSON properties by default, enough to label and filter the points
+pub const DEFAULT_GEOJSON_PROPERTIES: &[&str] = &[
+    "case_id",
+    "collision_datetime",
+    "corrected_primary_rd",
+    "corrected_secondary_rd",
+    "collision_severity_name",
+    "type_of_collision_name",
+    "pedestrian_accident",
+    "bicycle_accident",
+    "number_killed",
+    "number_injured",
+    "coordinate_source",
+    "coordinate_quality",
+];
+
+/// What is included in the properties of each collision in the GeoJSON export
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoJsonOptions {
+    /// Columns of `collisions_view`, `coordinate_source` or `coordinate_quality`, to include, None for all of them
+    pub properties: Option<Vec<String>>,
+    /// Embed the parties of the collision, from `parties_view`
+    pub parties: bool,
+    /// Embed the victims of each party, from `victims_view`, only when the parties are embedded
+    pub victims: bool,
+}
+
+impl Default for GeoJsonOptions {
+    fn default() -> Self {
+        Self {
+            properties: Some(
+                DEFAULT_GEOJSON_PROPERTIES
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            ),
+            parties: false,
+            victims: false,
+        }
+    }
+}
+
+/// Convert a SQLite value to JSON, blobs can't be converted
+fn json_value(value: ValueRef<'_>) -> Result<JsonValue, Box<dyn std::error::Error>> {
+    Ok(match value {
+        ValueRef::Null => JsonValue::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => Number::from_f64(f).map_or(JsonValue::Null, JsonValue::Number),
+        ValueRef::Text(s) => String::from_utf8_lossy(s).into(),
+        ValueRef::Blob(_) => return Err("blobs can not be exported to JSON".into()),
+    })
+}
+
+/// Every row of the query as a JSON object of its columns
+fn query_objects<P: Params>(
+    stmt: &mut Statement<'_>,
+    params: P,
+) -> Result<Vec<Map<String, JsonValue>>, Box<dyn std::error::Error>> {
+    let columns = stmt
+        .column_names()
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    let mut rows = stmt.query(params)?;
+    let mut objects = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut object = Map::new();
+        for (i, column) in columns.iter().enumerate() {
+            object.insert(column.clone(), json_value(row.get_ref(i)?)?);
+        }
+        objects.push(object);
+    }
+
+    Ok(objects)
+}
+
+/// Extensions to the DB Connection to export data
+pub trait ExportDB: NewDB {
+    /// Write every row of the query as CSV to the writer, with the column names as the header.
+    ///
+    /// NULLs are written as empty fields, the same as the raw SWITRS data. Returns the number of rows written.
+    fn export_csv<W: Write>(
+        &self,
+        query: &str,
+        writer: W,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        self.export_csv_with_params(query, [], writer)
+    }
+
+    /// Write every row of the query, with the params bound, as CSV to the writer
+    fn export_csv_with_params<P: Params, W: Write>(
+        &self,
+        query: &str,
+        params: P,
+        writer: W,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut stmt = self.connection().prepare(query)?;
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(stmt.column_names())?;
+
+        let column_count = stmt.column_count();
+        let mut rows = stmt.query(params)?;
+        let mut count = 0;
+        let mut record = Vec::with_capacity(column_count);
+        while let Some(row) = rows.next()? {
+            record.clear();
+            for i in 0..column_count {
+                record.push(match row.get_ref(i)? {
+                    ValueRef::Null => String::new(),
+                    ValueRef::Integer(i) => i.to_string(),
+                    ValueRef::Real(f) => f.to_string(),
+                    ValueRef::Text(s) => String::from_utf8_lossy(s).into_owned(),
+                    ValueRef::Blob(_) => return Err("blobs can not be exported to CSV".into()),
+                });
+            }
+            csv.write_record(&record)?;
+            count += 1;
+        }
+
+        csv.flush()?;
+        Ok(count)
+    }
+
+    /// Write every row of the table, or view, as CSV to the writer
+    fn export_table_csv<W: Write>(
+        &self,
+        table: &str,
+        writer: W,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        self.export_csv(
+            &format!("SELECT * FROM {table}", table = quote_identifier(table)),
+            writer,
+        )
+    }
+
+    /// Write the collisions matching the query, which have a reported or imputed latitude and longitude, as a
+    ///   GeoJSON FeatureCollection of points. Returns the number of features written.
+    ///
+    /// The properties of each feature are the columns of `collisions_view` in the options, plus the parties,
+    ///   each with their victims, when they are embedded. The point is from `collision_coordinates`, its
+    ///   `coordinate_source` property says whether it was reported or imputed, and `coordinate_quality` whether
+    ///   it passed the checks.
+    fn export_geojson<W: Write>(
+        &self,
+        query: &CollisionQuery,
+        options: &GeoJsonOptions,
+        mut writer: W,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let query = query.clone().with_coordinates();
+        // the first two columns are the point, the rest are the properties
+        let (sql, params) = query
+            .to_sql("cc.longitude, cc.latitude, cv.*, cc.coordinate_source, cc.coordinate_quality");
+        let mut stmt = self.connection().prepare(&sql)?;
+        let columns = stmt.column_names()[2..]
+            .iter()
+            .map(|column| column.to_string())
+            .collect::<Vec<_>>();
+        let properties = match &options.properties {
+            Some(properties) => properties
+                .iter()
+                .map(|property| {
+                    columns
+                        .iter()
+                        .position(|column| column == property)
+                        .map(|i| i + 2)
+                        .ok_or_else(|| format!("not a column of collisions_view: {property}"))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => (2..columns.len() + 2).collect(),
+        };
+
+        let mut parties = self
+            .connection()
+            .prepare("SELECT * FROM parties_view WHERE case_id = ? ORDER BY party_number")?;
+        let mut victims = self
+            .connection()
+            .prepare("SELECT * FROM victims_view WHERE case_id = ? AND party_number = ?")?;
+
+        writer.write_all(br#"{"type":"FeatureCollection","features":["#)?;
+        let mut rows = stmt.query(params_from_iter(params))?;
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            let mut feature_properties = Map::new();
+            for &i in &properties {
+                feature_properties.insert(columns[i - 2].clone(), json_value(row.get_ref(i)?)?);
+            }
+
+            if options.parties {
+                let case_id: String = row.get("case_id")?;
+                let mut case_parties = Vec::new();
+                for mut party in query_objects(&mut parties, params![case_id])? {
+                    if options.victims {
+                        let party_number = party.get("party_number").cloned().unwrap_or_default();
+                        let party_victims =
+                            query_objects(&mut victims, params![case_id, party_number.as_i64()])?;
+                        party.insert(
+                            "victims".to_string(),
+                            party_victims.into_iter().map(JsonValue::Object).collect(),
+                        );
+                    }
+                    case_parties.push(JsonValue::Object(party));
+                }
+                feature_properties.insert("parties".to_string(), JsonValue::Array(case_parties));
+            }
+
+            let feature = json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": [
+                        json_value(row.get_ref(0)?)?,
+                        json_value(row.get_ref(1)?)?,
+                    ],
+                },
+                "properties": feature_properties,
+            });
+
+            if count > 0 {
+                writer.write_all(b",")?;
+            }
+            writer.write_all(b"\n")?;
+            serde_json::to_writer(&mut writer, &feature)?;
+            count += 1;
+        }
+        writer.write_all(b"\n]}\n")?;
+        writer.flush()?;
+
+        Ok(count)
+    }
+}
+
+impl ExportDB for Connection {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{geocode::GeocodeDB, overlay::Overlay, query::Mode, schema::test_db};
+
+    #[test]
+    fn test_export_csv() {
+        let connection = test_db(&["collisions"]);
+
+        let mut out = Vec::new();
+        let count = connection
+            .export_table_csv("collisions", &mut out)
+            .expect("failed to export");
+        assert_eq!(40, count);
+
+        // the export reads back the same as the original data
+        let mut original = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path("tests/data/collisions.csv")
+            .expect("failed to read csv");
+        let mut exported = csv::Reader::from_reader(out.as_slice());
+        assert_eq!(
+            original.headers().expect("no headers").len(),
+            exported.headers().expect("no headers").len()
+        );
+        let exported = exported
+            .records()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("bad export");
+        assert_eq!(40, exported.len());
+        let first = original
+            .records()
+            .next()
+            .expect("no records")
+            .expect("bad record");
+        assert_eq!(first.get(0), exported[0].get(0));
+
+        let mut out = Vec::new();
+        connection
+            .export_csv(
+                "SELECT case_id, latitude FROM collisions WHERE bicycle_accident = 'Y' ORDER BY case_id",
+                &mut out,
+            )
+            .expect("failed to export");
+        let out = String::from_utf8(out).expect("not utf8");
+        assert_eq!(Some("case_id,latitude"), out.lines().next());
+        assert_eq!(5, out.lines().count());
+    }
+
+    #[test]
+    fn test_export_geojson() {
+        let connection = test_db(&[
+            "corrected_roads",
+            "collisions",
+            "parties",
+            "victims",
+            "intersections",
+            "collision_coordinates",
+        ]);
+        connection.geocode_collisions().expect("failed to geocode");
+
+        // one of the 4 bike collisions has no location
+        let query = CollisionQuery::new().involving(Mode::Bicycle);
+        let options = GeoJsonOptions {
+            parties: true,
+            victims: true,
+            ..GeoJsonOptions::default()
+        };
+        let mut out = Vec::new();
+        let count = connection
+            .export_geojson(&query, &options, &mut out)
+            .expect("failed to export");
+        assert_eq!(3, count);
+
+        let geojson: JsonValue = serde_json::from_slice(&out).expect("bad json");
+        assert_eq!("FeatureCollection", geojson["type"]);
+        let features = geojson["features"].as_array().expect("no features");
+        assert_eq!(3, features.len());
+        let feature = &features[0];
+        assert_eq!(
+            json!([-122.27, 37.86545]),
+            feature["geometry"]["coordinates"]
+        );
+        let properties = feature["properties"].as_object().expect("no properties");
+        assert_eq!("9641156", properties["case_id"]);
+        assert_eq!("reported", properties["coordinate_source"]);
+        assert_eq!("Y", properties["bicycle_accident"]);
+        assert_eq!(DEFAULT_GEOJSON_PROPERTIES.len() + 1, properties.len());
+        let parties = properties["parties"].as_array().expect("no parties");
+        assert_eq!(2, parties.len());
+        let victims = parties
+            .iter()
+            .map(|party| party["victims"].as_array().expect("no victims").len())
+            .sum::<usize>();
+        assert_eq!(1, victims);
+
+        // all the properties, and unknown properties are an error
+        let mut out = Vec::new();
+        let options = GeoJsonOptions {
+            properties: None,
+            ..GeoJsonOptions::default()
+        };
+        connection
+            .export_geojson(&query.clone().limit(1), &options, &mut out)
+            .expect("failed to export");
+        let geojson: JsonValue = serde_json::from_slice(&out).expect("bad json");
+        assert!(geojson["features"][0]["properties"]["city_name"].is_string());
+        assert!(geojson["features"][0]["properties"]["parties"].is_null());
+
+        let options = GeoJsonOptions {
+            properties: Some(vec!["case_id".to_string(), "nope".to_string()]),
+            ..GeoJsonOptions::default()
+        };
+        assert!(connection
+            .export_geojson(&query, &options, Vec::new())
+            .is_err());
+
+        // once its roads are corrected, the collision without a location is placed at the intersection
+        let overlay = Overlay::from_dir(Path::new("berkeley-tables")).expect("bad overlay");
+        connection
+            .load_overlay_data("intersections", &overlay)
+            .expect("failed to load overlay");
+        connection
+            .execute(
+                "INSERT INTO corrected_roads (case_id, primary_rd, secondary_rd, overlay)
+                VALUES ('9641417', 'CEDAR ST', 'WEST ST', 'berkeley')",
+                [],
+            )
+            .expect("failed to insert");
+        connection.geocode_collisions().expect("failed to geocode");
+        let mut out = Vec::new();
+        let options = GeoJsonOptions {
+            properties: Some(vec!["case_id".to_string(), "coordinate_source".to_string()]),
+            ..GeoJsonOptions::default()
+        };
+        assert_eq!(
+            4,
+            connection
+                .export_geojson(&query, &options, &mut out)
+                .expect("failed to export")
+        );
+        let geojson: JsonValue = serde_json::from_slice(&out).expect("bad json");
+        let imputed = geojson["features"]
+            .as_array()
+            .expect("no features")
+            .iter()
+            .find(|feature| feature["properties"]["case_id"] == "9641417")
+            .expect("not exported");
+        assert_eq!("imputed", imputed["properties"]["coordinate_source"]);
+        assert_eq!(
+            json!([-122.28521, 37.87628]),
+            imputed["geometry"]["coordinates"]
+        );
+    }
+}
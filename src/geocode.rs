@@ -0,0 +1,446 @@
+//! Geocoding of the collisions which are missing their latitude and longitude
+//!
+//! Each overlay has a gazetteer, `intersections`, of the coordinates of the intersections of its corrected roads.
+//!   A collision without reported coordinates is placed at the intersection of its corrected roads, or for
+//!   midblock collisions `distance` feet in `direction` from it, see collision_locations_view. Every located
+//!   collision is recorded in `collision_coordinates`, with whether its point was reported or imputed.
+//!
+//! The reported coordinates are sometimes wrong, swapped or missing their sign, so every point is then checked
+//!   against the GeoJSON boundary of its city from the overlays, and against the gazetteer location of its corrected
+//!   intersection. The outliers are flagged in the `coordinate_quality` of `collision_coordinates`.
+
+use std::{collections::HashMap, fmt};
+
+use rusqlite::{params, Connection};
+
+use crate::{
+    schema::{NewDB, Schema},
+    spatial::{distance_meters, offset_meters, Boundary, METERS_PER_FOOT},
+};
+
+/// Meters a reported point may be from the gazetteer location of its corrected intersection, about two blocks
+pub const MAX_INTERSECTION_DISTANCE: f64 = 250.0;
+
+/// Meters a point may be outside the boundary of its city, the boundaries are simplified outlines of the city
+///   limits, so points close to them are not flagged
+pub const MAX_BOUNDARY_DISTANCE: f64 = 500.0;
+
+/// Where the coordinates of a collision came from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CoordinateSource {
+    /// The latitude and longitude reported in the raw data
+    Reported,
+    /// The intersection of the corrected roads in the overlay's gazetteer, offset along the primary road for
+    ///   midblock collisions
+    Imputed,
+}
+
+impl CoordinateSource {
+    /// Name of the source in the collision_coordinates table
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reported => "reported",
+            Self::Imputed => "imputed",
+        }
+    }
+}
+
+impl fmt::Display for CoordinateSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Result of checking the coordinates of a collision, the first check which fails is recorded
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CoordinateQuality {
+    /// Passed all the checks which apply to the collision
+    Ok,
+    /// The point is further than `MAX_BOUNDARY_DISTANCE` outside the boundary of the collision's city
+    OutsideJurisdiction,
+    /// The reported point is further than `MAX_INTERSECTION_DISTANCE` from its corrected intersection
+    FarFromIntersection,
+    /// Neither the city has a boundary, nor the corrected intersection is in the gazetteer
+    Unchecked,
+}
+
+impl CoordinateQuality {
+    /// Name of the quality in the collision_coordinates table
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::OutsideJurisdiction => "outside_jurisdiction",
+            Self::FarFromIntersection => "far_from_intersection",
+            Self::Unchecked => "unchecked",
+        }
+    }
+}
+
+impl fmt::Display for CoordinateQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Counts of the collisions by where their coordinates came from
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GeocodeReport {
+    /// Collisions with a reported latitude and longitude
+    pub reported: usize,
+    /// Collisions placed from the gazetteer
+    pub imputed: usize,
+    /// Collisions which could not be located
+    pub missing: usize,
+}
+
+impl fmt::Display for GeocodeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{reported} reported, {imputed} imputed, {missing} missing",
+            reported = self.reported,
+            imputed = self.imputed,
+            missing = self.missing
+        )
+    }
+}
+
+/// Counts of the located collisions by the quality of their coordinates
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct QualityReport {
+    /// Collisions which passed the checks
+    pub ok: usize,
+    /// Collisions outside the boundary of their city
+    pub outside_jurisdiction: usize,
+    /// Collisions reported far from their corrected intersection
+    pub far_from_intersection: usize,
+    /// Collisions which could not be checked
+    pub unchecked: usize,
+}
+
+impl QualityReport {
+    /// Count the collision
+    fn add(&mut self, quality: CoordinateQuality) {
+        match quality {
+            CoordinateQuality::Ok => self.ok += 1,
+            CoordinateQuality::OutsideJurisdiction => self.outside_jurisdiction += 1,
+            CoordinateQuality::FarFromIntersection => self.far_from_intersection += 1,
+            CoordinateQuality::Unchecked => self.unchecked += 1,
+        }
+    }
+}
+
+impl fmt::Display for QualityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{ok} ok, {outside} outside the jurisdiction, {far} far from the intersection, {unchecked} unchecked",
+            ok = self.ok,
+            outside = self.outside_jurisdiction,
+            far = self.far_from_intersection,
+            unchecked = self.unchecked
+        )
+    }
+}
+
+/// The gazetteer location of every collision whose corrected roads are in the gazetteer of its overlay, offset
+///   along the primary road for the midblock collisions
+fn gazetteer_points(
+    connection: &Connection,
+) -> Result<HashMap<String, (f64, f64)>, rusqlite::Error> {
+    // the roads are the corrected roads, so only the collisions in an overlay's jurisdictions are matched
+    let mut stmt = connection.prepare(
+        "SELECT l.case_id, l.location_type, l.distance, l.direction, i.latitude, i.longitude
+        FROM collision_locations_view AS l
+        JOIN corrected_roads AS cr ON cr.case_id = l.case_id
+        JOIN intersections AS i ON i.overlay = cr.overlay
+            AND ((i.primary_rd = l.on_rd AND i.secondary_rd = l.from_rd)
+              OR (i.primary_rd = l.from_rd AND i.secondary_rd = l.on_rd))
+        WHERE l.location_type IN ('intersection', 'midblock')",
+    )?;
+    let points = stmt
+        .query_map([], |row| {
+            let location_type: String = row.get(1)?;
+            let meters = row.get::<_, Option<f64>>(2)?.unwrap_or_default() * METERS_PER_FOOT;
+            let direction: Option<String> = row.get(3)?;
+            let (north, east) = match (location_type.as_str(), direction.as_deref()) {
+                ("midblock", Some("N")) => (meters, 0.0),
+                ("midblock", Some("E")) => (0.0, meters),
+                ("midblock", Some("S")) => (-meters, 0.0),
+                ("midblock", Some("W")) => (0.0, -meters),
+                _ => (0.0, 0.0),
+            };
+            let intersection = (row.get(4)?, row.get(5)?);
+            Ok((row.get(0)?, offset_meters(intersection, north, east)))
+        })?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    Ok(points)
+}
+
+/// Extensions to the DB Connection for geocoding the collisions
+pub trait GeocodeDB: NewDB {
+    /// Rebuild collision_coordinates from the reported coordinates, and the gazetteers for the collisions without them
+    ///
+    /// Like the spatial index this is always rebuilt in full, it depends on the corrected roads and the gazetteers
+    ///   of the overlays.
+    fn geocode_collisions(&self) -> Result<GeocodeReport, Box<dyn std::error::Error>> {
+        println!("GEOCODING collisions");
+        let tx = self.connection().unchecked_transaction()?;
+        tx.execute("DELETE FROM collision_coordinates", [])?;
+
+        // SWITRS has 0 for some of the missing coordinates, and the longitudes are positive
+        let reported = tx.execute(
+            "INSERT INTO collision_coordinates (case_id, latitude, longitude, coordinate_source)
+            SELECT case_id, latitude, -longitude, ?1 FROM collisions
+            WHERE latitude != 0 AND longitude != 0",
+            [CoordinateSource::Reported.as_str()],
+        )?;
+
+        // the reported collisions are already in collision_coordinates, so they are ignored
+        let mut imputed = 0;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR IGNORE INTO collision_coordinates (case_id, latitude, longitude, coordinate_source)
+                VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (case_id, (latitude, longitude)) in gazetteer_points(&tx)? {
+                imputed += insert.execute(params![
+                    case_id,
+                    latitude,
+                    longitude,
+                    CoordinateSource::Imputed.as_str()
+                ])?;
+            }
+        }
+
+        let collisions: usize =
+            tx.query_row("SELECT count(*) FROM collisions", [], |row| row.get(0))?;
+        tx.commit()?;
+
+        let report = GeocodeReport {
+            reported,
+            imputed,
+            missing: collisions - reported - imputed,
+        };
+        println!("GEOCODED {report}");
+        Ok(report)
+    }
+
+    /// Check the coordinates of every collision in collision_coordinates, and record the coordinate_quality
+    ///
+    /// The points are checked against the boundary of the collision's city, from the overlays, and the reported
+    ///   points also against the gazetteer location of their corrected intersection.
+    fn check_coordinates(
+        &self,
+        schemas: &Schema,
+    ) -> Result<QualityReport, Box<dyn std::error::Error>> {
+        println!("CHECKING collision_coordinates");
+        let mut boundaries = HashMap::new();
+        for overlay in schemas.overlays() {
+            for (city, path) in overlay.boundaries() {
+                boundaries.insert(city.to_lowercase(), Boundary::from_geojson_file(&path)?);
+            }
+        }
+
+        let tx = self.connection().unchecked_transaction()?;
+        let expected = gazetteer_points(&tx)?;
+        let located = tx
+            .prepare(
+                "SELECT cc.case_id, cc.latitude, cc.longitude, cc.coordinate_source, lower(l.city)
+                FROM collision_coordinates AS cc
+                JOIN collisions AS c ON c.case_id = cc.case_id
+                LEFT JOIN cnty_city_loc AS l ON l.id = c.cnty_city_loc",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    (row.get::<_, f64>(1)?, row.get::<_, f64>(2)?),
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut report = QualityReport::default();
+        {
+            let mut update = tx.prepare(
+                "UPDATE collision_coordinates SET coordinate_quality = ?1 WHERE case_id = ?2",
+            )?;
+            for (case_id, point, source, city) in located {
+                let boundary = city.and_then(|city| boundaries.get(&city));
+                // the imputed points are the gazetteer locations
+                let intersection = expected
+                    .get(&case_id)
+                    .filter(|_| source == CoordinateSource::Reported.as_str());
+
+                let quality = if boundary
+                    .is_some_and(|boundary| boundary.distance_meters(point) > MAX_BOUNDARY_DISTANCE)
+                {
+                    CoordinateQuality::OutsideJurisdiction
+                } else if intersection.is_some_and(|&intersection| {
+                    distance_meters(intersection, point) > MAX_INTERSECTION_DISTANCE
+                }) {
+                    CoordinateQuality::FarFromIntersection
+                } else if boundary.is_none() && intersection.is_none() {
+                    CoordinateQuality::Unchecked
+                } else {
+                    CoordinateQuality::Ok
+                };
+
+                update.execute(params![quality.as_str(), case_id])?;
+                report.add(quality);
+            }
+        }
+        tx.commit()?;
+
+        println!("CHECKED {report}");
+        Ok(report)
+    }
+}
+
+impl GeocodeDB for Connection {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        overlay::Overlay,
+        schema::{test_db, Schema},
+        spatial::{distance_meters, SpatialDB},
+    };
+
+    /// Create the tables the geocoding needs, with the test collisions and the berkeley gazetteer loaded
+    fn geocode_db() -> Connection {
+        let connection = test_db(&[
+            "corrected_roads",
+            "collisions",
+            "intersections",
+            "collision_coordinates",
+            "collision_points",
+        ]);
+        let overlay = Overlay::from_dir(Path::new("berkeley-tables")).expect("bad overlay");
+        connection
+            .load_overlay_data("intersections", &overlay)
+            .expect("failed to load overlay");
+
+        connection
+    }
+
+    #[test]
+    fn test_geocode_collisions() {
+        let connection = geocode_db();
+
+        // without corrected roads the collisions without coordinates can't be matched to an overlay
+        assert_eq!(
+            GeocodeReport {
+                reported: 38,
+                imputed: 0,
+                missing: 2
+            },
+            connection.geocode_collisions().expect("failed to geocode")
+        );
+
+        // the midblock collision is 30 ft south of an intersection which is in the gazetteer in the other order
+        connection
+            .execute_batch(
+                "INSERT INTO corrected_roads (case_id, primary_rd, secondary_rd, overlay) VALUES
+                    ('9641417', 'CEDAR ST', 'WEST ST', 'berkeley'),
+                    ('9637959', 'MERCED ST', 'MADERA ST', 'berkeley'),
+                    ('9641156', 'MILVIA ST', 'CHANNING WAY', 'berkeley');",
+            )
+            .expect("failed to insert");
+        assert_eq!(
+            GeocodeReport {
+                reported: 38,
+                imputed: 2,
+                missing: 0
+            },
+            connection.geocode_collisions().expect("failed to geocode")
+        );
+
+        let coordinates = |case_id: &str| -> (f64, f64, String) {
+            connection
+                .query_row(
+                    "SELECT latitude, longitude, coordinate_source FROM collision_coordinates WHERE case_id = ?",
+                    [case_id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .expect("failed to query")
+        };
+        assert_eq!(
+            (37.87628, -122.28521, "imputed".to_string()),
+            coordinates("9641417")
+        );
+        let (latitude, longitude, source) = coordinates("9637959");
+        assert_eq!("imputed", source);
+        assert!(latitude < 37.89371);
+        assert!(
+            (distance_meters((37.89371, -122.27325), (latitude, longitude)) - 9.144).abs() < 0.01
+        );
+        assert_eq!(
+            (37.86545, -122.27, "reported".to_string()),
+            coordinates("9641156")
+        );
+
+        // the imputed points are indexed too
+        assert_eq!(
+            40,
+            connection
+                .index_collision_points()
+                .expect("failed to index")
+        );
+    }
+
+    #[test]
+    fn test_check_coordinates() {
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        let connection = geocode_db();
+        connection
+            .execute_batch(
+                "INSERT INTO corrected_roads (case_id, primary_rd, secondary_rd, overlay) VALUES
+                    ('9641404', 'SHATTUCK AVE', 'DERBY ST', 'berkeley'),
+                    ('9631232', 'ASHBY AVE', 'SAN PABLO AVE', 'berkeley'),
+                    ('9637450', 'ASHBY AVE', 'SHATTUCK AVE', 'berkeley'),
+                    ('9641417', 'CEDAR ST', 'WEST ST', 'berkeley');
+                -- a city without a boundary
+                UPDATE collisions SET cnty_city_loc = '0101' WHERE case_id = '9629920';",
+            )
+            .expect("failed to update");
+        connection.geocode_collisions().expect("failed to geocode");
+
+        assert_eq!(
+            QualityReport {
+                ok: 35,
+                outside_jurisdiction: 2,
+                far_from_intersection: 1,
+                unchecked: 1,
+            },
+            connection
+                .check_coordinates(&schemas)
+                .expect("failed to check")
+        );
+
+        let quality = |case_id: &str| -> String {
+            connection
+                .query_row(
+                    "SELECT coordinate_quality FROM collision_coordinates WHERE case_id = ?",
+                    [case_id],
+                    |row| row.get(0),
+                )
+                .expect("failed to query")
+        };
+        // 3.5km east of the intersection, in the Oakland hills
+        assert_eq!("outside_jurisdiction", quality("9641404"));
+        // also 3km east of its intersection, but just outside the outline of Berkeley
+        assert_eq!("ok", quality("9641405"));
+        // 400m west of the intersection, but still in Berkeley
+        assert_eq!("far_from_intersection", quality("9631232"));
+        // 136 ft east of the intersection, and reported 90m from there
+        assert_eq!("ok", quality("9637450"));
+        assert_eq!("ok", quality("9641417"));
+        assert_eq!("unchecked", quality("9629920"));
+    }
+}
@@ -0,0 +1,339 @@
+//! OGC GeoPackage point layer of the collisions, written into the DB itself so that GIS tools like QGIS can open
+//!   it without converting it
+//!
+//! The layer is the `collision_features` table, with the point from `collision_coordinates` in `geom`, and the
+//!   columns of `collisions_view` along with the `coordinate_source` and `coordinate_quality` of the point. It is
+//!   registered in the `gpkg_contents` and `gpkg_geometry_columns` tables, see schema/geopackage.sql.
+//!
+//! A GeoPackage is identified by the `application_id` and `user_version` pragmas. Only the `application_id` is set,
+//!   to `GPKG`. The `user_version` is left as the version of the migrations, see `migrations`, which would break
+//!   if it were the version of the GeoPackage standard, so tools which validate the version will warn about it.
+//!   The `info` command prints this trade-off for a DB with the layer.
+
+use std::{collections::HashMap, iter};
+
+use rusqlite::{params, params_from_iter, types::Value, Connection};
+
+use crate::{
+    export::quote_identifier,
+    query::CollisionQuery,
+    schema::{NewDB, Schema},
+};
+
+/// The `application_id` of a GeoPackage, `GPKG` in ASCII
+pub const GEOPACKAGE_APPLICATION_ID: i32 = 0x4750_4B47;
+
+/// Name of the table of the point layer
+pub const FEATURES_TABLE: &str = "collision_features";
+
+/// EPSG code of WGS 84, the latitude and longitude of the collisions
+pub const WGS_84: i32 = 4326;
+
+/// Tables required by the GeoPackage standard
+const GEOPACKAGE_SCHEMA: &str = include_str!("../schema/geopackage.sql");
+
+/// Encode the point as a GeoPackage geometry, a header without an envelope followed by the WKB of the point,
+///   both little endian
+pub fn geopackage_point(longitude: f64, latitude: f64) -> Vec<u8> {
+    let mut geometry = Vec::with_capacity(29);
+    // magic, version 0, and the flags for little endian without an envelope
+    geometry.extend_from_slice(b"GP");
+    geometry.extend_from_slice(&[0, 1]);
+    geometry.extend_from_slice(&WGS_84.to_le_bytes());
+    // little endian WKB Point
+    geometry.push(1);
+    geometry.extend_from_slice(&1u32.to_le_bytes());
+    geometry.extend_from_slice(&longitude.to_le_bytes());
+    geometry.extend_from_slice(&latitude.to_le_bytes());
+    geometry
+}
+
+/// GeoPackage type of a column from its declared type, following SQLite's rules for the type affinity. The
+///   columns computed in the views have no declared type, and are TEXT
+fn column_type(declared_type: &str) -> &'static str {
+    let declared_type = declared_type.to_uppercase();
+    if declared_type.contains("INT") {
+        "INTEGER"
+    } else if ["REAL", "FLOA", "DOUB"]
+        .iter()
+        .any(|real| declared_type.contains(real))
+    {
+        "REAL"
+    } else {
+        "TEXT"
+    }
+}
+
+/// Extensions to the DB Connection for the GeoPackage layer
+pub trait GeoPackageDB: NewDB {
+    /// True if the DB already has the collision_features layer
+    fn has_geopackage(&self) -> Result<bool, rusqlite::Error> {
+        self.connection().query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            [FEATURES_TABLE],
+            |row| row.get(0),
+        )
+    }
+
+    /// Write the layer when the Schema asks for it, or rewrite it when the DB already has one so that it stays up
+    ///   to date. Returns the number of features written, if the layer was written
+    fn update_geopackage(
+        &self,
+        schemas: &Schema,
+    ) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+        if !schemas.geopackage && !self.has_geopackage()? {
+            return Ok(None);
+        }
+
+        self.write_geopackage(&CollisionQuery::new()).map(Some)
+    }
+
+    /// Replace the collision_features layer with the collisions matching the query which have a latitude and
+    ///   longitude, reported or imputed. Returns the number of features written.
+    fn write_geopackage(
+        &self,
+        query: &CollisionQuery,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        println!("WRITING {FEATURES_TABLE}");
+        let tx = self.connection().unchecked_transaction()?;
+        tx.pragma_update(None, "application_id", GEOPACKAGE_APPLICATION_ID)?;
+        tx.execute_batch(GEOPACKAGE_SCHEMA)?;
+        tx.execute(
+            "DELETE FROM gpkg_geometry_columns WHERE table_name = ?1",
+            [FEATURES_TABLE],
+        )?;
+        tx.execute(
+            "DELETE FROM gpkg_contents WHERE table_name = ?1",
+            [FEATURES_TABLE],
+        )?;
+        tx.execute_batch(&format!(
+            "DROP TABLE IF EXISTS {table}",
+            table = quote_identifier(FEATURES_TABLE)
+        ))?;
+
+        // declared types of the columns, to choose the GeoPackage type of each
+        let mut declared_types = HashMap::new();
+        for table in ["collisions_view", "collision_coordinates"] {
+            let mut stmt = tx.prepare(&format!("PRAGMA table_info({table})"))?;
+            let columns = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>("name")?, row.get::<_, String>("type")?))
+            })?;
+            for column in columns {
+                let (name, declared_type) = column?;
+                declared_types.entry(name).or_insert(declared_type);
+            }
+        }
+
+        let (count, extent) = {
+            // the first two columns are the point, the reported latitude and longitude are left out of the rest
+            let query = query.clone().with_coordinates();
+            let (sql, params) = query.to_sql(
+                "cc.longitude, cc.latitude, cv.*, cc.coordinate_source, cc.coordinate_quality",
+            );
+            let mut select = tx.prepare(&sql)?;
+            let attributes = select
+                .column_names()
+                .into_iter()
+                .enumerate()
+                .skip(2)
+                .filter(|(_, name)| !["latitude", "longitude"].contains(name))
+                .map(|(i, name)| (i, name.to_string()))
+                .collect::<Vec<_>>();
+
+            let columns = attributes
+                .iter()
+                .map(|(_, name)| {
+                    format!(
+                        "{name} {column_type}",
+                        name = quote_identifier(name),
+                        column_type =
+                            column_type(declared_types.get(name).map_or("", String::as_str))
+                    )
+                })
+                .collect::<Vec<_>>();
+            tx.execute_batch(&format!(
+                "CREATE TABLE {table} (fid INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, geom POINT, {columns})",
+                table = quote_identifier(FEATURES_TABLE),
+                columns = columns.join(", ")
+            ))?;
+
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {table} (geom, {names}) VALUES (?{placeholders})",
+                table = quote_identifier(FEATURES_TABLE),
+                names = attributes
+                    .iter()
+                    .map(|(_, name)| quote_identifier(name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                placeholders = ", ?".repeat(attributes.len())
+            ))?;
+
+            let mut rows = select.query(params_from_iter(params))?;
+            let mut count = 0;
+            let mut extent: Option<(f64, f64, f64, f64)> = None;
+            while let Some(row) = rows.next()? {
+                let (longitude, latitude): (f64, f64) = (row.get(0)?, row.get(1)?);
+                let values = attributes
+                    .iter()
+                    .map(|&(i, _)| row.get::<_, Value>(i))
+                    .collect::<Result<Vec<_>, _>>()?;
+                insert.execute(params_from_iter(
+                    iter::once(Value::Blob(geopackage_point(longitude, latitude))).chain(values),
+                ))?;
+
+                extent = Some(match extent {
+                    Some((min_x, min_y, max_x, max_y)) => (
+                        min_x.min(longitude),
+                        min_y.min(latitude),
+                        max_x.max(longitude),
+                        max_y.max(latitude),
+                    ),
+                    None => (longitude, latitude, longitude, latitude),
+                });
+                count += 1;
+            }
+
+            (count, extent)
+        };
+
+        tx.execute(
+            "INSERT INTO gpkg_contents (table_name, data_type, identifier, description, min_x, min_y, max_x, max_y, srs_id)
+            VALUES (?1, 'features', ?1, 'SWITRS collisions', ?2, ?3, ?4, ?5, ?6)",
+            params![
+                FEATURES_TABLE,
+                extent.map(|extent| extent.0),
+                extent.map(|extent| extent.1),
+                extent.map(|extent| extent.2),
+                extent.map(|extent| extent.3),
+                WGS_84
+            ],
+        )?;
+        tx.execute(
+            "INSERT INTO gpkg_geometry_columns (table_name, column_name, geometry_type_name, srs_id, z, m)
+            VALUES (?1, 'geom', 'POINT', ?2, 0, 0)",
+            params![FEATURES_TABLE, WGS_84],
+        )?;
+        tx.commit()?;
+
+        Ok(count)
+    }
+}
+
+impl GeoPackageDB for Connection {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{geocode::GeocodeDB, report::ReportDB, schema::test_db};
+
+    #[test]
+    fn test_geopackage_point() {
+        let geometry = geopackage_point(-122.27, 37.87);
+        assert_eq!(29, geometry.len());
+        assert_eq!(b"GP\x00\x01", &geometry[..4]);
+        assert_eq!(
+            WGS_84,
+            i32::from_le_bytes(geometry[4..8].try_into().expect("srs_id is 4 bytes"))
+        );
+        assert_eq!(
+            -122.27,
+            f64::from_le_bytes(geometry[13..21].try_into().expect("x is 8 bytes"))
+        );
+        assert_eq!(
+            37.87,
+            f64::from_le_bytes(geometry[21..].try_into().expect("y is 8 bytes"))
+        );
+    }
+
+    #[test]
+    fn test_write_geopackage() {
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        let connection = test_db(&[
+            "corrected_roads",
+            "collisions",
+            "intersections",
+            "collision_coordinates",
+        ]);
+        connection.geocode_collisions().expect("failed to geocode");
+
+        // without the option, and without an existing layer, nothing is written
+        assert!(!schemas.geopackage);
+        assert_eq!(
+            None,
+            connection
+                .update_geopackage(&schemas)
+                .expect("failed to update")
+        );
+        assert!(!connection.has_geopackage().expect("failed to check"));
+
+        // the 2 collisions without coordinates are left out
+        assert_eq!(
+            38,
+            connection
+                .write_geopackage(&CollisionQuery::new())
+                .expect("failed to write")
+        );
+        assert_eq!(
+            GEOPACKAGE_APPLICATION_ID,
+            connection
+                .pragma_query_value(None, "application_id", |row| row.get::<_, i32>(0))
+                .expect("failed to query")
+        );
+        assert!(connection.db_info().expect("failed to get info").geopackage);
+
+        let (data_type, srs_id, min_x, max_y): (String, i32, f64, f64) = connection
+            .query_row(
+                "SELECT data_type, srs_id, min_x, max_y FROM gpkg_contents WHERE table_name = ?1",
+                [FEATURES_TABLE],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .expect("failed to query contents");
+        assert_eq!(("features", WGS_84), (data_type.as_str(), srs_id));
+        assert!(min_x < -122.0 && max_y > 37.8 && max_y < 38.0);
+        assert_eq!(
+            "POINT",
+            connection
+                .query_row(
+                    "SELECT geometry_type_name FROM gpkg_geometry_columns WHERE table_name = ?1",
+                    [FEATURES_TABLE],
+                    |row| row.get::<_, String>(0),
+                )
+                .expect("failed to query geometry columns")
+        );
+
+        // the geometry matches the coordinates, and the columns have the declared types
+        let (geom, case_id, coordinate_source): (Vec<u8>, String, String) = connection
+            .query_row(
+                "SELECT geom, case_id, coordinate_source FROM collision_features ORDER BY fid LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .expect("failed to query features");
+        let (latitude, longitude): (f64, f64) = connection
+            .query_row(
+                "SELECT latitude, longitude FROM collision_coordinates WHERE case_id = ?1",
+                [&case_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("failed to query coordinates");
+        assert_eq!(geopackage_point(longitude, latitude), geom);
+        assert_eq!("reported", coordinate_source);
+
+        // an existing layer is rewritten by updates, replacing the old one
+        assert_eq!(
+            Some(38),
+            connection
+                .update_geopackage(&schemas)
+                .expect("failed to update")
+        );
+        assert_eq!(
+            1,
+            connection
+                .query_row("SELECT COUNT(*) FROM gpkg_contents", [], |row| row
+                    .get::<_, i64>(0))
+                .expect("failed to count")
+        );
+    }
+}
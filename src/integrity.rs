@@ -0,0 +1,240 @@
+//! Foreign key integrity of the loaded data against the lookup tables
+//!
+//! SQLite's `foreign_keys` pragma is turned off while loading, otherwise the first unknown code would fail the
+//!   whole build. Instead the `FOREIGN KEY` references in `schema/*.sql` are checked after everything is
+//!   loaded, with `PRAGMA foreign_key_check`, and all the codes missing from the lookup tables are reported.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::PathBuf,
+};
+
+use rusqlite::{types::Value, Connection};
+
+use crate::schema::{NewDB, Schema};
+
+/// All the rows of a table where the column(s) reference a code missing from the parent table
+#[derive(Debug, Eq, PartialEq)]
+pub struct ForeignKeyViolation {
+    /// Table with the references, e.g. `collisions`
+    pub table: String,
+    /// Column(s) in the table, comma separated for multi column keys
+    pub columns: String,
+    /// Referenced table, e.g. `weather`
+    pub parent: String,
+    /// Path to the CSV data of the parent table, when it's a lookup table
+    pub lookup_data: Option<PathBuf>,
+    /// Each missing code, and the number of rows with that code
+    pub codes: BTreeMap<String, usize>,
+}
+
+impl ForeignKeyViolation {
+    /// Number of rows with a missing code
+    pub fn rows(&self) -> usize {
+        self.codes.values().sum()
+    }
+}
+
+/// Result of the foreign key integrity check, grouped by table and column
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct IntegrityReport {
+    /// Violations ordered by table, column and parent table
+    pub violations: Vec<ForeignKeyViolation>,
+}
+
+impl IntegrityReport {
+    /// True if every foreign key reference was found
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Number of rows with a missing code, across all the tables
+    pub fn rows(&self) -> usize {
+        self.violations.iter().map(ForeignKeyViolation::rows).sum()
+    }
+}
+
+impl fmt::Display for IntegrityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no foreign key violations");
+        }
+
+        write!(
+            f,
+            "{rows} rows with foreign key violations",
+            rows = self.rows()
+        )?;
+        for violation in &self.violations {
+            write!(
+                f,
+                "\n  {table}.{columns} -> {parent}",
+                table = violation.table,
+                columns = violation.columns,
+                parent = violation.parent
+            )?;
+            if let Some(lookup_data) = &violation.lookup_data {
+                write!(f, " ({lookup_data})", lookup_data = lookup_data.display())?;
+            }
+            write!(f, ", missing codes:")?;
+            for (code, count) in &violation.codes {
+                write!(f, " '{code}' ({count} rows)")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Extensions to the DB Connection to check the integrity of the loaded data
+pub trait IntegrityDB: NewDB {
+    /// Check all the foreign keys in the DB, the report has the missing codes for each table and column
+    fn check_foreign_keys(
+        &self,
+        schemas: &Schema,
+    ) -> Result<IntegrityReport, Box<dyn std::error::Error>> {
+        let connection = self.connection();
+
+        // (table, fk id) -> rowids of the violating rows
+        let mut violating_rows = BTreeMap::<(String, usize), (String, Vec<i64>)>::new();
+        {
+            let mut check = connection.prepare("PRAGMA foreign_key_check")?;
+            let mut rows = check.query([])?;
+            while let Some(row) = rows.next()? {
+                let table: String = row.get("table")?;
+                let rowid: i64 = row.get("rowid")?;
+                let parent: String = row.get("parent")?;
+                let fkid: usize = row.get("fkid")?;
+
+                violating_rows
+                    .entry((table, fkid))
+                    .or_insert_with(|| (parent, Vec::new()))
+                    .1
+                    .push(rowid);
+            }
+        }
+
+        let mut foreign_keys = HashMap::<String, BTreeMap<usize, Vec<String>>>::new();
+        let mut violations = Vec::new();
+        for ((table, fkid), (parent, rowids)) in violating_rows {
+            if !foreign_keys.contains_key(&table) {
+                foreign_keys.insert(table.clone(), foreign_key_columns(connection, &table)?);
+            }
+            let columns = foreign_keys[&table]
+                .get(&fkid)
+                .ok_or_else(|| format!("unknown foreign key {fkid} on {table}"))?
+                .join(", ");
+
+            let mut select =
+                connection.prepare(&format!("SELECT {columns} FROM {table} WHERE rowid = ?"))?;
+            let mut codes = BTreeMap::new();
+            for rowid in rowids {
+                let code = select.query_row([rowid], |row| {
+                    (0..row.as_ref().column_count())
+                        .map(|i| row.get::<_, Value>(i).map(|value| format_value(&value)))
+                        .collect::<Result<Vec<_>, _>>()
+                })?;
+
+                *codes.entry(code.join(", ")).or_insert(0) += 1;
+            }
+
+            violations.push(ForeignKeyViolation {
+                lookup_data: schemas.lookup_tables.get(&parent).map(|t| t.data.clone()),
+                table,
+                columns,
+                parent,
+                codes,
+            });
+        }
+
+        violations.sort_by(|a, b| {
+            (&a.table, &a.columns, &a.parent).cmp(&(&b.table, &b.columns, &b.parent))
+        });
+        Ok(IntegrityReport { violations })
+    }
+}
+
+impl IntegrityDB for Connection {}
+
+/// The column(s) of each foreign key on the table, by foreign key id
+fn foreign_key_columns(
+    connection: &Connection,
+    table: &str,
+) -> Result<BTreeMap<usize, Vec<String>>, rusqlite::Error> {
+    let mut list = connection.prepare(&format!("PRAGMA foreign_key_list({table})"))?;
+    let mut rows = list.query([])?;
+
+    // rows are ordered by id and then seq, the position of the column in multi column keys
+    let mut columns = BTreeMap::<usize, Vec<String>>::new();
+    while let Some(row) = rows.next()? {
+        columns
+            .entry(row.get("id")?)
+            .or_default()
+            .push(row.get("from")?);
+    }
+
+    Ok(columns)
+}
+
+/// Format the value as it would appear in the CSV data
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Integer(i) => i.to_string(),
+        Value::Real(f) => f.to_string(),
+        Value::Text(s) => s.clone(),
+        Value::Blob(b) => format!("{b:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::schema::test_db;
+
+    #[test]
+    fn test_no_violations() {
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        let connection = test_db(&["collisions"]);
+
+        let report = connection
+            .check_foreign_keys(&schemas)
+            .expect("failed to check");
+        assert!(report.is_empty(), "{report}");
+    }
+
+    #[test]
+    fn test_missing_codes() {
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        let connection = test_db(&["collisions"]);
+
+        connection
+            .execute_batch(
+                "UPDATE collisions SET weather_1 = 'Z' WHERE case_id IN ('9629920', '9631228');
+                UPDATE collisions SET weather_2 = 'Y' WHERE case_id = '9629920';",
+            )
+            .expect("failed to update");
+
+        let report = connection
+            .check_foreign_keys(&schemas)
+            .expect("failed to check");
+        assert_eq!(3, report.rows());
+        assert_eq!(2, report.violations.len());
+
+        let weather_1 = &report.violations[0];
+        assert_eq!("collisions", weather_1.table);
+        assert_eq!("weather_1", weather_1.columns);
+        assert_eq!("weather", weather_1.parent);
+        assert_eq!(
+            Some(PathBuf::from("lookup-tables/WEATHER_1.csv")),
+            weather_1.lookup_data
+        );
+        assert_eq!(Some(&2), weather_1.codes.get("Z"));
+
+        assert_eq!("weather_2", report.violations[1].columns);
+        assert_eq!(Some(&1), report.violations[1].codes.get("Y"));
+    }
+}
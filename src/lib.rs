@@ -1,3 +1,18 @@
 //! SWITRS DB builder library
 
+pub mod address;
+pub mod build;
+pub mod effectiveness;
+pub mod export;
+pub mod geocode;
+pub mod geopackage;
+pub mod integrity;
+pub mod migrations;
+pub mod overlay;
+pub mod query;
+pub mod records;
+pub mod report;
+pub mod roads;
 pub mod schema;
+pub mod spatial;
+pub mod update;
@@ -1,52 +1,901 @@
 //! CLI for generating the Sqlite DB from the SWITRS database
 
-use std::path::PathBuf;
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    process,
+};
 
-use clap::Parser;
-use rusqlite::{Connection, DatabaseName};
+use clap::{Parser, Subcommand, ValueEnum};
+use rusqlite::{params_from_iter, Connection, OpenFlags};
+use time::Date;
 
-use switrs_db::schema::{NewDB, Schema};
+use switrs_db::{
+    build::{self, BuildMode},
+    effectiveness::{EffectivenessDB, EffectivenessOptions, SeverityWeights},
+    export::{self, ExportDB, GeoJsonOptions, DEFAULT_GEOJSON_PROPERTIES},
+    geopackage::{GeoPackageDB, FEATURES_TABLE},
+    migrations::{self, MigrateDB},
+    query::{parse_date, BoundingBox, CollisionQuery, Mode},
+    records::CollisionSeverity,
+    report::ReportDB,
+    roads::{self, RoadsDB},
+    schema::{NewDB, Schema},
+    update::UpdateDB,
+};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+// keep each option on one line, the help is longer than the default width
+#[command(term_width = 0)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Without a command the options are the same as `build`, for compatibility with older scripts
+    #[command(flatten)]
+    build: BuildArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct BuildArgs {
     /// Path to the raw data dump from iswitrs
-    #[arg(short = 'd')]
-    data_path: PathBuf,
+    #[arg(short = 'd', required = true)]
+    data_path: Option<PathBuf>,
 
     /// SQLITE db file to create from the raw data
-    #[arg(short = 'f')]
-    sqlite_file: PathBuf,
+    #[arg(short = 'f', required = true)]
+    sqlite_file: Option<PathBuf>,
 
     /// Path to the Schemas TOML configuration file
     #[arg(short = 's', default_value = "Schemas.toml")]
     schema: PathBuf,
+
+    /// Merge the raw data into the existing SQLITE db file, instead of creating a new one, same as `update`
+    #[arg(short = 'u', long)]
+    update: bool,
+
+    /// Build the DB directly in the file instead of in memory, defaults to on disk for raw data over 1GiB
+    #[arg(long, conflicts_with_all = ["update", "in_memory"])]
+    on_disk: bool,
+
+    /// Build the DB in memory, and then write it to the file, regardless of the size of the raw data
+    #[arg(long, conflicts_with = "update")]
+    in_memory: bool,
+
+    /// Fail when any of the data references codes missing from the lookup tables, the DB is not written
+    #[arg(long)]
+    strict_foreign_keys: bool,
+
+    /// Also write the collisions as a GeoPackage point layer, so that GIS tools like QGIS can open the DB
+    #[arg(long)]
+    geopackage: bool,
+
+    #[command(flatten)]
+    corrections: CorrectionsArgs,
+}
+
+/// Where the road corrections are written, overrides the `[corrections]` in the Schemas TOML
+#[derive(clap::Args, Debug)]
+#[command(about = None, long_about = None)]
+struct CorrectionsArgs {
+    /// Directory to write the road corrections of each overlay to, as `{overlay}_CORRECTED_ROADS.csv`
+    #[arg(long)]
+    corrections_dir: Option<PathBuf>,
+
+    /// Write the new and changed road corrections, otherwise they are only shown as a diff
+    #[arg(long)]
+    write_corrections: bool,
+}
+
+impl CorrectionsArgs {
+    /// Load the Schemas TOML, with these overrides for the corrections
+    fn load_schema(self, schema: &Path) -> Result<Schema, Box<dyn std::error::Error>> {
+        let mut schemas = Schema::from_toml_file(schema)?;
+        schemas.set_corrections(self.corrections_dir, self.write_corrections)?;
+        Ok(schemas)
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Build a new SQLITE db file from the raw data, an existing file is only replaced when the build succeeds
+    Build(BuildArgs),
+    /// Merge a newer raw data export into an existing SQLITE db file
+    Update {
+        /// Path to the raw data dump from iswitrs
+        #[arg(short = 'd')]
+        data_path: PathBuf,
+
+        /// SQLITE db file to merge the raw data into
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Path to the Schemas TOML configuration file
+        #[arg(short = 's', default_value = "Schemas.toml")]
+        schema: PathBuf,
+
+        /// Fail when any of the data references codes missing from the lookup tables, nothing is merged
+        #[arg(long)]
+        strict_foreign_keys: bool,
+
+        /// Also write the collisions as a GeoPackage point layer, a DB which has one always keeps it up to date
+        #[arg(long)]
+        geopackage: bool,
+
+        #[command(flatten)]
+        corrections: CorrectionsArgs,
+    },
+    /// Check the foreign keys of an existing SQLITE db file against the lookup tables, fails on any missing codes
+    Check {
+        /// SQLITE db file to check
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Path to the Schemas TOML configuration file, used to name the lookup CSV of each missing code
+        #[arg(short = 's', default_value = "Schemas.toml")]
+        schema: PathBuf,
+    },
+    /// Show the schema version, the rows in each table and the dates covered by an existing SQLITE db file
+    Info {
+        /// SQLITE db file to inspect
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Output format of the info
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Show the applied and pending schema migrations of an existing SQLITE db file
+    Migrations {
+        /// SQLITE db file to inspect
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Apply all the pending migrations
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Report on the data quality of an existing SQLITE db file, missing values, distributions and orphaned rows
+    Report {
+        /// SQLITE db file to inspect
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Path to the Schemas TOML configuration file, the tables in its table-order are reported on
+        #[arg(short = 's', default_value = "Schemas.toml")]
+        schema: PathBuf,
+
+        /// Output format of the report
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Compare the collisions per year before and after each road safety improvement of an existing SQLITE db file
+    Effectiveness {
+        /// SQLITE db file to analyze
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Years of collisions before the improvement, ending at the start of construction
+        #[arg(long, default_value_t = 3.0)]
+        before_years: f64,
+
+        /// Years of collisions after the improvement, starting on the date it was completed
+        #[arg(long, default_value_t = 3.0)]
+        after_years: f64,
+
+        /// Days of construction before the improvement was completed, left out of the before window
+        #[arg(long, default_value_t = 0)]
+        construction_days: u32,
+
+        /// Weights of the collision severities for the weighted rates, from fatal to property damage only
+        #[arg(long, value_delimiter = ',', default_value = "10,10,3,3,1")]
+        weights: Vec<f64>,
+
+        /// CSV file to write the effectiveness of every improvement to
+        #[arg(short = 'o')]
+        output: Option<PathBuf>,
+    },
+    /// Find the collisions near a point, or within a bounding box, of an existing SQLITE db file
+    Within {
+        /// SQLITE db file to search
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Point to search around, as latitude,longitude, e.g. 37.8704,-122.2819
+        #[arg(long, value_delimiter = ',', required_unless_present = "bbox")]
+        near: Option<Vec<f64>>,
+
+        /// Distance from the point in meters
+        #[arg(long, default_value_t = 100.0, requires = "near")]
+        radius: f64,
+
+        /// Bounding box to search, as min_latitude,min_longitude,max_latitude,max_longitude
+        #[arg(long, value_delimiter = ',', conflicts_with = "near")]
+        bbox: Option<Vec<f64>>,
+
+        /// CSV file to write the collisions from collisions_view to, defaults to stdout
+        #[arg(short = 'o')]
+        output: Option<PathBuf>,
+    },
+    /// Export data from an existing SQLITE db file
+    #[command(subcommand)]
+    Export(ExportCommand),
+    /// Maintain the road name corrections of the overlays
+    #[command(subcommand)]
+    Roads(RoadsCommand),
+    /// Serve an existing SQLITE db file with datasette, which must be installed, e.g. `pip install datasette`
+    Serve {
+        /// SQLITE db file to serve
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Port to serve on, datasette's default is 8001
+        #[arg(short = 'p', long)]
+        port: Option<u16>,
+
+        /// Any other options are passed to `datasette serve`
+        #[arg(last = true)]
+        datasette_args: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ExportCommand {
+    /// Export a table, view or query as CSV
+    Csv {
+        /// SQLITE db file to export from
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Table or view to export
+        #[arg(short = 't', long, default_value = "collisions_view")]
+        table: String,
+
+        /// SQL query to export, instead of a table
+        #[arg(short = 'q', long, conflicts_with = "table")]
+        query: Option<String>,
+
+        /// CSV file to write, defaults to stdout
+        #[arg(short = 'o')]
+        output: Option<PathBuf>,
+    },
+    /// Export the collisions with a latitude and longitude as a GeoJSON FeatureCollection of points
+    Geojson(GeoJsonArgs),
+    /// Write the collisions with a latitude and longitude as a GeoPackage point layer into the DB itself
+    Geopackage {
+        /// SQLITE db file to add the layer to
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct GeoJsonArgs {
+    /// SQLITE db file to export from
+    #[arg(short = 'f')]
+    sqlite_file: PathBuf,
+
+    /// Columns of collisions_view to include in the properties of each collision
+    #[arg(long, value_delimiter = ',', default_values = DEFAULT_GEOJSON_PROPERTIES)]
+    properties: Vec<String>,
+
+    /// Include all the columns of collisions_view in the properties
+    #[arg(long, conflicts_with = "properties")]
+    all_properties: bool,
+
+    /// Embed the parties of each collision in its properties
+    #[arg(long)]
+    parties: bool,
+
+    /// Embed the victims of each party, along with the parties
+    #[arg(long, requires = "parties")]
+    victims: bool,
+
+    /// Only collisions on or after the date, YYYY-MM-DD
+    #[arg(long)]
+    from: Option<String>,
+
+    /// Only collisions on or before the date, YYYY-MM-DD
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Only collisions involving the mode, when repeated the collisions must involve all of them
+    #[arg(long, value_enum)]
+    mode: Vec<Mode>,
+
+    /// Only collisions of the severities, as collision_severity codes, e.g. 1,2 for fatal and severe injury
+    #[arg(long, value_delimiter = ',')]
+    severity: Vec<String>,
+
+    /// GeoJSON file to write, defaults to stdout
+    #[arg(short = 'o')]
+    output: Option<PathBuf>,
+}
+
+impl GeoJsonArgs {
+    /// The collisions to export
+    fn query(&self) -> Result<CollisionQuery, Box<dyn std::error::Error>> {
+        let mut query = CollisionQuery::new();
+        if self.from.is_some() || self.to.is_some() {
+            let from = self.from.as_deref().map(parse_date).transpose()?;
+            let to = self.to.as_deref().map(parse_date).transpose()?;
+            query = query.between(from.unwrap_or(Date::MIN), to.unwrap_or(Date::MAX));
+        }
+        for &mode in &self.mode {
+            query = query.involving(mode);
+        }
+        if !self.severity.is_empty() {
+            let severities = self
+                .severity
+                .iter()
+                .map(|code| match CollisionSeverity::from(code.clone()) {
+                    CollisionSeverity::Unknown(code) => {
+                        Err(format!("unknown collision_severity code: {code}"))
+                    }
+                    severity => Ok(severity),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            query = query.severity(&severities);
+        }
+
+        Ok(query)
+    }
+
+    /// What to include with each collision
+    fn options(&self) -> GeoJsonOptions {
+        GeoJsonOptions {
+            properties: (!self.all_properties).then(|| self.properties.clone()),
+            parties: self.parties,
+            victims: self.victims,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum RoadsCommand {
+    /// Rerun the road fixups and geocoding on an existing SQLITE db file after editing an overlay's road tables
+    Fix {
+        /// SQLITE db file to fix
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Path to the Schemas TOML configuration file
+        #[arg(short = 's', default_value = "Schemas.toml")]
+        schema: PathBuf,
+
+        #[command(flatten)]
+        corrections: CorrectionsArgs,
+    },
+    /// Review the roads the fixups could not correct, most frequent first, and add corrections to road_typos
+    Review {
+        /// SQLITE db file with the unknown roads
+        #[arg(short = 'f')]
+        sqlite_file: PathBuf,
+
+        /// Path to the Schemas TOML configuration file
+        #[arg(short = 's', default_value = "Schemas.toml")]
+        schema: PathBuf,
+
+        /// Only review the roads of this overlay, defaults to all the overlays
+        #[arg(long)]
+        overlay: Option<String>,
+
+        /// Review at most this many roads of each overlay
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+}
+
+/// Output format for reports
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    /// Human readable text
+    Text,
+    /// JSON, for other tools
+    Json,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
-    let data_path = args.data_path;
-    let sqlite_file = args.sqlite_file;
+    match args.command {
+        None => build(args.build),
+        Some(Command::Build(build_args)) => build(build_args),
+        Some(Command::Update {
+            data_path,
+            sqlite_file,
+            schema,
+            strict_foreign_keys,
+            geopackage,
+            corrections,
+        }) => update(
+            data_path,
+            sqlite_file,
+            schema,
+            strict_foreign_keys,
+            geopackage,
+            corrections,
+        ),
+        Some(Command::Check {
+            sqlite_file,
+            schema,
+        }) => check(sqlite_file, schema),
+        Some(Command::Info {
+            sqlite_file,
+            format,
+        }) => info(sqlite_file, format),
+        Some(Command::Migrations { sqlite_file, apply }) => migrations(sqlite_file, apply),
+        Some(Command::Report {
+            sqlite_file,
+            schema,
+            format,
+        }) => report(sqlite_file, schema, format),
+        Some(Command::Effectiveness {
+            sqlite_file,
+            before_years,
+            after_years,
+            construction_days,
+            weights,
+            output,
+        }) => effectiveness(
+            sqlite_file,
+            EffectivenessOptions::new(before_years, after_years, construction_days)
+                .weights(SeverityWeights::from_slice(&weights)?),
+            output,
+        ),
+        Some(Command::Within {
+            sqlite_file,
+            near,
+            radius,
+            bbox,
+            output,
+        }) => within(sqlite_file, near, radius, bbox, output),
+        Some(Command::Export(ExportCommand::Csv {
+            sqlite_file,
+            table,
+            query,
+            output,
+        })) => export_csv(sqlite_file, table, query, output),
+        Some(Command::Export(ExportCommand::Geojson(args))) => export_geojson(args),
+        Some(Command::Export(ExportCommand::Geopackage { sqlite_file })) => {
+            export_geopackage(sqlite_file)
+        }
+        Some(Command::Roads(RoadsCommand::Fix {
+            sqlite_file,
+            schema,
+            corrections,
+        })) => roads_fix(sqlite_file, schema, corrections),
+        Some(Command::Roads(RoadsCommand::Review {
+            sqlite_file,
+            schema,
+            overlay,
+            limit,
+        })) => roads_review(sqlite_file, schema, overlay, limit),
+        Some(Command::Serve {
+            sqlite_file,
+            port,
+            datasette_args,
+        }) => serve(sqlite_file, port, datasette_args),
+    }
+}
+
+/// Build a new DB from the raw data, or merge it into the existing DB with `-u`
+fn build(args: BuildArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let data_path = args.data_path.expect("-d is required");
+    let sqlite_file = args.sqlite_file.expect("-f is required");
     let schema = args.schema;
 
+    if args.update {
+        return update(
+            data_path,
+            sqlite_file,
+            schema,
+            args.strict_foreign_keys,
+            args.geopackage,
+            args.corrections,
+        );
+    }
+
     println!(
         "Loading data from {data_path} and writing to {sqlite_file}",
         data_path = data_path.display(),
         sqlite_file = sqlite_file.display()
     );
 
-    // we'll build the DB in memory, and then store in a file
-    let connection = Connection::open_in_memory()?;
+    let mut schemas = args.corrections.load_schema(&schema)?;
+    schemas.set_geopackage(args.geopackage);
+    let mode = if args.on_disk {
+        BuildMode::OnDisk
+    } else if args.in_memory {
+        BuildMode::InMemory
+    } else {
+        BuildMode::for_data(&schemas, &data_path)?
+    };
+
+    // the DB is built in a temporary file, and only replaces the existing DB when everything succeeded
+    build::build_db(
+        &schemas,
+        &data_path,
+        &sqlite_file,
+        mode,
+        args.strict_foreign_keys,
+    )?;
+
+    println!(
+        "Successfully imported data, wrote DB to {sqlite_file}",
+        sqlite_file = sqlite_file.display()
+    );
+
+    Ok(())
+}
+
+/// Merge the raw data into the existing DB
+fn update(
+    data_path: PathBuf,
+    sqlite_file: PathBuf,
+    schema: PathBuf,
+    strict_foreign_keys: bool,
+    geopackage: bool,
+    corrections: CorrectionsArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!(
+        "Merging data from {data_path} into {sqlite_file}",
+        data_path = data_path.display(),
+        sqlite_file = sqlite_file.display()
+    );
+
+    let mut schemas = corrections.load_schema(&schema)?;
+    schemas.set_geopackage(geopackage);
+    let connection = open_and_migrate(&sqlite_file)?;
+
+    let report = connection.update_from_schema(&schemas, &data_path, strict_foreign_keys)?;
+
+    println!("Successfully merged data, {report}");
+    Ok(())
+}
+
+/// Open an existing DB, which may have been built by an older version, and bring it up to date
+fn open_and_migrate(sqlite_file: &Path) -> Result<Connection, Box<dyn std::error::Error>> {
+    if !sqlite_file.exists() {
+        return Err(format!(
+            "{sqlite_file} does not exist",
+            sqlite_file = sqlite_file.display()
+        )
+        .into());
+    }
+
+    let mut connection = Connection::open(sqlite_file)?;
+    let applied = migrations::migrate_to_latest(&mut connection)?;
+    if applied > 0 {
+        println!("Applied {applied} migrations");
+    }
+
+    Ok(connection)
+}
+
+/// Check the foreign keys of the DB, any missing codes are an error
+fn check(sqlite_file: PathBuf, schema: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let schemas = Schema::from_toml_file(&schema)?;
+    let connection = Connection::open_with_flags(&sqlite_file, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    build::check_integrity(&connection, &schemas, true)
+}
+
+/// Print the overview of the DB
+fn info(sqlite_file: PathBuf, format: Format) -> Result<(), Box<dyn std::error::Error>> {
+    let connection = Connection::open_with_flags(&sqlite_file, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let info = connection.db_info()?;
+    match format {
+        Format::Text => println!("{info}"),
+        Format::Json => println!("{json}", json = serde_json::to_string_pretty(&info)?),
+    }
+
+    Ok(())
+}
+
+/// Print the migration status of the DB, and optionally apply the pending migrations
+fn migrations(sqlite_file: PathBuf, apply: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut connection = Connection::open(&sqlite_file)?;
+
+    if apply {
+        let applied = migrations::migrate_to_latest(&mut connection)?;
+        println!(
+            "Applied {applied} migrations to {sqlite_file}",
+            sqlite_file = sqlite_file.display()
+        );
+    }
+
+    println!(
+        "{sqlite_file} is at version {version}",
+        sqlite_file = sqlite_file.display(),
+        version = connection.user_version()?
+    );
+    for migration in connection.migration_status()? {
+        let status = if migration.applied {
+            "applied"
+        } else {
+            "pending"
+        };
+        println!("  {status} {name}", name = migration.name);
+    }
+
+    Ok(())
+}
 
+/// Print the data quality report of the DB
+fn report(
+    sqlite_file: PathBuf,
+    schema: PathBuf,
+    format: Format,
+) -> Result<(), Box<dyn std::error::Error>> {
     let schemas = Schema::from_toml_file(&schema)?;
-    connection.load_from_schema(&schemas, &data_path)?;
+    let connection = Connection::open_with_flags(&sqlite_file, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let report = connection.quality_report(&schemas)?;
+    match format {
+        Format::Text => println!("{report}"),
+        Format::Json => println!("{json}", json = serde_json::to_string_pretty(&report)?),
+    }
+
+    Ok(())
+}
+
+/// Print the before and after effectiveness of the improvements, and optionally write it as CSV
+fn effectiveness(
+    sqlite_file: PathBuf,
+    options: EffectivenessOptions,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let connection = Connection::open_with_flags(&sqlite_file, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let report = connection.effectiveness_report(&options)?;
+    println!("{report}");
+
+    if let Some(output) = output {
+        let count = report.write_csv(BufWriter::new(File::create(&output)?))?;
+        println!(
+            "Wrote {count} improvements to {output}",
+            output = output.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Export the collisions near the point, or within the bounding box, as CSV to the output file or stdout
+fn within(
+    sqlite_file: PathBuf,
+    near: Option<Vec<f64>>,
+    radius: f64,
+    bbox: Option<Vec<f64>>,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let connection = Connection::open_with_flags(&sqlite_file, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let query = match (near.as_deref(), bbox.as_deref()) {
+        (Some(&[latitude, longitude]), None) => {
+            CollisionQuery::new().near(latitude, longitude, radius)
+        }
+        (None, Some(&[min_latitude, min_longitude, max_latitude, max_longitude])) => {
+            CollisionQuery::new().within(BoundingBox {
+                min_latitude,
+                min_longitude,
+                max_latitude,
+                max_longitude,
+            })
+        }
+        (Some(_), _) => return Err("--near must be latitude,longitude".into()),
+        _ => {
+            return Err(
+                "--bbox must be min_latitude,min_longitude,max_latitude,max_longitude".into(),
+            )
+        }
+    };
+
+    let (sql, params) = query.to_sql("cv.*");
+    match output {
+        Some(output) => {
+            let count = connection.export_csv_with_params(
+                &sql,
+                params_from_iter(params),
+                BufWriter::new(File::create(&output)?),
+            )?;
+            println!(
+                "Exported {count} collisions to {output}",
+                output = output.display()
+            );
+        }
+        None => {
+            connection.export_csv_with_params(
+                &sql,
+                params_from_iter(params),
+                io::stdout().lock(),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Export the table, or the query, as CSV to the output file or stdout
+fn export_csv(
+    sqlite_file: PathBuf,
+    table: String,
+    query: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let connection = Connection::open_with_flags(&sqlite_file, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let query = query.unwrap_or_else(|| {
+        format!(
+            "SELECT * FROM {table}",
+            table = export::quote_identifier(&table)
+        )
+    });
+    match output {
+        Some(output) => {
+            let count = connection.export_csv(&query, BufWriter::new(File::create(&output)?))?;
+            println!(
+                "Exported {count} rows to {output}",
+                output = output.display()
+            );
+        }
+        None => {
+            connection.export_csv(&query, io::stdout().lock())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Export the collisions as GeoJSON to the output file or stdout
+fn export_geojson(args: GeoJsonArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let connection =
+        Connection::open_with_flags(&args.sqlite_file, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let query = args.query()?;
+    let options = args.options();
+    match &args.output {
+        Some(output) => {
+            let count = connection.export_geojson(
+                &query,
+                &options,
+                BufWriter::new(File::create(output)?),
+            )?;
+            println!(
+                "Exported {count} collisions to {output}",
+                output = output.display()
+            );
+        }
+        None => {
+            connection.export_geojson(&query, &options, io::stdout().lock())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the GeoPackage layer into the DB, it is kept up to date by later updates
+fn export_geopackage(sqlite_file: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let connection = open_and_migrate(&sqlite_file)?;
+
+    let count = connection.write_geopackage(&CollisionQuery::new())?;
+    println!(
+        "Wrote {count} collisions to the {FEATURES_TABLE} layer of {sqlite_file}",
+        sqlite_file = sqlite_file.display()
+    );
+    Ok(())
+}
+
+/// Rerun the road fixups with the current overlay data
+fn roads_fix(
+    sqlite_file: PathBuf,
+    schema: PathBuf,
+    corrections: CorrectionsArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let schemas = corrections.load_schema(&schema)?;
+    let connection = open_and_migrate(&sqlite_file)?;
+
+    connection.refix_roads(&schemas)?;
 
     println!(
-        "Successfully imported data, writing DB to {sqlite_file}",
+        "Successfully fixed roads in {sqlite_file}",
         sqlite_file = sqlite_file.display()
     );
-    connection.backup(DatabaseName::Main, sqlite_file, None)?;
+    Ok(())
+}
+
+/// Interactively review the unknown roads of each overlay, and add the corrections to its road_typos
+fn roads_review(
+    sqlite_file: PathBuf,
+    schema: PathBuf,
+    overlay: Option<String>,
+    limit: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let schemas = Schema::from_toml_file(&schema)?;
+    let connection = open_and_migrate(&sqlite_file)?;
+
+    if let Some(name) = &overlay {
+        if !schemas.overlays().iter().any(|o| o.name() == name) {
+            return Err(format!("unknown overlay: {name}").into());
+        }
+    }
+
+    let mut added = 0;
+    for overlay in schemas
+        .overlays()
+        .iter()
+        .filter(|o| overlay.as_deref().is_none_or(|name| o.name() == name))
+    {
+        let Some(road_typos) = overlay.table_data("road_typos") else {
+            println!(
+                "SKIPPING {overlay}, it has no road_typos",
+                overlay = overlay.name()
+            );
+            continue;
+        };
+
+        let mut unresolved = connection.unresolved_roads(overlay)?;
+        let total = unresolved.len();
+        unresolved.truncate(limit.unwrap_or(total));
+        println!(
+            "REVIEWING {count} of {total} unknown roads for {overlay}",
+            count = unresolved.len(),
+            overlay = overlay.name()
+        );
+
+        let known_roads = connection.known_roads(overlay)?;
+        let corrections =
+            roads::review_roads(&unresolved, &known_roads, io::stdin().lock(), io::stdout())?;
+        let count = roads::add_road_typos(&road_typos, &corrections)?;
+        println!(
+            "Added {count} roads to {road_typos}",
+            road_typos = road_typos.display()
+        );
+        added += count;
+    }
+
+    if added > 0 {
+        println!(
+            "Run `roads fix -f {sqlite_file}` to apply the corrections",
+            sqlite_file = sqlite_file.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Serve the DB with datasette, until it's stopped
+fn serve(
+    sqlite_file: PathBuf,
+    port: Option<u16>,
+    datasette_args: Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !sqlite_file.exists() {
+        return Err(format!(
+            "{sqlite_file} does not exist",
+            sqlite_file = sqlite_file.display()
+        )
+        .into());
+    }
+
+    let mut datasette = process::Command::new("datasette");
+    datasette.arg("serve").arg(&sqlite_file);
+    if let Some(port) = port {
+        datasette.arg("--port").arg(port.to_string());
+    }
+    datasette.args(datasette_args);
+
+    let status = datasette.status().map_err(|e| {
+        format!("failed to run datasette, install it with `pip install datasette`: {e}")
+    })?;
+    if !status.success() {
+        return Err(format!("datasette failed, {status}").into());
+    }
 
     Ok(())
 }
@@ -0,0 +1,311 @@
+//! Versioned migrations for upgrading a published DB in place
+//!
+//! A freshly built DB always has the latest schema from `schema/*.sql`, so it is stamped with the latest
+//!   migration version. The migrations in `migrations/` are only applied to DBs built by older versions.
+
+use include_dir::{include_dir, Dir};
+use rusqlite::{Connection, Transaction};
+use rusqlite_migration::{HookError, HookResult, Migrations, MigrationsBuilder};
+
+use crate::schema::NewDB;
+
+static MIGRATIONS_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/migrations");
+
+/// All the migrations in the `migrations` directory
+pub fn migrations() -> Result<Migrations<'static>, rusqlite_migration::Error> {
+    let migrations = MigrationsBuilder::from_directory(&MIGRATIONS_DIR)?
+        .edit(2, |m| m.set_up_hook(recreate_collisions_view))
+        .finalize();
+
+    Ok(migrations)
+}
+
+/// The join to corrected_roads in collisions_view before `02-overlays`
+const CORRECTED_ROADS_JOIN: &str =
+    "LEFT JOIN corrected_roads ON c.case_id = corrected_roads.case_id\n";
+
+/// The join to corrected_roads in collisions_view since `02-overlays`, see `schema/collisions.sql`
+const OVERLAY_CORRECTED_ROADS_JOIN: &str = "\
+-- each overlay has its own corrections, when the jurisdictions of overlays overlap the first overlay by name is used
+LEFT JOIN corrected_roads ON c.case_id = corrected_roads.case_id
+    AND corrected_roads.overlay = (SELECT min(o.overlay) FROM corrected_roads AS o WHERE o.case_id = c.case_id)
+";
+
+/// Recreate collisions_view in `02-overlays`, so that it only joins one correction for each collision.
+///   The view is created from the jurisdictions in Schemas.toml, so the existing view is recreated with only the join replaced
+fn recreate_collisions_view(tx: &Transaction<'_>) -> HookResult {
+    let view: String = tx.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'view' AND name = 'collisions_view'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !view.contains(CORRECTED_ROADS_JOIN) {
+        return Err(HookError::Hook(
+            "collisions_view has an unexpected join to corrected_roads, rebuild the DB".to_string(),
+        ));
+    }
+
+    tx.execute_batch(&format!(
+        "DROP VIEW collisions_view;\n{view};",
+        view = view.replacen(CORRECTED_ROADS_JOIN, OVERLAY_CORRECTED_ROADS_JOIN, 1)
+    ))?;
+    Ok(())
+}
+
+/// A single migration and whether it has been applied to the DB
+#[derive(Debug, Eq, PartialEq)]
+pub struct MigrationStatus {
+    /// Version of the DB after this migration is applied, this is the `user_version` pragma
+    pub version: usize,
+    /// Name of the migration directory, e.g. `01-baseline`
+    pub name: &'static str,
+    /// True if the DB is at or past this version
+    pub applied: bool,
+}
+
+/// Names of all the migrations, ordered by version
+fn migration_names() -> Vec<(usize, &'static str)> {
+    let mut names = MIGRATIONS_DIR
+        .dirs()
+        .filter_map(|dir| {
+            let name = dir.path().file_name()?.to_str()?;
+            let (version, _) = name.split_once('-')?;
+            Some((version.parse().ok()?, name))
+        })
+        .collect::<Vec<_>>();
+
+    names.sort();
+    names
+}
+
+/// Apply all pending migrations to the DB, returns the number of migrations that were applied
+pub fn migrate_to_latest(connection: &mut Connection) -> Result<usize, Box<dyn std::error::Error>> {
+    let before = connection.user_version()?;
+    migrations()?.to_latest(connection)?;
+    let after = connection.user_version()?;
+
+    Ok(after.saturating_sub(before))
+}
+
+/// Extensions to the DB Connection for tracking the schema version
+pub trait MigrateDB: NewDB {
+    /// The current `user_version` of the DB, 0 if it was never set
+    fn user_version(&self) -> Result<usize, rusqlite::Error> {
+        self.connection()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+    }
+
+    /// Stamp the DB with the latest migration version, used after building the DB from the current schema
+    fn mark_migrations_applied(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let latest = migration_names()
+            .last()
+            .map(|(version, _)| *version)
+            .unwrap_or(0);
+
+        self.connection()
+            .pragma_update(None, "user_version", latest)?;
+        Ok(())
+    }
+
+    /// List all the migrations, and if they have been applied to this DB
+    fn migration_status(&self) -> Result<Vec<MigrationStatus>, Box<dyn std::error::Error>> {
+        let current = self.user_version()?;
+
+        let status = migration_names()
+            .into_iter()
+            .map(|(version, name)| MigrationStatus {
+                version,
+                name,
+                applied: version <= current,
+            })
+            .collect();
+
+        Ok(status)
+    }
+}
+
+impl MigrateDB for Connection {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::test_db;
+
+    /// Column names and types of the table or view
+    fn table_info(connection: &Connection, table: &str) -> Vec<(String, String)> {
+        let mut stmt = connection
+            .prepare(&format!("PRAGMA table_info({table})"))
+            .expect("bad pragma");
+        let columns = stmt
+            .query_map([], |row| Ok((row.get("name")?, row.get("type")?)))
+            .expect("failed to query")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("failed to read columns");
+
+        assert!(!columns.is_empty(), "{table} does not exist");
+        columns
+    }
+
+    #[test]
+    fn test_migrations_load() {
+        let migrations = migrations().expect("failed to load migrations");
+        migrations.validate().expect("invalid migrations");
+
+        let names = migration_names();
+        assert_eq!((1, "01-baseline"), names[0]);
+        for (i, (version, _)) in names.iter().enumerate() {
+            assert_eq!(i + 1, *version, "migration versions must be sequential");
+        }
+    }
+
+    #[test]
+    fn test_migration_status() {
+        let mut connection = Connection::open_in_memory().expect("failed to open in memory DB");
+
+        let status = connection.migration_status().expect("failed to get status");
+        assert!(status.iter().all(|m| !m.applied));
+
+        // an old DB, without any version, is brought up to the latest
+        let applied = migrate_to_latest(&mut connection).expect("failed to migrate");
+        assert_eq!(migration_names().len(), applied);
+
+        let status = connection.migration_status().expect("failed to get status");
+        assert!(status.iter().all(|m| m.applied));
+    }
+
+    #[test]
+    fn test_mark_migrations_applied() {
+        let connection = Connection::open_in_memory().expect("failed to open in memory DB");
+
+        connection
+            .mark_migrations_applied()
+            .expect("failed to set version");
+        assert_eq!(
+            migration_names().len(),
+            connection.user_version().expect("no version")
+        );
+    }
+
+    #[test]
+    fn test_migrate_from_baseline() {
+        // a DB as it was built at the baseline
+        let mut connection = Connection::open_in_memory().expect("failed to open in memory DB");
+        migrations()
+            .expect("failed to load migrations")
+            .to_version(&mut connection, 1)
+            .expect("failed to create baseline");
+        connection
+            .execute_batch(
+                "INSERT INTO berkeley_road_typos (normalized_rd, correct_rd) VALUES
+                    ('10TH', 'TENTH ST'),
+                    ('UNIVERSITY', 'UNIVERSITY AVE'),
+                    ('SACRAMENTO', 'SACRAMENTO ST'),
+                    ('SAN PABLO AVE', 'SAN PABLO AVE');
+                INSERT INTO cnty_city_loc (id, county, city) VALUES('0103', 'Alameda', 'Berkeley');
+                INSERT INTO collisions (case_id, cnty_city_loc, primary_rd, secondary_rd) VALUES
+                    ('1', '0103', 'SHATTUCK', NULL),
+                    ('9629920', NULL, 'UNIVERSITY', 'SACRAMENTO'),
+                    ('9631228', NULL, 'SAN PABLO AVE', 'UNIV');
+                INSERT INTO normalized_roads (case_id, primary_rd, secondary_rd) VALUES
+                    ('9629920', 'UNIVERSITY', 'SACRAMENTO'),
+                    ('9631228', 'SAN PABLO AVE', 'UNIV');
+                INSERT INTO corrected_roads (case_id, primary_rd, secondary_rd) VALUES
+                    ('1', 'SHATTUCK AVE', NULL),
+                    ('9629920', 'UNIVERSITY AVE', ''),
+                    ('9631228', 'SAN PABLO AVE', 'UNIVERSITY AVE');",
+            )
+            .expect("failed to insert");
+
+        let applied = migrate_to_latest(&mut connection).expect("failed to migrate");
+        assert_eq!(migration_names().len() - 1, applied);
+
+        // the migrated tables and views must match a DB freshly created from the schema
+        let fresh = test_db(&[
+            "road_typos",
+            "corrected_roads",
+            "normalized_roads",
+            "collisions",
+            "intersection_improvements",
+            "corridor_cross_streets",
+            "street_improvements",
+            "intersections",
+            "collision_coordinates",
+            "collision_points",
+        ]);
+        for table in [
+            "road_typos",
+            "corrected_roads",
+            "normalized_roads",
+            "collisions_view",
+            "intersection_improvements",
+            "intersection_improvements_view",
+            "intersection_performance_view",
+            "corridor_cross_streets",
+            "corridor_collisions_view",
+            "street_improvements",
+            "street_improvements_view",
+            "street_performance_view",
+            "intersections",
+            "collision_coordinates",
+            "collision_points",
+        ] {
+            assert_eq!(
+                table_info(&fresh, table),
+                table_info(&connection, table),
+                "{table} differs"
+            );
+        }
+
+        let overlay: String = connection
+            .query_row(
+                "SELECT overlay FROM road_typos WHERE normalized_rd = '10TH'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("failed to query");
+        assert_eq!("berkeley", overlay);
+
+        // the sources of the existing corrections are derived from the roads as reported
+        let provenance = |case_id: &str| -> (String, String, String) {
+            connection
+                .query_row(
+                    "SELECT primary_rd_source, secondary_rd_source, original_primary_rd FROM corrected_roads WHERE case_id = ?",
+                    [case_id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .expect("failed to query")
+        };
+        assert_eq!(
+            (
+                "typo".to_string(),
+                "unresolved".to_string(),
+                "UNIVERSITY".to_string()
+            ),
+            provenance("9629920")
+        );
+        assert_eq!(
+            (
+                "verified".to_string(),
+                "manual".to_string(),
+                "SAN PABLO AVE".to_string()
+            ),
+            provenance("9631228")
+        );
+
+        // another overlay's correction of the same collision is kept, and collisions_view uses the first
+        connection
+            .execute(
+                "INSERT INTO corrected_roads (case_id, primary_rd, overlay) VALUES('1', 'SHATTUCK PL', 'oakland')",
+                [],
+            )
+            .expect("failed to insert");
+        let corrected: Vec<String> = connection
+            .prepare("SELECT corrected_primary_rd FROM collisions_view WHERE case_id = '1'")
+            .expect("bad select")
+            .query_map([], |row| row.get(0))
+            .expect("failed to query")
+            .collect::<Result<_, _>>()
+            .expect("failed to read collisions_view");
+        assert_eq!(vec!["SHATTUCK AVE".to_string()], corrected);
+    }
+}
@@ -0,0 +1,93 @@
+//! City specific overlays, road name fixes and safety improvements maintained by local advocates
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::schema::Jurisdictions;
+
+/// File name of the manifest in each overlay directory
+pub const OVERLAY_MANIFEST: &str = "overlay.toml";
+
+/// An overlay directory, as declared by its `overlay.toml` manifest
+#[derive(Debug, Deserialize)]
+pub struct Overlay {
+    /// Unique name of the overlay, this is stored in the `overlay` column of each overlay table
+    pub(crate) name: String,
+
+    /// Collisions in these jurisdictions have their roads fixed with this overlay
+    pub(crate) jurisdictions: Jurisdictions,
+
+    /// CSV data for each of the overlay tables, like road_typos, relative to the overlay directory
+    tables: HashMap<String, PathBuf>,
+
+    /// GeoJSON outline of each city of the jurisdictions, relative to the overlay directory, for checking the
+    ///   coordinates of the collisions
+    #[serde(default)]
+    boundaries: HashMap<String, PathBuf>,
+
+    /// Directory the manifest was loaded from
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+impl Overlay {
+    /// Loads the overlay from the `overlay.toml` manifest in the directory
+    pub fn from_dir(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let manifest = dir.join(OVERLAY_MANIFEST);
+        let mut overlay: Self = basic_toml::from_slice(&fs::read(&manifest).map_err(|e| {
+            format!(
+                "failed to read {manifest}: {e}",
+                manifest = manifest.display()
+            )
+        })?)?;
+
+        overlay.dir = dir.to_path_buf();
+        Ok(overlay)
+    }
+
+    /// Name of the overlay
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Path to the CSV data for the table, if the overlay has data for it
+    pub fn table_data(&self, table: &str) -> Option<PathBuf> {
+        self.tables.get(table).map(|path| self.dir.join(path))
+    }
+
+    /// Each city with a boundary, and the path to its GeoJSON
+    pub fn boundaries(&self) -> impl Iterator<Item = (&str, PathBuf)> {
+        self.boundaries
+            .iter()
+            .map(|(city, path)| (city.as_str(), self.dir.join(path)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_berkeley_overlay() {
+        let overlay = Overlay::from_dir(Path::new("berkeley-tables")).expect("bad overlay");
+
+        assert_eq!("berkeley", overlay.name());
+        assert_eq!(
+            Some(PathBuf::from("berkeley-tables/BERKELEY_ROAD_TYPOS.csv")),
+            overlay.table_data("road_typos")
+        );
+        assert_eq!(None, overlay.table_data("collisions"));
+        assert_eq!(
+            vec![(
+                "Berkeley",
+                PathBuf::from("berkeley-tables/BERKELEY_BOUNDARY.geojson")
+            )],
+            overlay.boundaries().collect::<Vec<_>>()
+        );
+    }
+}
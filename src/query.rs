@@ -0,0 +1,710 @@
+//! Query API for the common safety analyses, like all the bike collisions on a street between two dates
+//!
+//! The queries are compiled to SQL over `collisions_view`, so they use the corrected road names and are limited
+//!   to the jurisdictions the DB was built for, and return typed `Collision` records. The spatial filters use
+//!   `collision_coordinates`, so they include the collisions with imputed coordinates.
+
+use rusqlite::{params_from_iter, types::Value, Connection};
+use time::{Date, Month};
+
+use crate::{
+    records::{Collision, CollisionSeverity, PartySex, PartyType, RecordsDB},
+    schema::NewDB,
+    spatial::{meters_per_degree_longitude, METERS_PER_DEGREE},
+};
+
+/// Mode of travel involved in a collision, as flagged on the collision
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
+pub enum Mode {
+    /// A pedestrian was involved
+    Pedestrian,
+    /// A bicycle was involved
+    Bicycle,
+    /// A motorcycle was involved
+    Motorcycle,
+    /// A big truck was involved
+    Truck,
+}
+
+impl Mode {
+    /// Column in the collisions table flagging the mode with "Y"
+    fn column(self) -> &'static str {
+        match self {
+            Self::Pedestrian => "pedestrian_accident",
+            Self::Bicycle => "bicycle_accident",
+            Self::Motorcycle => "motorcycle_accident",
+            Self::Truck => "truck_accident",
+        }
+    }
+}
+
+/// Where a collision occurred along the road, see collision_locations_view
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LocationType {
+    /// At the intersection of the primary and secondary roads
+    Intersection,
+    /// On the primary road, some distance from the secondary road
+    Midblock,
+    /// Neither the intersection nor the distance were reported
+    Unknown,
+}
+
+impl LocationType {
+    /// Value of the location_type column in collision_locations_view
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Intersection => "intersection",
+            Self::Midblock => "midblock",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Rectangle of latitude and longitude, longitude is negative in California
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    /// Southern edge
+    pub min_latitude: f64,
+    /// Western edge
+    pub min_longitude: f64,
+    /// Northern edge
+    pub max_latitude: f64,
+    /// Eastern edge
+    pub max_longitude: f64,
+}
+
+impl BoundingBox {
+    /// The smallest box containing the circle of `meters` around the point
+    pub fn around(latitude: f64, longitude: f64, meters: f64) -> Self {
+        let latitude_delta = meters / METERS_PER_DEGREE;
+        let longitude_delta = meters / meters_per_degree_longitude(latitude);
+        Self {
+            min_latitude: latitude - latitude_delta,
+            min_longitude: longitude - longitude_delta,
+            max_latitude: latitude + latitude_delta,
+            max_longitude: longitude + longitude_delta,
+        }
+    }
+
+    /// Condition matching the collisions `c` in the box using the `collision_points` spatial index, the index
+    ///   is only approximate so it should be refined on the exact location
+    fn indexed_condition(&self) -> (String, [Value; 4]) {
+        (
+            "c.rowid IN (SELECT id FROM collision_points
+                WHERE max_latitude >= ? AND min_latitude <= ? AND max_longitude >= ? AND min_longitude <= ?)"
+                .to_string(),
+            [
+                Value::Real(self.min_latitude),
+                Value::Real(self.max_latitude),
+                Value::Real(self.min_longitude),
+                Value::Real(self.max_longitude),
+            ],
+        )
+    }
+}
+
+/// Attributes of a party, a collision matches when any one of its parties has all of the attributes
+#[derive(Clone, Debug, Default)]
+pub struct PartyFilter {
+    party_type: Option<PartyType>,
+    party_sex: Option<PartySex>,
+    min_age: Option<u16>,
+    max_age: Option<u16>,
+    at_fault: Option<bool>,
+}
+
+impl PartyFilter {
+    /// A filter matching any party
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The party is of this type, e.g. a Bicyclist
+    pub fn party_type(mut self, party_type: PartyType) -> Self {
+        self.party_type = Some(party_type);
+        self
+    }
+
+    /// The party is of this sex
+    pub fn party_sex(mut self, party_sex: PartySex) -> Self {
+        self.party_sex = Some(party_sex);
+        self
+    }
+
+    /// The party's age is between min and max, inclusive
+    pub fn age(mut self, min: u16, max: u16) -> Self {
+        self.min_age = Some(min);
+        self.max_age = Some(max);
+        self
+    }
+
+    /// The party was, or was not, at fault
+    pub fn at_fault(mut self, at_fault: bool) -> Self {
+        self.at_fault = Some(at_fault);
+        self
+    }
+}
+
+/// Builder for a query of the collisions, all the filters must match
+#[derive(Clone, Debug, Default)]
+pub struct CollisionQuery {
+    conditions: Vec<String>,
+    params: Vec<Value>,
+    limit: Option<usize>,
+}
+
+impl CollisionQuery {
+    /// A query for all the collisions in the DB's jurisdictions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a condition, each `?` in the condition is bound to the next param
+    fn condition(mut self, condition: String, params: impl IntoIterator<Item = Value>) -> Self {
+        self.conditions.push(condition);
+        self.params.extend(params);
+        self
+    }
+
+    /// Collisions which occurred between the two dates, inclusive
+    pub fn between(self, from: Date, to: Date) -> Self {
+        self.condition(
+            "substr(cv.collision_datetime, 1, 10) BETWEEN ? AND ?".to_string(),
+            [Value::Text(from.to_string()), Value::Text(to.to_string())],
+        )
+    }
+
+    /// Collisions where the primary or secondary road is `road`, using the corrected road names when there
+    ///   are corrections
+    pub fn on_road(self, road: &str) -> Self {
+        self.condition(
+            format!(
+                "({primary} = upper(?) OR {secondary} = upper(?))",
+                primary = PRIMARY_RD,
+                secondary = SECONDARY_RD
+            ),
+            [Value::Text(road.to_string()), Value::Text(road.to_string())],
+        )
+    }
+
+    /// Collisions at the intersection of the two roads, in either order, using the corrected road names
+    pub fn at_intersection(self, road: &str, cross_road: &str) -> Self {
+        self.condition(
+            format!(
+                "(({primary} = upper(?) AND {secondary} = upper(?)) OR ({primary} = upper(?) AND {secondary} = upper(?)))",
+                primary = PRIMARY_RD,
+                secondary = SECONDARY_RD
+            ),
+            [
+                Value::Text(road.to_string()),
+                Value::Text(cross_road.to_string()),
+                Value::Text(cross_road.to_string()),
+                Value::Text(road.to_string()),
+            ],
+        )
+    }
+
+    /// Collisions at the location type, e.g. only the midblock collisions
+    pub fn located(self, location_type: LocationType) -> Self {
+        self.condition(
+            "c.case_id IN (SELECT l.case_id FROM collision_locations_view AS l WHERE l.location_type = ?)"
+                .to_string(),
+            [Value::Text(location_type.as_str().to_string())],
+        )
+    }
+
+    /// Collisions which involved the mode of travel
+    pub fn involving(self, mode: Mode) -> Self {
+        self.condition(format!("c.{column} = 'Y'", column = mode.column()), [])
+    }
+
+    /// Collisions with any of the severities
+    pub fn severity(self, severities: &[CollisionSeverity]) -> Self {
+        let placeholders = vec!["?"; severities.len()].join(", ");
+        self.condition(
+            format!("c.collision_severity IN ({placeholders})"),
+            severities
+                .iter()
+                .map(|severity| Value::Text(severity.code().to_string())),
+        )
+    }
+
+    /// Collisions where someone was killed or severely injured, KSI
+    pub fn killed_or_severely_injured(self) -> Self {
+        self.severity(&[CollisionSeverity::Fatal, CollisionSeverity::SevereInjury])
+    }
+
+    /// Collisions located within the bounding box, collisions without a latitude/longitude never match
+    pub fn within(self, bounds: BoundingBox) -> Self {
+        let (indexed, params) = bounds.indexed_condition();
+        self.condition(indexed, params).condition(
+            "cc.latitude BETWEEN ? AND ? AND cc.longitude BETWEEN ? AND ?".to_string(),
+            [
+                Value::Real(bounds.min_latitude),
+                Value::Real(bounds.max_latitude),
+                Value::Real(bounds.min_longitude),
+                Value::Real(bounds.max_longitude),
+            ],
+        )
+    }
+
+    /// Collisions with a latitude and longitude, either reported or imputed by the geocoding
+    pub fn with_coordinates(self) -> Self {
+        self.condition("cc.case_id IS NOT NULL".to_string(), [])
+    }
+
+    /// Collisions located within `meters` of the point, collisions without a latitude/longitude never match
+    pub fn near(self, latitude: f64, longitude: f64, meters: f64) -> Self {
+        let (indexed, params) =
+            BoundingBox::around(latitude, longitude, meters).indexed_condition();
+        let north = Value::Real(METERS_PER_DEGREE);
+        let east = Value::Real(meters_per_degree_longitude(latitude));
+        self.condition(indexed, params).condition(
+            "(cc.latitude - ?) * ? * (cc.latitude - ?) * ? + (cc.longitude - ?) * ? * (cc.longitude - ?) * ? <= ?"
+                .to_string(),
+            [
+                Value::Real(latitude),
+                north.clone(),
+                Value::Real(latitude),
+                north,
+                Value::Real(longitude),
+                east.clone(),
+                Value::Real(longitude),
+                east,
+                Value::Real(meters * meters),
+            ],
+        )
+    }
+
+    /// Collisions where any party matches all the attributes of the filter
+    pub fn with_party(self, party: PartyFilter) -> Self {
+        let mut conditions = vec!["p.case_id = c.case_id".to_string()];
+        let mut params = Vec::new();
+
+        if let Some(party_type) = party.party_type {
+            conditions.push("p.party_type = ?".to_string());
+            params.push(Value::Text(party_type.code().to_string()));
+        }
+        if let Some(party_sex) = party.party_sex {
+            conditions.push("p.party_sex = ?".to_string());
+            params.push(Value::Text(party_sex.code().to_string()));
+        }
+        if let Some(min_age) = party.min_age {
+            conditions.push("p.party_age >= ?".to_string());
+            params.push(Value::Integer(min_age.into()));
+        }
+        if let Some(max_age) = party.max_age {
+            conditions.push("p.party_age <= ?".to_string());
+            params.push(Value::Integer(max_age.into()));
+        }
+        if let Some(at_fault) = party.at_fault {
+            conditions.push("p.at_fault = ?".to_string());
+            params.push(Value::Text(if at_fault { "Y" } else { "N" }.to_string()));
+        }
+
+        self.condition(
+            format!(
+                "EXISTS (SELECT 1 FROM parties AS p WHERE {conditions})",
+                conditions = conditions.join(" AND ")
+            ),
+            params,
+        )
+    }
+
+    /// Return at most `limit` collisions
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The SQL and params for the query, the SQL selects `columns` from the collisions `c` joined with
+    ///   collisions_view `cv`, and the collision_coordinates `cc` when the collision has any
+    pub fn to_sql(&self, columns: &str) -> (String, &[Value]) {
+        let mut sql = format!(
+            "SELECT {columns} FROM collisions AS c JOIN collisions_view AS cv ON cv.case_id = c.case_id
+            LEFT JOIN collision_coordinates AS cc ON cc.case_id = c.case_id"
+        );
+        if !self.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY cv.collision_datetime, c.case_id");
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {limit}"));
+        }
+
+        (sql, &self.params)
+    }
+}
+
+/// Parse a `YYYY-MM-DD` date, anything after the date is ignored, like the time of the collision datetimes
+pub fn parse_date(date: &str) -> Result<Date, Box<dyn std::error::Error>> {
+    let bad_date = || format!("bad date, expected YYYY-MM-DD: {date}");
+    let mut parts = date.get(..10).ok_or_else(bad_date)?.splitn(3, '-');
+    let mut next = || -> Result<i32, Box<dyn std::error::Error>> {
+        Ok(parts.next().ok_or_else(bad_date)?.parse()?)
+    };
+    let (year, month, day) = (next()?, next()?, next()?);
+
+    Ok(Date::from_calendar_date(
+        year,
+        Month::try_from(u8::try_from(month)?)?,
+        u8::try_from(day)?,
+    )?)
+}
+
+/// Corrected primary road, falling back to the road as reported
+const PRIMARY_RD: &str = "upper(coalesce(cv.corrected_primary_rd, cv.primary_rd))";
+/// Corrected secondary road, falling back to the road as reported
+const SECONDARY_RD: &str = "upper(coalesce(cv.corrected_secondary_rd, cv.secondary_rd))";
+
+/// Extensions to the DB Connection to run the collision queries
+pub trait QueryDB: NewDB {
+    /// All the collisions matching the query, ordered by date
+    fn query_collisions(
+        &self,
+        query: &CollisionQuery,
+    ) -> Result<Vec<Collision>, Box<dyn std::error::Error>> {
+        let (sql, params) = query.to_sql("c.*");
+        let mut collisions = Vec::new();
+        self.connection()
+            .query_records(&sql, params_from_iter(params), |collision| {
+                collisions.push(collision);
+                Ok(())
+            })?;
+
+        Ok(collisions)
+    }
+
+    /// Number of collisions matching the query
+    fn count_collisions(
+        &self,
+        query: &CollisionQuery,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let (sql, params) = query.to_sql("c.case_id");
+        let count = self.connection().query_row(
+            &format!("SELECT count(*) FROM ({sql})"),
+            params_from_iter(params),
+            |row| row.get(0),
+        )?;
+
+        Ok(count)
+    }
+}
+
+impl QueryDB for Connection {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rusqlite::OptionalExtension;
+
+    use super::*;
+    use crate::{geocode::GeocodeDB, overlay::Overlay, schema::test_db, spatial::SpatialDB};
+
+    /// Create all the tables collisions_view needs, with the test data and a corrected road loaded
+    fn corrected_db() -> Connection {
+        let connection = test_db(&[
+            "corrected_roads",
+            "collisions",
+            "parties",
+            "intersections",
+            "collision_coordinates",
+            "collision_points",
+        ]);
+        connection
+            .execute(
+                "INSERT INTO corrected_roads (case_id, primary_rd, secondary_rd, overlay)
+                VALUES('9629920', 'UNIVERSITY AVE', 'SACRAMENTO ST', 'berkeley')",
+                [],
+            )
+            .expect("failed to insert");
+        connection.geocode_collisions().expect("failed to geocode");
+        connection
+            .index_collision_points()
+            .expect("failed to index collision points");
+
+        connection
+    }
+
+    #[test]
+    fn test_all() {
+        let connection = corrected_db();
+
+        let collisions = connection
+            .query_collisions(&CollisionQuery::new())
+            .expect("failed to query");
+        assert_eq!(40, collisions.len());
+        assert_eq!(
+            40,
+            connection
+                .count_collisions(&CollisionQuery::new())
+                .expect("failed to count")
+        );
+
+        let limited = connection
+            .query_collisions(&CollisionQuery::new().limit(5))
+            .expect("failed to query");
+        assert_eq!(&collisions[..5], &limited[..]);
+    }
+
+    #[test]
+    fn test_filters() {
+        let connection = corrected_db();
+        let count = |query: CollisionQuery| {
+            connection
+                .count_collisions(&query)
+                .expect("failed to count")
+        };
+
+        assert_eq!(4, count(CollisionQuery::new().involving(Mode::Bicycle)));
+        assert_eq!(6, count(CollisionQuery::new().involving(Mode::Pedestrian)));
+        assert_eq!(4, count(CollisionQuery::new().killed_or_severely_injured()));
+        assert_eq!(
+            20,
+            count(CollisionQuery::new().severity(&[
+                CollisionSeverity::OtherVisibleInjury,
+                CollisionSeverity::ComplaintOfPain
+            ]))
+        );
+
+        let september = Date::from_calendar_date(2023, Month::September, 1).expect("bad date");
+        let december = Date::from_calendar_date(2023, Month::December, 31).expect("bad date");
+        assert_eq!(
+            38,
+            count(CollisionQuery::new().between(september, december))
+        );
+
+        assert_eq!(
+            5,
+            count(CollisionQuery::new().within(BoundingBox {
+                min_latitude: 37.87,
+                min_longitude: -122.28,
+                max_latitude: 37.88,
+                max_longitude: -122.26,
+            }))
+        );
+
+        assert_eq!(
+            4,
+            count(
+                CollisionQuery::new()
+                    .with_party(PartyFilter::new().party_type(PartyType::Bicyclist))
+            )
+        );
+        assert_eq!(
+            8,
+            count(
+                CollisionQuery::new().with_party(
+                    PartyFilter::new()
+                        .party_type(PartyType::Driver)
+                        .at_fault(true)
+                        .age(16, 25)
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn test_roads() {
+        let connection = corrected_db();
+        let count = |query: CollisionQuery| {
+            connection
+                .count_collisions(&query)
+                .expect("failed to count")
+        };
+
+        // 9629920 was corrected from UNIVERSITY and SACRAMENTO
+        assert_eq!(1, count(CollisionQuery::new().on_road("University Ave")));
+        assert_eq!(1, count(CollisionQuery::new().on_road("university")));
+        assert_eq!(3, count(CollisionQuery::new().on_road("cedar")));
+        assert_eq!(
+            1,
+            count(CollisionQuery::new().at_intersection("Sacramento St", "University Ave"))
+        );
+        assert_eq!(
+            1,
+            count(CollisionQuery::new().at_intersection("SAN PABLO", "GILMAN"))
+        );
+        assert_eq!(
+            0,
+            count(
+                CollisionQuery::new()
+                    .at_intersection("SAN PABLO", "GILMAN")
+                    .involving(Mode::Truck)
+            )
+        );
+    }
+
+    #[test]
+    fn test_near() {
+        let connection = corrected_db();
+        let count = |query: CollisionQuery| {
+            connection
+                .count_collisions(&query)
+                .expect("failed to count")
+        };
+
+        // UNIVERSITY AVE and SACRAMENTO ST, the nearest collisions are on HEARST AVE at 639m and CEDAR ST at 701m
+        let (latitude, longitude) = (37.87041, -122.28194);
+        let near = |meters| {
+            connection
+                .query_collisions(&CollisionQuery::new().near(latitude, longitude, meters))
+                .expect("failed to query")
+                .into_iter()
+                .map(|collision| collision.case_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["9629920".to_string()], near(100.0));
+        assert_eq!(2, near(650.0).len());
+        assert_eq!(3, near(710.0).len());
+
+        // the box around the circle also has the corners
+        let bounds = BoundingBox::around(latitude, longitude, 1200.0);
+        assert!(
+            count(CollisionQuery::new().within(bounds))
+                > count(CollisionQuery::new().near(latitude, longitude, 1200.0))
+        );
+        assert_eq!(
+            0,
+            count(
+                CollisionQuery::new()
+                    .near(latitude, longitude, 1000.0)
+                    .between(
+                        Date::from_calendar_date(2023, Month::January, 1).expect("bad date"),
+                        Date::from_calendar_date(2023, Month::January, 31).expect("bad date"),
+                    )
+            )
+        );
+    }
+
+    #[test]
+    fn test_locations() {
+        let connection = corrected_db();
+        let count = |query: CollisionQuery| {
+            connection
+                .count_collisions(&query)
+                .expect("failed to count")
+        };
+
+        assert_eq!(
+            30,
+            count(CollisionQuery::new().located(LocationType::Intersection))
+        );
+        assert_eq!(
+            10,
+            count(CollisionQuery::new().located(LocationType::Midblock))
+        );
+        assert_eq!(
+            0,
+            count(CollisionQuery::new().located(LocationType::Unknown))
+        );
+
+        let descriptions = connection
+            .prepare(
+                "SELECT case_id, description FROM collision_locations_view
+                WHERE case_id IN ('9629920', '9634179') ORDER BY case_id",
+            )
+            .expect("bad query")
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .expect("failed to query")
+            .collect::<Result<Vec<(String, String)>, _>>()
+            .expect("bad row");
+        assert_eq!(
+            vec![
+                (
+                    "9629920".to_string(),
+                    "UNIVERSITY AVE and SACRAMENTO ST".to_string()
+                ),
+                (
+                    "9634179".to_string(),
+                    "86 ft south of OREGON ST on PARK ST".to_string()
+                ),
+            ],
+            descriptions
+        );
+    }
+
+    #[test]
+    fn test_street_performance() {
+        let connection = test_db(&[
+            "corrected_roads",
+            "collisions",
+            "parties",
+            "corridor_cross_streets",
+            "street_improvements",
+        ]);
+        let overlay = Overlay::from_dir(Path::new("berkeley-tables")).expect("bad overlay");
+        for table in ["corridor_cross_streets", "street_improvements"] {
+            connection
+                .load_overlay_data(table, &overlay)
+                .expect("failed to load overlay");
+        }
+
+        connection
+            .execute_batch(
+                "INSERT INTO corrected_roads (case_id, primary_rd, secondary_rd, overlay) VALUES
+                    ('9641156', 'MILVIA ST', 'CHANNING WAY', 'berkeley'),
+                    ('9641415', 'MILVIA ST', 'ADDISON ST', 'berkeley'),
+                    ('9641497', 'MILVIA ST', 'KITTREDGE ST', 'berkeley'),
+                    ('9637946', 'HEARST AVE', 'SHATTUCK AVE', 'berkeley'),
+                    ('9637942', 'VINE ST', 'SHATTUCK AVE', 'berkeley'),
+                    ('9641160', 'BANCROFT WAY', 'DANA ST', 'berkeley'),
+                    ('9634179', 'MILVIA ST', 'BLAKE ST', 'berkeley'),
+                    ('9634382', 'MILVIA ST', 'BLAKE ST', 'berkeley'),
+                    ('9637959', 'MILVIA ST', 'HEARST AVE', 'berkeley'),
+                    ('9641402', 'MILVIA ST', 'HEARST AVE', 'berkeley'),
+                    ('9634381', 'MILVIA ST', 'ADDISON ST', 'berkeley');",
+            )
+            .expect("failed to insert");
+
+        let positions = |case_id: &str| -> Option<(i64, i64)> {
+            connection
+                .query_row(
+                    "SELECT from_position, to_position FROM corridor_collisions_view WHERE case_id = ?",
+                    [case_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .expect("failed to query")
+        };
+
+        // 216 ft west of DANA ST is between DANA ST and ELLSWORTH ST
+        assert_eq!(Some((2, 3)), positions("9641160"));
+
+        // MILVIA ST runs south from HEARST AVE, the first cross street, to BLAKE ST, the last
+        assert_eq!(Some((1, 2)), positions("9637959"), "south of HEARST AVE");
+        assert_eq!(None, positions("9641402"), "north of HEARST AVE");
+        assert_eq!(Some((11, 12)), positions("9634382"), "north of BLAKE ST");
+        assert_eq!(None, positions("9634179"), "south of BLAKE ST");
+
+        // east of ADDISON ST is across MILVIA ST
+        assert_eq!(None, positions("9634381"), "east of ADDISON ST");
+
+        let collisions = connection
+            .prepare(
+                "SELECT id, case_id, location_type, before_improvement FROM street_performance_view
+                WHERE case_id IS NOT NULL ORDER BY id, case_id",
+            )
+            .expect("bad query")
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .expect("failed to query")
+            .collect::<Result<Vec<(i64, String, String, bool)>, _>>()
+            .expect("bad row");
+        assert_eq!(
+            vec![
+                (2, "9641160".to_string(), "midblock".to_string(), false),
+                (5, "9637946".to_string(), "intersection".to_string(), false),
+                (6, "9634382".to_string(), "midblock".to_string(), false),
+                (6, "9637959".to_string(), "midblock".to_string(), false),
+                (6, "9641156".to_string(), "intersection".to_string(), false),
+                (6, "9641415".to_string(), "intersection".to_string(), false),
+                (6, "9641497".to_string(), "intersection".to_string(), false),
+            ],
+            collisions
+        );
+    }
+}
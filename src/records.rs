@@ -0,0 +1,1047 @@
+//! Typed records for the collisions, parties and victims tables
+//!
+//! The records can be read from either the raw SWITRS export, or from a built DB. Both are read as CSV records
+//!   with lowercase headers, matching the columns in `schema/*.sql`. Coded columns with an enum are validated
+//!   against their `lookup-tables` CSV in the tests, codes that are missing from the enum are kept as `Unknown`.
+
+use std::{fmt, path::Path};
+
+use csv::StringRecord;
+use rusqlite::{types::ValueRef, Connection, Params};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+
+use crate::schema::NewDB;
+
+/// Define an enum for a coded column, where each variant is one of the codes in the lookup table
+macro_rules! coded_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident => $lookup:literal {
+            $($(#[$variant_meta:meta])* $variant:ident = $code:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+        #[serde(from = "String", into = "String")]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)+
+            /// A code that is missing from the lookup table
+            Unknown(String),
+        }
+
+        impl $name {
+            /// Path to the lookup table CSV with the names of all the codes
+            pub const LOOKUP_TABLE: &'static str = $lookup;
+
+            /// The code as it appears in the raw data and the lookup table
+            pub fn code(&self) -> &str {
+                match self {
+                    $(Self::$variant => $code,)+
+                    Self::Unknown(code) => code,
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(code: String) -> Self {
+                match code.as_str() {
+                    $($code => Self::$variant,)+
+                    _ => Self::Unknown(code),
+                }
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.code().to_string()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.code())
+            }
+        }
+    };
+}
+
+coded_enum! {
+    /// Collision Severity, the most severe injury in the collision
+    CollisionSeverity => "lookup-tables/COLLISION_SEVERITY.csv" {
+        /// Fatal
+        Fatal = "1",
+        /// Injury (Severe)
+        SevereInjury = "2",
+        /// Injury (Other Visible)
+        OtherVisibleInjury = "3",
+        /// Injury (Complaint of Pain)
+        ComplaintOfPain = "4",
+        /// Property Damage Only
+        PropertyDamageOnly = "0",
+    }
+}
+
+coded_enum! {
+    /// Day of the week when the collision occurred
+    DayOfWeek => "lookup-tables/DAY_OF_WEEK.csv" {
+        /// Monday
+        Monday = "1",
+        /// Tuesday
+        Tuesday = "2",
+        /// Wednesday
+        Wednesday = "3",
+        /// Thursday
+        Thursday = "4",
+        /// Friday
+        Friday = "5",
+        /// Saturday
+        Saturday = "6",
+        /// Sunday
+        Sunday = "7",
+    }
+}
+
+coded_enum! {
+    /// Weather condition at the time of the collision
+    Weather => "lookup-tables/WEATHER_1.csv" {
+        /// Clear
+        Clear = "A",
+        /// Cloudy
+        Cloudy = "B",
+        /// Raining
+        Raining = "C",
+        /// Snowing
+        Snowing = "D",
+        /// Fog
+        Fog = "E",
+        /// Other
+        Other = "F",
+        /// Wind
+        Wind = "G",
+        /// Not Stated
+        NotStated = "-",
+    }
+}
+
+coded_enum! {
+    /// Hit And Run
+    HitAndRun => "lookup-tables/HIT_AND_RUN.csv" {
+        /// Felony
+        Felony = "F",
+        /// Misdemeanor
+        Misdemeanor = "M",
+        /// Not Hit and Run
+        NotHitAndRun = "N",
+    }
+}
+
+coded_enum! {
+    /// Type of Collision
+    TypeOfCollision => "lookup-tables/TYPE_OF_COLLISION.csv" {
+        /// Head-On
+        HeadOn = "A",
+        /// Sideswipe
+        Sideswipe = "B",
+        /// Rear End
+        RearEnd = "C",
+        /// Broadside
+        Broadside = "D",
+        /// Hit Object
+        HitObject = "E",
+        /// Overturned
+        Overturned = "F",
+        /// Vehicle/Pedestrian
+        VehiclePedestrian = "G",
+        /// Other
+        Other = "H",
+        /// Not Stated
+        NotStated = "-",
+    }
+}
+
+coded_enum! {
+    /// Pedestrian Action
+    PedAction => "lookup-tables/PED_ACTION.csv" {
+        /// No Pedestrian Involved
+        NoPedestrianInvolved = "A",
+        /// Crossing in Crosswalk at Intersection
+        CrosswalkAtIntersection = "B",
+        /// Crossing in Crosswalk Not at Intersection
+        CrosswalkNotAtIntersection = "C",
+        /// Crossing Not in Crosswalk
+        NotInCrosswalk = "D",
+        /// In Road, Including Shoulder
+        InRoad = "E",
+        /// Not in Road
+        NotInRoad = "F",
+        /// Approaching/Leaving School Bus
+        SchoolBus = "G",
+        /// Not Stated
+        NotStated = "-",
+    }
+}
+
+coded_enum! {
+    /// Road Surface
+    RoadSurface => "lookup-tables/ROAD_SURFACE.csv" {
+        /// Dry
+        Dry = "A",
+        /// Wet
+        Wet = "B",
+        /// Snowy or Icy
+        SnowyOrIcy = "C",
+        /// Slippery (Muddy, Oily, etc.)
+        Slippery = "D",
+        /// Not Stated
+        NotStated = "-",
+    }
+}
+
+coded_enum! {
+    /// Lighting
+    Lighting => "lookup-tables/LIGHTING.csv" {
+        /// Daylight
+        Daylight = "A",
+        /// Dusk - Dawn
+        DuskDawn = "B",
+        /// Dark - Street Lights
+        DarkStreetLights = "C",
+        /// Dark - No Street Lights
+        DarkNoStreetLights = "D",
+        /// Dark - Street Lights Not Functioning
+        DarkStreetLightsNotFunctioning = "E",
+        /// Not Stated
+        NotStated = "-",
+    }
+}
+
+coded_enum! {
+    /// Party Type
+    PartyType => "lookup-tables/party-tables/PARTY_TYPE.csv" {
+        /// Driver (including Hit and Run)
+        Driver = "1",
+        /// Pedestrian
+        Pedestrian = "2",
+        /// Parked Vehicle
+        ParkedVehicle = "3",
+        /// Bicyclist
+        Bicyclist = "4",
+        /// Other
+        Other = "5",
+        /// Undefined in RawData_template
+        Undefined = "6",
+        /// Not Stated
+        NotStated = "-",
+    }
+}
+
+coded_enum! {
+    /// Party Sex
+    PartySex => "lookup-tables/party-tables/PARTY_SEX.csv" {
+        /// Male
+        Male = "M",
+        /// Female
+        Female = "F",
+        /// Undefined in RawData_template
+        Undefined = "X",
+        /// Not Stated
+        NotStated = "-",
+    }
+}
+
+coded_enum! {
+    /// Party Sobriety
+    PartySobriety => "lookup-tables/party-tables/PARTY_SOBRIETY.csv" {
+        /// Had Not Been Drinking
+        NotDrinking = "A",
+        /// Had Been Drinking, Under Influence
+        UnderInfluence = "B",
+        /// Had Been Drinking, Not Under Influence
+        NotUnderInfluence = "C",
+        /// Had Been Drinking, Impairment Unknown
+        DrinkingImpairmentUnknown = "D",
+        /// Impairment Unknown
+        ImpairmentUnknown = "G",
+        /// Not Applicable
+        NotApplicable = "H",
+        /// Not Stated
+        NotStated = "-",
+    }
+}
+
+coded_enum! {
+    /// Statewide Vehicle Type
+    StatewideVehicleType => "lookup-tables/party-tables/STATEWIDE_VEHICLE_TYPE.csv" {
+        /// Passenger Car/Station Wagon
+        PassengerCar = "A",
+        /// Passenger Car with Trailer
+        PassengerCarWithTrailer = "B",
+        /// Motorcycle/Scooter
+        Motorcycle = "C",
+        /// Pickup or Panel Truck
+        PickupTruck = "D",
+        /// Pickup or Panel Truck with Trailer
+        PickupTruckWithTrailer = "E",
+        /// Truck or Truck Tractor
+        Truck = "F",
+        /// Truck or Truck Tractor with Trailer
+        TruckWithTrailer = "G",
+        /// Schoolbus
+        Schoolbus = "H",
+        /// Other Bus
+        OtherBus = "I",
+        /// Emergency Vehicle
+        EmergencyVehicle = "J",
+        /// Highway Construction Equipment
+        ConstructionEquipment = "K",
+        /// Bicycle
+        Bicycle = "L",
+        /// Other Vehicle
+        OtherVehicle = "M",
+        /// Pedestrian
+        Pedestrian = "N",
+        /// Moped
+        Moped = "O",
+        /// Not Stated
+        NotStated = "-",
+    }
+}
+
+coded_enum! {
+    /// Victim Role
+    VictimRole => "lookup-tables/victim-tables/VICTIM_ROLE.csv" {
+        /// Driver
+        Driver = "1",
+        /// Passenger (includes non-operator on bicycle or any victim on/in parked vehicle or multiple victims
+        ///   on/in non-motor vehicle)
+        Passenger = "2",
+        /// Pedestrian
+        Pedestrian = "3",
+        /// Bicyclist
+        Bicyclist = "4",
+        /// Other (single victim on/in non-motor vehicle; e.g. ridden animal, horse-drawn carriage, train, or
+        ///   building)
+        Other = "5",
+        /// Non-Injured Party
+        NonInjuredParty = "6",
+    }
+}
+
+coded_enum! {
+    /// Victim Sex
+    VictimSex => "lookup-tables/victim-tables/VICTIM_SEX.csv" {
+        /// Male
+        Male = "M",
+        /// Female
+        Female = "F",
+        /// Not Stated
+        NotStated = "-",
+    }
+}
+
+coded_enum! {
+    /// Victim Degree of Injury
+    DegreeOfInjury => "lookup-tables/victim-tables/VICTIM_DEGREE_OF_INJURY.csv" {
+        /// Killed
+        Killed = "1",
+        /// Severe Injury
+        SevereInjury = "2",
+        /// Other Visible Injury
+        OtherVisibleInjury = "3",
+        /// Complaint of Pain
+        ComplaintOfPain = "4",
+        /// Suspected Serious Injury
+        SuspectedSeriousInjury = "5",
+        /// Suspected Minor Injury
+        SuspectedMinorInjury = "6",
+        /// Possible Injury
+        PossibleInjury = "7",
+        /// No Injury
+        NoInjury = "0",
+    }
+}
+
+/// Columns which are "Y" or blank
+fn yes_or_blank<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.as_deref() == Some("Y"))
+}
+
+/// Columns which are "Y", "N" or blank for not stated
+fn yes_or_no<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<bool>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    match value.as_deref() {
+        Some("Y") => Ok(Some(true)),
+        Some("N") => Ok(Some(false)),
+        _ => Ok(None),
+    }
+}
+
+/// A row in one of the tables, which can be read from the raw export or the DB
+pub trait Record: DeserializeOwned {
+    /// Name of the table in the DB
+    const TABLE: &'static str;
+}
+
+/// A collision, see `schema/collisions.sql` for the descriptions of each column
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Collision {
+    /// Case Id, `CASE_ID`
+    pub case_id: String,
+    /// Collision Year, `ACCIDENT_YEAR`
+    pub accident_year: Option<u16>,
+    /// Process Date, `PROC_DATE`
+    pub proc_date: Option<String>,
+    /// Jurisdiction, `JURIS`
+    pub juris: Option<u32>,
+    /// Collision Date, `COLLISION_DATE`
+    pub collision_date: Option<String>,
+    /// Collision Time, `COLLISION_TIME`
+    pub collision_time: Option<String>,
+    /// Officer Id, `OFFICER_ID`
+    pub officer_id: Option<String>,
+    /// Reporting District, `REPORTING_DISTRICT`
+    pub reporting_district: Option<String>,
+    /// Day of Week, `DAY_OF_WEEK`
+    pub day_of_week: Option<DayOfWeek>,
+    /// CHP Shift, `CHP_SHIFT`
+    pub chp_shift: Option<String>,
+    /// Population, `POPULATION`
+    pub population: Option<String>,
+    /// County City Location, `CNTY_CITY_LOC`
+    pub cnty_city_loc: Option<String>,
+    /// Special Condition, `SPECIAL_COND`
+    pub special_cond: Option<String>,
+    /// Beat Type, `BEAT_TYPE`
+    pub beat_type: Option<String>,
+    /// CHP Beat Type, `CHP_BEAT_TYPE`
+    pub chp_beat_type: Option<String>,
+    /// City Division LAPD, `CITY_DIVISION_LAPD`
+    pub city_division_lapd: Option<String>,
+    /// CHP Beat Class, `CHP_BEAT_CLASS`
+    pub chp_beat_class: Option<String>,
+    /// Beat Number, `BEAT_NUMBER`
+    pub beat_number: Option<String>,
+    /// Primary Rd, `PRIMARY_RD`
+    pub primary_rd: Option<String>,
+    /// Secondary Rd, `SECONDARY_RD`
+    pub secondary_rd: Option<String>,
+    /// Distance, `DISTANCE`, in feet from the secondary road
+    pub distance: Option<f64>,
+    /// Direction, `DIRECTION`
+    pub direction: Option<String>,
+    /// Intersection, `INTERSECTION`, None when not stated
+    #[serde(deserialize_with = "yes_or_no")]
+    pub intersection: Option<bool>,
+    /// Weather 1, `WEATHER_1`
+    pub weather_1: Option<Weather>,
+    /// Weather 2, `WEATHER_2`
+    pub weather_2: Option<Weather>,
+    /// State Highway Indicator, `STATE_HWY_IND`, None when not stated
+    #[serde(deserialize_with = "yes_or_no")]
+    pub state_hwy_ind: Option<bool>,
+    /// Caltrans County, `CALTRANS_COUNTY`
+    pub caltrans_county: Option<String>,
+    /// Caltrans District, `CALTRANS_DISTRICT`
+    pub caltrans_district: Option<i64>,
+    /// State Route, `STATE_ROUTE`
+    pub state_route: Option<i64>,
+    /// Route Suffix, `ROUTE_SUFFIX`
+    pub route_suffix: Option<String>,
+    /// Postmile Prefix, `POSTMILE_PREFIX`
+    pub postmile_prefix: Option<String>,
+    /// Postmile, `POSTMILE`
+    pub postmile: Option<f64>,
+    /// Location Type, `LOCATION_TYPE`
+    pub location_type: Option<String>,
+    /// Ramp Intersection, `RAMP_INTERSECTION`
+    pub ramp_intersection: Option<String>,
+    /// Side Of Highway, `SIDE_OF_HWY`
+    pub side_of_hwy: Option<String>,
+    /// Tow Away, `TOW_AWAY`, None when not stated
+    #[serde(deserialize_with = "yes_or_no")]
+    pub tow_away: Option<bool>,
+    /// Collision Severity, `COLLISION_SEVERITY`
+    pub collision_severity: Option<CollisionSeverity>,
+    /// Killed victims, `NUMBER_KILLED`
+    pub number_killed: Option<u32>,
+    /// Injured victims, `NUMBER_INJURED`
+    pub number_injured: Option<u32>,
+    /// Party Count, `PARTY_COUNT`
+    pub party_count: Option<u32>,
+    /// Primary Collision Factor, `PRIMARY_COLL_FACTOR`
+    pub primary_coll_factor: Option<String>,
+    /// PCF Violation Code, `PCF_CODE_OF_VIOL`
+    pub pcf_code_of_viol: Option<String>,
+    /// PCF Violation Category, `PCF_VIOL_CATEGORY`
+    pub pcf_viol_category: Option<String>,
+    /// PCF Violation, `PCF_VIOLATION`
+    pub pcf_violation: Option<i64>,
+    /// PCF Violation Subsection, `PCF_VIOL_SUBSECTION`
+    pub pcf_viol_subsection: Option<String>,
+    /// Hit And Run, `HIT_AND_RUN`
+    pub hit_and_run: Option<HitAndRun>,
+    /// Type of Collision, `TYPE_OF_COLLISION`
+    pub type_of_collision: Option<TypeOfCollision>,
+    /// Motor Vehicle Involved With, `MVIW`
+    pub mviw: Option<String>,
+    /// Ped Action, `PED_ACTION`
+    pub ped_action: Option<PedAction>,
+    /// Road Surface, `ROAD_SURFACE`
+    pub road_surface: Option<RoadSurface>,
+    /// Road Condition 1, `ROAD_COND_1`
+    pub road_cond_1: Option<String>,
+    /// Road Condition 2, `ROAD_COND_2`
+    pub road_cond_2: Option<String>,
+    /// Lighting, `LIGHTING`
+    pub lighting: Option<Lighting>,
+    /// Control Device, `CONTROL_DEVICE`
+    pub control_device: Option<String>,
+    /// CHP Road Type, `CHP_ROAD_TYPE`
+    pub chp_road_type: Option<String>,
+    /// Pedestrian Collision, `PEDESTRIAN_ACCIDENT`
+    #[serde(deserialize_with = "yes_or_blank")]
+    pub pedestrian_accident: bool,
+    /// Bicycle Collision, `BICYCLE_ACCIDENT`
+    #[serde(deserialize_with = "yes_or_blank")]
+    pub bicycle_accident: bool,
+    /// Motorcycle Collision, `MOTORCYCLE_ACCIDENT`
+    #[serde(deserialize_with = "yes_or_blank")]
+    pub motorcycle_accident: bool,
+    /// Truck Collision, `TRUCK_ACCIDENT`
+    #[serde(deserialize_with = "yes_or_blank")]
+    pub truck_accident: bool,
+    /// Not Private Property, `NOT_PRIVATE_PROPERTY`
+    #[serde(deserialize_with = "yes_or_blank")]
+    pub not_private_property: bool,
+    /// Alcohol Involved, `ALCOHOL_INVOLVED`
+    #[serde(deserialize_with = "yes_or_blank")]
+    pub alcohol_involved: bool,
+    /// Statewide Vehicle Type At Fault, `STWD_VEHTYPE_AT_FAULT`
+    pub stwd_vehtype_at_fault: Option<String>,
+    /// CHP Vehicle Type At Fault, `CHP_VEHTYPE_AT_FAULT`
+    pub chp_vehtype_at_fault: Option<String>,
+    /// Severe Injury count, `COUNT_SEVERE_INJ`
+    pub count_severe_inj: Option<u32>,
+    /// Other Visible Injury count, `COUNT_VISIBLE_INJ`
+    pub count_visible_inj: Option<u32>,
+    /// Complaint of Pain Injury count, `COUNT_COMPLAINT_PAIN`
+    pub count_complaint_pain: Option<u32>,
+    /// Pedestrian Killed count, `COUNT_PED_KILLED`
+    pub count_ped_killed: Option<u32>,
+    /// Pedestrian Injured count, `COUNT_PED_INJURED`
+    pub count_ped_injured: Option<u32>,
+    /// Bicyclist Killed count, `COUNT_BICYCLIST_KILLED`
+    pub count_bicyclist_killed: Option<u32>,
+    /// Bicyclist Injured count, `COUNT_BICYCLIST_INJURED`
+    pub count_bicyclist_injured: Option<u32>,
+    /// Motorcyclist Killed count, `COUNT_MC_KILLED`
+    pub count_mc_killed: Option<u32>,
+    /// Motorcyclist Injured count, `COUNT_MC_INJURED`
+    pub count_mc_injured: Option<u32>,
+    /// Primary Ramp, `PRIMARY_RAMP`
+    pub primary_ramp: Option<String>,
+    /// Secondary Ramp, `SECONDARY_RAMP`, same codes as the primary ramp
+    pub secondary_ramp: Option<String>,
+    /// Latitude, `LATITUDE`, as reported
+    pub latitude: Option<f64>,
+    /// Longitude, `LONGITUDE`, as reported
+    pub longitude: Option<f64>,
+}
+
+impl Record for Collision {
+    const TABLE: &'static str = "collisions";
+}
+
+/// A party involved in a collision, see `schema/parties.sql` for the descriptions of each column
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Party {
+    /// Case Id, `CASE_ID`
+    pub case_id: String,
+    /// Party Number, `PARTY_NUMBER`
+    pub party_number: u16,
+    /// Party Type, `PARTY_TYPE`
+    pub party_type: Option<PartyType>,
+    /// At Fault, `AT_FAULT`
+    #[serde(deserialize_with = "yes_or_no")]
+    pub at_fault: Option<bool>,
+    /// Party Sex, `PARTY_SEX`
+    pub party_sex: Option<PartySex>,
+    /// Party Age, `PARTY_AGE`
+    pub party_age: Option<u16>,
+    /// Party Sobriety, `PARTY_SOBRIETY`
+    pub party_sobriety: Option<PartySobriety>,
+    /// Party Drug Physical, `PARTY_DRUG_PHYSICAL`
+    pub party_drug_physical: Option<String>,
+    /// Direction Of Travel, `DIR_OF_TRAVEL`
+    pub dir_of_travel: Option<String>,
+    /// Party Safety Equipment 1, `PARTY_SAFETY_EQUIP_1`
+    pub party_safety_equip_1: Option<String>,
+    /// Party Safety Equipment 2, `PARTY_SAFETY_EQUIP_2`
+    pub party_safety_equip_2: Option<String>,
+    /// Financial Responsibility, `FINAN_RESPONS`
+    pub finan_respons: Option<String>,
+    /// Special Information 1, `SP_INFO_1`
+    pub sp_info_1: Option<String>,
+    /// Special Information 2, `SP_INFO_2`
+    pub sp_info_2: Option<String>,
+    /// Special Information 3, `SP_INFO_3`
+    pub sp_info_3: Option<String>,
+    /// OAF Violation Code, `OAF_VIOLATION_CODE`
+    pub oaf_violation_code: Option<String>,
+    /// OAF Violation Category, `OAF_VIOL_CAT`
+    pub oaf_viol_cat: Option<String>,
+    /// OAF Violation Section, `OAF_VIOL_SECTION`
+    pub oaf_viol_section: Option<i64>,
+    /// OAF Violation Suffix, `OAF_VIOLATION_SUFFIX`
+    pub oaf_violation_suffix: Option<String>,
+    /// Other Associated Factor 1, `OAF_1`
+    pub oaf_1: Option<String>,
+    /// Other Associated Factor 2, `OAF_2`
+    pub oaf_2: Option<String>,
+    /// Party Number Killed, `PARTY_NUMBER_KILLED`
+    pub party_number_killed: Option<u32>,
+    /// Party Number Injured, `PARTY_NUMBER_INJURED`
+    pub party_number_injured: Option<u32>,
+    /// Movement Preceding Collision, `MOVE_PRE_ACC`
+    pub move_pre_acc: Option<String>,
+    /// Vehicle Year, `VEHICLE_YEAR`
+    pub vehicle_year: Option<u16>,
+    /// Vehicle Make, `VEHICLE_MAKE`
+    pub vehicle_make: Option<String>,
+    /// Statewide Vehicle Type, `STWD_VEHICLE_TYPE`
+    pub stwd_vehicle_type: Option<StatewideVehicleType>,
+    /// CHP Vehicle Type Towing, `CHP_VEH_TYPE_TOWING`
+    pub chp_veh_type_towing: Option<String>,
+    /// CHP Vehicle Type Towed, `CHP_VEH_TYPE_TOWED`
+    pub chp_veh_type_towed: Option<String>,
+    /// Party Race, `RACE`
+    pub race: Option<String>,
+}
+
+impl Record for Party {
+    const TABLE: &'static str = "parties";
+}
+
+/// A victim in a collision, see `schema/victims.sql` for the descriptions of each column
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Victim {
+    /// Case Id, `CASE_ID`
+    pub case_id: String,
+    /// Party Number, `PARTY_NUMBER`
+    pub party_number: u16,
+    /// Victim Role, `VICTIM_ROLE`
+    pub victim_role: Option<VictimRole>,
+    /// Victim Sex, `VICTIM_SEX`
+    pub victim_sex: Option<VictimSex>,
+    /// Victim Age, `VICTIM_AGE`
+    pub victim_age: Option<u16>,
+    /// Victim Degree of Injury, `VICTIM_DEGREE_OF_INJURY`
+    pub victim_degree_of_injury: Option<DegreeOfInjury>,
+    /// Victim Seating Position, `VICTIM_SEATING_POSITION`
+    pub victim_seating_position: Option<String>,
+    /// Victim Safety Equipment 1, `VICTIM_SAFETY_EQUIP_1`
+    pub victim_safety_equip_1: Option<String>,
+    /// Victim Safety Equipment 2, `VICTIM_SAFETY_EQUIP_2`
+    pub victim_safety_equip_2: Option<String>,
+    /// Victim Ejected, `VICTIM_EJECTED`
+    pub victim_ejected: Option<String>,
+}
+
+impl Record for Victim {
+    const TABLE: &'static str = "victims";
+}
+
+/// Read the records from a CSV in the raw export, e.g. `CollisionRecords.txt` for `Collision`. The records are
+///   read as the iterator is advanced, so even a statewide export is never held in memory
+pub fn read_export<T: Record>(
+    path: &Path,
+) -> Result<impl Iterator<Item = Result<T, csv::Error>>, Box<dyn std::error::Error>> {
+    // same options as loading the data into the DB
+    let mut csv = csv::ReaderBuilder::new()
+        .quoting(true)
+        .has_headers(true)
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|e| format!("failed to read csv {path}: {e}", path = path.display()))?;
+
+    // the raw export has uppercase headers, the DB and the records have lowercase
+    let headers = csv
+        .headers()?
+        .iter()
+        .map(str::to_lowercase)
+        .collect::<StringRecord>();
+    csv.set_headers(headers);
+
+    Ok(csv.into_deserialize())
+}
+
+/// Extensions to the DB Connection to read typed records from a built DB
+pub trait RecordsDB: NewDB {
+    /// Read all the records in the table of the record type
+    fn read_records<T: Record>(&self) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+        let mut records = Vec::new();
+        self.query_records(
+            &format!("SELECT * FROM {table}", table = T::TABLE),
+            [],
+            |record| {
+                records.push(record);
+                Ok(())
+            },
+        )?;
+
+        Ok(records)
+    }
+
+    /// Call `f` with each of the records selected by the query, the query must select the columns of the
+    ///   record's table. The records are read one row at a time, so a large query is never held in memory.
+    ///   An error from `f` stops the query and is returned
+    fn query_records<T: Record, P: Params>(
+        &self,
+        query: &str,
+        params: P,
+        mut f: impl FnMut(T) -> Result<(), Box<dyn std::error::Error>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut stmt = self.connection().prepare(query)?;
+        let headers = stmt
+            .column_names()
+            .into_iter()
+            .map(str::to_lowercase)
+            .collect::<StringRecord>();
+        let column_count = stmt.column_count();
+
+        let mut rows = stmt.query(params)?;
+        while let Some(row) = rows.next()? {
+            // each row is converted into a CSV record, so it's deserialized the same as the raw export
+            let mut record = StringRecord::with_capacity(0, column_count);
+            for i in 0..column_count {
+                match row.get_ref(i)? {
+                    ValueRef::Null => record.push_field(""),
+                    ValueRef::Integer(i) => record.push_field(&i.to_string()),
+                    ValueRef::Real(f) => record.push_field(&f.to_string()),
+                    ValueRef::Text(s) | ValueRef::Blob(s) => {
+                        record.push_field(&String::from_utf8_lossy(s))
+                    }
+                }
+            }
+
+            f(record.deserialize(Some(&headers))?)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl RecordsDB for Connection {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::schema::test_db;
+
+    /// All the codes in the lookup table CSV
+    fn lookup_codes(lookup_table: &str) -> BTreeSet<String> {
+        let mut csv = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(lookup_table)
+            .expect("failed to read lookup table");
+
+        csv.records()
+            .map(|record| record.expect("bad record")[0].to_string())
+            .collect()
+    }
+
+    /// Every code in the lookup table has a variant, and every variant is in the lookup table
+    fn validate_enum<T: fmt::Display>(lookup_table: &str, variants: &[T]) {
+        let codes = lookup_codes(lookup_table);
+        let variant_codes = variants
+            .iter()
+            .map(ToString::to_string)
+            .collect::<BTreeSet<_>>();
+
+        assert_eq!(codes, variant_codes, "{lookup_table}");
+        assert_eq!(codes.len(), variants.len(), "{lookup_table} has duplicates");
+    }
+
+    #[test]
+    fn test_enums_match_lookup_tables() {
+        use CollisionSeverity as S;
+        validate_enum(
+            S::LOOKUP_TABLE,
+            &[
+                S::Fatal,
+                S::SevereInjury,
+                S::OtherVisibleInjury,
+                S::ComplaintOfPain,
+                S::PropertyDamageOnly,
+            ],
+        );
+
+        use DayOfWeek as D;
+        validate_enum(
+            D::LOOKUP_TABLE,
+            &[
+                D::Monday,
+                D::Tuesday,
+                D::Wednesday,
+                D::Thursday,
+                D::Friday,
+                D::Saturday,
+                D::Sunday,
+            ],
+        );
+
+        use Weather as W;
+        validate_enum(
+            W::LOOKUP_TABLE,
+            &[
+                W::Clear,
+                W::Cloudy,
+                W::Raining,
+                W::Snowing,
+                W::Fog,
+                W::Other,
+                W::Wind,
+                W::NotStated,
+            ],
+        );
+
+        use HitAndRun as H;
+        validate_enum(
+            H::LOOKUP_TABLE,
+            &[H::Felony, H::Misdemeanor, H::NotHitAndRun],
+        );
+
+        use TypeOfCollision as T;
+        validate_enum(
+            T::LOOKUP_TABLE,
+            &[
+                T::HeadOn,
+                T::Sideswipe,
+                T::RearEnd,
+                T::Broadside,
+                T::HitObject,
+                T::Overturned,
+                T::VehiclePedestrian,
+                T::Other,
+                T::NotStated,
+            ],
+        );
+
+        use PedAction as P;
+        validate_enum(
+            P::LOOKUP_TABLE,
+            &[
+                P::NoPedestrianInvolved,
+                P::CrosswalkAtIntersection,
+                P::CrosswalkNotAtIntersection,
+                P::NotInCrosswalk,
+                P::InRoad,
+                P::NotInRoad,
+                P::SchoolBus,
+                P::NotStated,
+            ],
+        );
+
+        use RoadSurface as R;
+        validate_enum(
+            R::LOOKUP_TABLE,
+            &[R::Dry, R::Wet, R::SnowyOrIcy, R::Slippery, R::NotStated],
+        );
+
+        use Lighting as L;
+        validate_enum(
+            L::LOOKUP_TABLE,
+            &[
+                L::Daylight,
+                L::DuskDawn,
+                L::DarkStreetLights,
+                L::DarkNoStreetLights,
+                L::DarkStreetLightsNotFunctioning,
+                L::NotStated,
+            ],
+        );
+
+        use PartyType as PT;
+        validate_enum(
+            PT::LOOKUP_TABLE,
+            &[
+                PT::Driver,
+                PT::Pedestrian,
+                PT::ParkedVehicle,
+                PT::Bicyclist,
+                PT::Other,
+                PT::Undefined,
+                PT::NotStated,
+            ],
+        );
+
+        use PartySex as PS;
+        validate_enum(
+            PS::LOOKUP_TABLE,
+            &[PS::Male, PS::Female, PS::Undefined, PS::NotStated],
+        );
+
+        use PartySobriety as PB;
+        validate_enum(
+            PB::LOOKUP_TABLE,
+            &[
+                PB::NotDrinking,
+                PB::UnderInfluence,
+                PB::NotUnderInfluence,
+                PB::DrinkingImpairmentUnknown,
+                PB::ImpairmentUnknown,
+                PB::NotApplicable,
+                PB::NotStated,
+            ],
+        );
+
+        use StatewideVehicleType as V;
+        validate_enum(
+            V::LOOKUP_TABLE,
+            &[
+                V::PassengerCar,
+                V::PassengerCarWithTrailer,
+                V::Motorcycle,
+                V::PickupTruck,
+                V::PickupTruckWithTrailer,
+                V::Truck,
+                V::TruckWithTrailer,
+                V::Schoolbus,
+                V::OtherBus,
+                V::EmergencyVehicle,
+                V::ConstructionEquipment,
+                V::Bicycle,
+                V::OtherVehicle,
+                V::Pedestrian,
+                V::Moped,
+                V::NotStated,
+            ],
+        );
+
+        use VictimRole as VR;
+        validate_enum(
+            VR::LOOKUP_TABLE,
+            &[
+                VR::Driver,
+                VR::Passenger,
+                VR::Pedestrian,
+                VR::Bicyclist,
+                VR::Other,
+                VR::NonInjuredParty,
+            ],
+        );
+
+        use VictimSex as VS;
+        validate_enum(VS::LOOKUP_TABLE, &[VS::Male, VS::Female, VS::NotStated]);
+
+        use DegreeOfInjury as I;
+        validate_enum(
+            I::LOOKUP_TABLE,
+            &[
+                I::Killed,
+                I::SevereInjury,
+                I::OtherVisibleInjury,
+                I::ComplaintOfPain,
+                I::SuspectedSeriousInjury,
+                I::SuspectedMinorInjury,
+                I::PossibleInjury,
+                I::NoInjury,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_unknown_code() {
+        assert_eq!(
+            CollisionSeverity::Unknown("9".to_string()),
+            CollisionSeverity::from("9".to_string())
+        );
+        assert_eq!("9", CollisionSeverity::Unknown("9".to_string()).code());
+    }
+
+    #[test]
+    fn test_read_export() {
+        let collisions = read_export::<Collision>(Path::new("tests/data/collisions.csv"))
+            .expect("bad export")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("bad record");
+        assert_eq!(40, collisions.len());
+
+        let collision = &collisions[0];
+        assert_eq!("9629920", collision.case_id);
+        assert_eq!(Some(103), collision.juris);
+        assert_eq!(Some(DayOfWeek::Tuesday), collision.day_of_week);
+        assert_eq!(Some(true), collision.intersection);
+        assert_eq!(Some(Weather::NotStated), collision.weather_2);
+        assert_eq!(
+            Some(CollisionSeverity::PropertyDamageOnly),
+            collision.collision_severity
+        );
+        assert!(collision.not_private_property);
+        assert!(!collision.alcohol_involved);
+        assert!(!collision.bicycle_accident);
+        assert_eq!(Some(37.87041), collision.latitude);
+
+        let parties = read_export::<Party>(Path::new("tests/data/parties.csv"))
+            .expect("bad export")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("bad record");
+        assert_eq!(80, parties.len());
+        assert_eq!(Some(PartyType::Driver), parties[0].party_type);
+        assert_eq!(Some(true), parties[0].at_fault);
+        assert_eq!(Some(41), parties[0].party_age);
+
+        let victims = read_export::<Victim>(Path::new("tests/data/victims.csv"))
+            .expect("bad export")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("bad record");
+        assert_eq!(39, victims.len());
+        assert_eq!(
+            Some(DegreeOfInjury::OtherVisibleInjury),
+            victims[0].victim_degree_of_injury
+        );
+    }
+
+    #[test]
+    fn test_read_db() {
+        let connection = test_db(&["collisions", "parties", "victims"]);
+
+        // the records read from the DB are the same as read from the export
+        let collisions = connection
+            .read_records::<Collision>()
+            .expect("failed to read");
+        assert_eq!(
+            read_export::<Collision>(Path::new("tests/data/collisions.csv"))
+                .expect("bad export")
+                .collect::<Result<Vec<_>, _>>()
+                .expect("bad record"),
+            collisions
+        );
+
+        let parties = connection.read_records::<Party>().expect("failed to read");
+        assert_eq!(
+            read_export::<Party>(Path::new("tests/data/parties.csv"))
+                .expect("bad export")
+                .collect::<Result<Vec<_>, _>>()
+                .expect("bad record"),
+            parties
+        );
+
+        let mut victims = Vec::<Victim>::new();
+        connection
+            .query_records(
+                "SELECT * FROM victims WHERE case_id = ?",
+                ["9631272"],
+                |victim| {
+                    victims.push(victim);
+                    Ok(())
+                },
+            )
+            .expect("failed to read");
+        assert!(!victims.is_empty());
+        assert!(victims.iter().all(|v| v.case_id == "9631272"));
+    }
+}
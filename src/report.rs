@@ -0,0 +1,507 @@
+//! Data quality report for a built DB, how complete and consistent the SWITRS export was
+
+use std::{collections::HashMap, fmt};
+
+use rusqlite::{types::Value, Connection};
+use serde::Serialize;
+
+use crate::{
+    geopackage::{FEATURES_TABLE, GEOPACKAGE_APPLICATION_ID},
+    schema::{NewDB, Schema},
+};
+
+/// Number of values of each coded column listed in the text report, the JSON report has all of them
+const TEXT_DISTRIBUTION_LIMIT: usize = 10;
+
+/// Number of rows with a single value in a lookup coded column
+#[derive(Debug, Serialize)]
+pub struct ValueCount {
+    /// The code as it appears in the raw data, None for NULL
+    pub value: Option<String>,
+    /// Name of the code from the lookup table, None if the code is missing from the lookup table
+    pub name: Option<String>,
+    /// Number of rows with the code
+    pub count: usize,
+}
+
+/// Completeness of a single column
+#[derive(Debug, Serialize)]
+pub struct ColumnQuality {
+    /// Name of the column
+    pub name: String,
+    /// Rows where the column is NULL, the raw data was empty
+    pub null: usize,
+    /// Rows where the column is "-", which SWITRS uses for not stated in many columns
+    pub dash: usize,
+    /// Rows where the column is "Not Stated", or is a code which the lookup table names "Not Stated"
+    pub not_stated: usize,
+    /// Lookup table of the column, if it's a coded column
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lookup: Option<String>,
+    /// Count of every value, only for lookup coded columns, most frequent first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub distribution: Vec<ValueCount>,
+}
+
+/// Completeness of all the columns of a table
+#[derive(Debug, Serialize)]
+pub struct TableQuality {
+    /// Name of the table
+    pub name: String,
+    /// Number of rows in the table
+    pub rows: usize,
+    /// Each column, in the order of the table
+    pub columns: Vec<ColumnQuality>,
+}
+
+/// Data quality of all the tables loaded from the Schema, plus checks across the collision tables
+#[derive(Debug, Serialize)]
+pub struct QualityReport {
+    /// Each table in the `table-order` of the Schema
+    pub tables: Vec<TableQuality>,
+    /// Collisions where the latitude or longitude is missing, or 0
+    pub collisions_missing_location: usize,
+    /// Parties which have no victims
+    pub parties_without_victims: usize,
+    /// Victims where the (case_id, party_number) has no matching party
+    pub victims_without_party: usize,
+}
+
+/// Number of rows in a table of the DB
+#[derive(Debug, Serialize)]
+pub struct TableRows {
+    /// Name of the table
+    pub name: String,
+    /// Number of rows in the table
+    pub rows: usize,
+}
+
+/// Overview of a built DB, its schema version, tables and the range of the data
+#[derive(Debug, Serialize)]
+pub struct DbInfo {
+    /// Schema version, `PRAGMA user_version`, see the migrations
+    pub version: u32,
+    /// Every table in the DB, by name
+    pub tables: Vec<TableRows>,
+    /// First and last processed date of the collisions in collisions_view, from version_view
+    pub proc_dates: Option<(String, String)>,
+    /// First and last collision datetime of the collisions in collisions_view, from version_view
+    pub collision_datetimes: Option<(String, String)>,
+    /// True if the DB is marked as a GeoPackage, `PRAGMA application_id` is `GPKG`. Only the `application_id` is
+    ///   set, `user_version` stays the schema version rather than the GeoPackage version
+    pub geopackage: bool,
+}
+
+impl fmt::Display for DbInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "schema version {version}", version = self.version)?;
+        if let Some((first, last)) = &self.collision_datetimes {
+            writeln!(f, "collisions from {first} to {last}")?;
+        }
+        if let Some((first, last)) = &self.proc_dates {
+            writeln!(f, "processed from {first} to {last}")?;
+        }
+        if self.geopackage {
+            writeln!(
+                f,
+                "GeoPackage layer {FEATURES_TABLE}, application_id GPKG, user_version is the schema version not the GeoPackage version"
+            )?;
+        }
+
+        write!(f, "{name:<36} {rows:>10}", name = "table", rows = "rows")?;
+        for table in &self.tables {
+            write!(
+                f,
+                "\n{name:<36} {rows:>10}",
+                name = table.name,
+                rows = table.rows
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Percent of rows, for display
+fn percent(count: usize, rows: usize) -> f64 {
+    if rows == 0 {
+        0.0
+    } else {
+        count as f64 * 100.0 / rows as f64
+    }
+}
+
+impl fmt::Display for QualityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for table in &self.tables {
+            writeln!(
+                f,
+                "{name} ({rows} rows)",
+                name = table.name,
+                rows = table.rows
+            )?;
+            writeln!(
+                f,
+                "  {column:32} {null:>8} {dash:>8} {not_stated:>11}",
+                column = "column",
+                null = "null",
+                dash = "\"-\"",
+                not_stated = "not stated"
+            )?;
+
+            for column in &table.columns {
+                writeln!(
+                    f,
+                    "  {name:32} {null:>7.1}% {dash:>7.1}% {not_stated:>10.1}%",
+                    name = column.name,
+                    null = percent(column.null, table.rows),
+                    dash = percent(column.dash, table.rows),
+                    not_stated = percent(column.not_stated, table.rows),
+                )?;
+
+                for value in column.distribution.iter().take(TEXT_DISTRIBUTION_LIMIT) {
+                    writeln!(
+                        f,
+                        "      {value:>6} {name:40} {count:>8}",
+                        value = value.value.as_deref().unwrap_or("NULL"),
+                        name = value.name.as_deref().unwrap_or("(unknown)"),
+                        count = value.count
+                    )?;
+                }
+                if column.distribution.len() > TEXT_DISTRIBUTION_LIMIT {
+                    writeln!(
+                        f,
+                        "      ... {more} more values",
+                        more = column.distribution.len() - TEXT_DISTRIBUTION_LIMIT
+                    )?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(
+            f,
+            "collisions missing latitude/longitude: {count}",
+            count = self.collisions_missing_location
+        )?;
+        writeln!(
+            f,
+            "parties without victims: {count}",
+            count = self.parties_without_victims
+        )?;
+        write!(
+            f,
+            "victims without a matching party: {count}",
+            count = self.victims_without_party
+        )
+    }
+}
+
+/// Extensions to the DB Connection to report on the quality of the loaded data
+pub trait ReportDB: NewDB {
+    /// Report on the completeness of every column in the table
+    fn table_quality(&self, table: &str) -> Result<TableQuality, Box<dyn std::error::Error>> {
+        let connection = self.connection();
+
+        let columns = {
+            let mut stmt = connection.prepare("SELECT name FROM pragma_table_info(?)")?;
+            let columns = stmt
+                .query_map([table], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            columns
+        };
+        if columns.is_empty() {
+            return Err(format!("no such table: {table}").into());
+        }
+
+        // single column foreign keys to an id, these are the lookup coded columns
+        let lookups = {
+            let mut stmt = connection.prepare(
+                r#"SELECT "from", "table" FROM pragma_foreign_key_list(?1)
+                WHERE "to" = 'id' AND id IN (SELECT id FROM pragma_foreign_key_list(?1) GROUP BY id HAVING count(*) = 1)"#,
+            )?;
+            let lookups = stmt
+                .query_map([table], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))?
+                .collect::<Result<HashMap<String, String>, _>>()?;
+            lookups
+        };
+
+        // count everything in a single pass over the table
+        let sums = columns
+            .iter()
+            .map(|c| format!("sum({c} IS NULL), sum({c} = '-'), sum({c} = 'Not Stated')"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let counts = connection.query_row(
+            &format!("SELECT count(*), {sums} FROM {table}"),
+            [],
+            |row| {
+                (0..row.as_ref().column_count())
+                    .map(|i| Ok(row.get::<_, Option<usize>>(i)?.unwrap_or(0)))
+                    .collect::<Result<Vec<_>, rusqlite::Error>>()
+            },
+        )?;
+
+        let mut quality = TableQuality {
+            name: table.to_string(),
+            rows: counts[0],
+            columns: Vec::with_capacity(columns.len()),
+        };
+
+        for (i, name) in columns.into_iter().enumerate() {
+            let mut column = ColumnQuality {
+                null: counts[1 + i * 3],
+                dash: counts[2 + i * 3],
+                not_stated: counts[3 + i * 3],
+                lookup: lookups.get(&name).cloned(),
+                distribution: Vec::new(),
+                name,
+            };
+
+            if let Some(lookup) = &column.lookup {
+                // most lookups have a name, others like cnty_city_loc describe the code in several columns
+                let mut stmt = connection
+                    .prepare("SELECT name FROM pragma_table_info(?) WHERE name != 'id'")?;
+                let lookup_columns = stmt
+                    .query_map([lookup], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                let lookup_name = if lookup_columns.iter().any(|c| c == "name") {
+                    "l.name".to_string()
+                } else {
+                    lookup_columns
+                        .iter()
+                        .map(|c| format!("l.{c}"))
+                        .collect::<Vec<_>>()
+                        .join(" || ', ' || ")
+                };
+
+                let mut stmt = connection.prepare(&format!(
+                    "SELECT t.{name}, {lookup_name}, count(*) AS count FROM {table} AS t
+                    LEFT JOIN {lookup} AS l ON t.{name} = l.id
+                    GROUP BY t.{name} ORDER BY count DESC, t.{name}",
+                    name = column.name
+                ))?;
+                column.distribution = stmt
+                    .query_map([], |row| {
+                        Ok(ValueCount {
+                            value: row.get::<_, Option<Value>>(0)?.map(|v| match v {
+                                Value::Integer(i) => i.to_string(),
+                                Value::Real(f) => f.to_string(),
+                                Value::Text(s) => s,
+                                Value::Null | Value::Blob(_) => String::new(),
+                            }),
+                            name: row.get(1)?,
+                            count: row.get(2)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                // the literal "Not Stated" was already counted, add the codes that mean not stated
+                column.not_stated += column
+                    .distribution
+                    .iter()
+                    .filter(|v| {
+                        v.value.as_deref() != Some("Not Stated")
+                            && v.name
+                                .as_deref()
+                                .is_some_and(|name| name.eq_ignore_ascii_case("not stated"))
+                    })
+                    .map(|v| v.count)
+                    .sum::<usize>();
+            }
+
+            quality.columns.push(column);
+        }
+
+        Ok(quality)
+    }
+
+    /// Report on the quality of all the tables in the Schema, and the consistency between collisions, parties
+    ///   and victims
+    fn quality_report(
+        &self,
+        schemas: &Schema,
+    ) -> Result<QualityReport, Box<dyn std::error::Error>> {
+        self.quality_report_for(&schemas.table_order)
+    }
+
+    /// Same as `quality_report`, but only for the given tables, which must include collisions, parties and victims
+    fn quality_report_for<T: AsRef<str>>(
+        &self,
+        tables: &[T],
+    ) -> Result<QualityReport, Box<dyn std::error::Error>> {
+        let connection = self.connection();
+
+        let tables = tables
+            .iter()
+            .map(|table| self.table_quality(table.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let count = |query: &str| connection.query_row(query, [], |row| row.get::<_, usize>(0));
+
+        Ok(QualityReport {
+            tables,
+            collisions_missing_location: count(
+                "SELECT count(*) FROM collisions
+                WHERE latitude IS NULL OR longitude IS NULL OR latitude = 0 OR longitude = 0",
+            )?,
+            parties_without_victims: count(
+                "SELECT count(*) FROM parties AS p
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM victims AS v WHERE v.case_id = p.case_id AND v.party_number = p.party_number
+                )",
+            )?,
+            victims_without_party: count(
+                "SELECT count(*) FROM victims AS v
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM parties AS p WHERE p.case_id = v.case_id AND p.party_number = v.party_number
+                )",
+            )?,
+        })
+    }
+
+    /// Overview of the DB, the schema version, the rows in each table and the range of dates in the data
+    fn db_info(&self) -> Result<DbInfo, Box<dyn std::error::Error>> {
+        let connection = self.connection();
+        let version = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let geopackage = connection
+            .query_row("PRAGMA application_id", [], |row| row.get::<_, i32>(0))?
+            == GEOPACKAGE_APPLICATION_ID;
+
+        let names = {
+            let mut stmt = connection.prepare(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            )?;
+            let names = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            names
+        };
+        let tables = names
+            .into_iter()
+            .map(|name| {
+                let rows =
+                    connection.query_row(&format!("SELECT count(*) FROM {name}"), [], |row| {
+                        row.get(0)
+                    })?;
+                Ok(TableRows { name, rows })
+            })
+            .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+        // version_view only exists once collisions are loaded, and is empty without any collisions
+        let has_version_view = connection.query_row(
+            "SELECT count(*) FROM sqlite_master WHERE type = 'view' AND name = 'version_view'",
+            [],
+            |row| row.get::<_, usize>(0),
+        )? > 0;
+        let (proc_dates, collision_datetimes) = if has_version_view {
+            connection.query_row(
+                "SELECT first_proc_date, last_proc_date, first_collision_datetime, last_collision_datetime
+                FROM version_view",
+                [],
+                |row| {
+                    let range = |first: usize, last: usize| -> Result<_, rusqlite::Error> {
+                        Ok(row
+                            .get::<_, Option<String>>(first)?
+                            .zip(row.get::<_, Option<String>>(last)?))
+                    };
+                    Ok((range(0, 1)?, range(2, 3)?))
+                },
+            )?
+        } else {
+            (None, None)
+        };
+
+        Ok(DbInfo {
+            version,
+            tables,
+            proc_dates,
+            collision_datetimes,
+            geopackage,
+        })
+    }
+}
+
+impl ReportDB for Connection {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::test_db;
+
+    #[test]
+    fn test_table_quality() {
+        let connection = test_db(&["collisions", "parties", "victims"]);
+
+        let collisions = connection
+            .table_quality("collisions")
+            .expect("failed to report");
+        assert_eq!(40, collisions.rows);
+
+        let case_id = &collisions.columns[0];
+        assert_eq!("case_id", case_id.name);
+        assert_eq!(0, case_id.null);
+        assert!(case_id.lookup.is_none());
+        assert!(case_id.distribution.is_empty());
+
+        let severity = collisions
+            .columns
+            .iter()
+            .find(|c| c.name == "collision_severity")
+            .expect("no collision_severity");
+        assert_eq!(Some("collision_severity"), severity.lookup.as_deref());
+        assert_eq!(
+            40,
+            severity.distribution.iter().map(|v| v.count).sum::<usize>()
+        );
+        assert!(severity.distribution.iter().all(|v| v.name.is_some()));
+
+        assert!(connection.table_quality("no_such_table").is_err());
+    }
+
+    #[test]
+    fn test_quality_report() {
+        let connection = test_db(&["collisions", "parties", "victims"]);
+
+        // a victim of a party that doesn't exist
+        connection
+            .execute(
+                "INSERT INTO victims (case_id, party_number) VALUES('9629920', 9)",
+                [],
+            )
+            .expect("failed to insert");
+
+        let report = connection
+            .quality_report_for(&["collisions", "parties", "victims"])
+            .expect("failed to report");
+        assert_eq!(3, report.tables.len());
+        assert_eq!(2, report.collisions_missing_location);
+        assert_eq!(1, report.victims_without_party);
+        assert!(report.parties_without_victims > 0);
+
+        let json = serde_json::to_value(&report).expect("failed to serialize");
+        assert_eq!("victims", json["tables"][2]["name"]);
+        assert_eq!(40, json["tables"][0]["rows"]);
+    }
+
+    #[test]
+    fn test_db_info() {
+        let connection = test_db(&["corrected_roads", "collisions", "parties", "victims"]);
+
+        let info = connection.db_info().expect("failed to get info");
+        assert_eq!(0, info.version);
+        let collisions = info
+            .tables
+            .iter()
+            .find(|t| t.name == "collisions")
+            .expect("no collisions");
+        assert_eq!(40, collisions.rows);
+        assert!(info.tables.iter().any(|t| t.name == "weather"));
+
+        let (first, last) = info.collision_datetimes.clone().expect("no collisions");
+        assert!(first.starts_with("2023-05-09"), "{first}");
+        assert!(last.starts_with("2023-10-31"), "{last}");
+        assert!(info.to_string().starts_with("schema version 0\n"));
+        assert!(!info.geopackage);
+    }
+}
@@ -0,0 +1,755 @@
+//! Review of the road names which the road fixups could not correct
+//!
+//! Every collision in an overlay's jurisdictions with an unknown primary or secondary road is grouped by the
+//!   normalized road name, most frequent first. For each name the closest known `correct_rd` values from the
+//!   overlay's road_typos are suggested, and the accepted corrections are added to the road_typos CSV.
+//!
+//! During the build, roads which don't exactly match the road_typos are matched fuzzily with the `RoadMatcher`,
+//!   these corrections are recorded with a confidence below 1 in corrected_roads.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{BufRead, Write},
+    path::Path,
+};
+
+use rusqlite::{named_params, Connection};
+
+use crate::{
+    address::STREET_TYPES,
+    overlay::Overlay,
+    schema::{sql_list, NewDB},
+};
+
+/// Number of suggestions offered for each unknown road
+pub const SUGGESTIONS: usize = 3;
+
+/// Suggestions scoring lower than this, with Jaro-Winkler similarity, are not offered
+const MIN_SIMILARITY: f64 = 0.85;
+
+/// A normalized road name which has no correction, with the number of collisions it occurs in
+#[derive(Debug, PartialEq)]
+pub struct UnresolvedRoad {
+    /// Road name after normalization, this is the `normalized_rd` in road_typos
+    pub normalized_rd: String,
+    /// One of the road names as it appears in the raw data
+    pub original_rd: String,
+    /// Number of collisions with this road as the primary or secondary road
+    pub count: usize,
+}
+
+/// The correct road names closest to the unknown road, most similar first.
+///
+/// `known_roads` are the `(normalized_rd, correct_rd)` pairs of the road_typos, the unknown road is compared to
+///   both names, so that a typo close to an earlier typo suggests the same correction.
+pub fn suggest_roads<'a>(road: &str, known_roads: &'a [(String, String)]) -> Vec<(&'a str, f64)> {
+    let mut best = BTreeMap::<&str, f64>::new();
+    for (normalized_rd, correct_rd) in known_roads {
+        let similarity =
+            strsim::jaro_winkler(road, normalized_rd).max(strsim::jaro_winkler(road, correct_rd));
+        let entry = best.entry(correct_rd.as_str()).or_insert(similarity);
+        *entry = entry.max(similarity);
+    }
+
+    let mut suggestions = best
+        .into_iter()
+        .filter(|(_, similarity)| *similarity >= MIN_SIMILARITY)
+        .collect::<Vec<_>>();
+
+    // most similar first, the sort is stable so ties stay in alphabetical order
+    suggestions.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    suggestions.truncate(SUGGESTIONS);
+    suggestions
+}
+
+/// Where the correction of a road came from, in order of precedence
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CorrectionSource {
+    /// Entered by hand in the overlay's corrected_roads CSV, these are never changed by the fixups
+    Manual,
+    /// The road in the raw data is already one of the known correct roads
+    Verified,
+    /// The normalized road is a `normalized_rd` in the overlay's road_typos
+    Typo,
+    /// Fuzzy match against the known roads, see `RoadMatcher`
+    Fuzzy,
+    /// No correction was found
+    Unresolved,
+}
+
+impl CorrectionSource {
+    /// Name of the source in the corrected_roads table
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Verified => "verified",
+            Self::Typo => "typo",
+            Self::Fuzzy => "fuzzy",
+            Self::Unresolved => "unresolved",
+        }
+    }
+}
+
+impl fmt::Display for CorrectionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The correction of one road of a collision
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoadCorrection {
+    /// Corrected road name, empty when unresolved
+    pub road: String,
+    /// 1 for exact corrections, the confidence of fuzzy matches, None when unresolved
+    pub confidence: Option<f64>,
+    /// Where the correction came from
+    pub source: CorrectionSource,
+}
+
+impl RoadCorrection {
+    /// Choose the correction from the most trusted source that has one, `fuzzy` is only matched when needed
+    pub fn choose(
+        manual: Option<&str>,
+        verified: Option<&str>,
+        typo: Option<&str>,
+        fuzzy: impl FnOnce() -> Option<(String, f64)>,
+    ) -> Self {
+        let exact = [
+            (manual, CorrectionSource::Manual),
+            (verified, CorrectionSource::Verified),
+            (typo, CorrectionSource::Typo),
+        ];
+        if let Some((road, source)) = exact
+            .into_iter()
+            .find_map(|(road, source)| road.filter(|r| !r.is_empty()).map(|r| (r, source)))
+        {
+            return Self {
+                road: road.to_string(),
+                confidence: Some(1.0),
+                source,
+            };
+        }
+
+        match fuzzy() {
+            Some((road, confidence)) => Self {
+                road,
+                confidence: Some(confidence),
+                source: CorrectionSource::Fuzzy,
+            },
+            None => Self {
+                road: String::new(),
+                confidence: None,
+                source: CorrectionSource::Unresolved,
+            },
+        }
+    }
+}
+
+/// Fuzzy matches with a confidence below this are not used as corrections
+pub const MIN_CONFIDENCE: f64 = 0.8;
+
+/// Ordinals which are spelled out in the correct road names, e.g. `TENTH ST`, larger ones stay numeric
+const ORDINALS: &[(&str, &str)] = &[
+    ("1ST", "FIRST"),
+    ("2ND", "SECOND"),
+    ("3RD", "THIRD"),
+    ("4TH", "FOURTH"),
+    ("5TH", "FIFTH"),
+    ("6TH", "SIXTH"),
+    ("7TH", "SEVENTH"),
+    ("8TH", "EIGHTH"),
+    ("9TH", "NINTH"),
+    ("10TH", "TENTH"),
+    ("11TH", "ELEVENTH"),
+    ("12TH", "TWELFTH"),
+    ("13TH", "THIRTEENTH"),
+    ("14TH", "FOURTEENTH"),
+    ("15TH", "FIFTEENTH"),
+    ("16TH", "SIXTEENTH"),
+    ("17TH", "SEVENTEENTH"),
+    ("18TH", "EIGHTEENTH"),
+    ("19TH", "NINETEENTH"),
+    ("20TH", "TWENTIETH"),
+];
+
+/// Split the road name into tokens, with the street type abbreviated and the small ordinals spelled out
+fn canonical_tokens(road: &str) -> Vec<String> {
+    road.split(|c: char| c.is_whitespace() || c == '.' || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let token = token.to_uppercase();
+            if let Some((_, word)) = ORDINALS.iter().find(|(ordinal, _)| *ordinal == token) {
+                return word.to_string();
+            }
+            if let Some((abbreviation, _)) = STREET_TYPES
+                .iter()
+                .find(|(_, spellings)| spellings.contains(&token.as_str()))
+            {
+                return abbreviation.to_string();
+            }
+            token
+        })
+        .collect()
+}
+
+/// The tokens without the trailing street type, e.g. `SHATTUCK` for `SHATTUCK AVE`
+fn base_tokens(tokens: &[String]) -> &[String] {
+    match tokens.split_last() {
+        Some((last, base))
+            if !base.is_empty()
+                && STREET_TYPES
+                    .iter()
+                    .any(|(abbreviation, _)| abbreviation == last) =>
+        {
+            base
+        }
+        _ => tokens,
+    }
+}
+
+/// A known road name, prepared for matching
+#[derive(Debug)]
+struct KnownRoad {
+    tokens: Vec<String>,
+    sorted: Vec<String>,
+    joined: String,
+    correct_rd: usize,
+}
+
+/// Fuzzy matching of road names against the known roads of an overlay's road_typos.
+///
+/// Names are compared after abbreviating the street types (AV, AVE and AVENUE) and spelling out the small
+///   ordinals (6TH and SIXTH). In order of confidence a name matches a known road when the tokens are the same,
+///   the tokens are the same in a different order, the names are the same without the street type, or when
+///   the edit distance is small. A name which matches two different correct roads equally well has no match.
+#[derive(Debug)]
+pub struct RoadMatcher {
+    correct_roads: Vec<String>,
+    known: Vec<KnownRoad>,
+}
+
+impl RoadMatcher {
+    /// Matcher for the `(normalized_rd, correct_rd)` pairs of the road_typos
+    pub fn new(known_roads: &[(String, String)]) -> Self {
+        let mut correct_roads = known_roads
+            .iter()
+            .map(|(_, correct_rd)| correct_rd.clone())
+            .collect::<Vec<_>>();
+        correct_roads.sort();
+        correct_roads.dedup();
+
+        let mut known = Vec::new();
+        for (normalized_rd, correct_rd) in known_roads {
+            let correct_rd = correct_roads
+                .binary_search(correct_rd)
+                .expect("correct_rd was added");
+
+            for name in [normalized_rd, &correct_roads[correct_rd]] {
+                let tokens = canonical_tokens(name);
+                let mut sorted = tokens.clone();
+                sorted.sort();
+                known.push(KnownRoad {
+                    joined: tokens.join(" "),
+                    tokens,
+                    sorted,
+                    correct_rd,
+                });
+            }
+        }
+
+        Self {
+            correct_roads,
+            known,
+        }
+    }
+
+    /// The best matching correct road, and the confidence of the match, if any is at least `MIN_CONFIDENCE`
+    pub fn match_road(&self, road: &str) -> Option<(&str, f64)> {
+        let tokens = canonical_tokens(road);
+        if tokens.is_empty() {
+            return None;
+        }
+        let mut sorted = tokens.clone();
+        sorted.sort();
+        let joined = tokens.join(" ");
+        let base = base_tokens(&tokens);
+
+        // best confidence for each correct road
+        let mut best = BTreeMap::<usize, f64>::new();
+        for known in &self.known {
+            let confidence = if known.tokens == tokens {
+                0.95
+            } else if known.sorted == sorted {
+                0.9
+            } else if base_tokens(&known.tokens) == base {
+                0.85
+            } else {
+                strsim::normalized_levenshtein(&joined, &known.joined) * 0.9
+            };
+
+            let entry = best.entry(known.correct_rd).or_insert(confidence);
+            *entry = entry.max(confidence);
+        }
+
+        let mut matches = best.into_iter().collect::<Vec<_>>();
+        matches.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        match matches.as_slice() {
+            [(correct_rd, confidence), rest @ ..]
+                if *confidence >= MIN_CONFIDENCE
+                    && rest.first().is_none_or(|(_, next)| next < confidence) =>
+            {
+                Some((&self.correct_roads[*correct_rd], *confidence))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Interactively review each unresolved road, reading the answers from `input` and prompting on `output`.
+///
+/// For each road the user can accept a suggestion by number, type the correct road name, skip it with an
+///   empty line or quit with `q`. Returns the accepted `(normalized_rd, correct_rd)` corrections.
+pub fn review_roads<R: BufRead, W: Write>(
+    unresolved: &[UnresolvedRoad],
+    known_roads: &[(String, String)],
+    mut input: R,
+    mut output: W,
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let mut corrections = Vec::new();
+
+    for (i, road) in unresolved.iter().enumerate() {
+        let suggestions = suggest_roads(&road.normalized_rd, known_roads);
+
+        writeln!(
+            output,
+            "\n[{n}/{total}] {normalized_rd} ({count} collisions, e.g. '{original_rd}')",
+            n = i + 1,
+            total = unresolved.len(),
+            normalized_rd = road.normalized_rd,
+            count = road.count,
+            original_rd = road.original_rd
+        )?;
+        for (n, (suggestion, similarity)) in suggestions.iter().enumerate() {
+            writeln!(output, "  {n}) {suggestion} ({similarity:.2})", n = n + 1)?;
+        }
+        write!(
+            output,
+            "number to accept, a road name to correct it, empty to skip or q to quit: "
+        )?;
+        output.flush()?;
+
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            break;
+        }
+
+        let answer = answer.trim();
+        if answer.eq_ignore_ascii_case("q") {
+            break;
+        }
+        if answer.is_empty() {
+            continue;
+        }
+
+        let correct_rd = match answer.parse::<usize>() {
+            Ok(n) if (1..=suggestions.len()).contains(&n) => suggestions[n - 1].0.to_string(),
+            _ => answer.to_uppercase(),
+        };
+        corrections.push((road.normalized_rd.clone(), correct_rd));
+    }
+
+    Ok(corrections)
+}
+
+/// Add the corrections to the road_typos CSV, the file is rewritten sorted by `normalized_rd` with no duplicates.
+///
+/// Existing entries in the file are kept over the new corrections, and are written exactly as they were so
+///   that the diff of the file only has the new entries. The new entries have the columns in the order of the
+///   file's header, any other columns are left empty. Returns the number of entries added.
+pub fn add_road_typos(
+    road_typos: &Path,
+    corrections: &[(String, String)],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let original = fs::read_to_string(road_typos).map_err(|e| {
+        format!(
+            "failed to read csv {road_typos}: {e}",
+            road_typos = road_typos.display()
+        )
+    })?;
+
+    // normalized_rd -> the line of the entry
+    let mut typos = BTreeMap::<String, String>::new();
+    let mut csv = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(original.as_bytes());
+    let header = csv.headers()?.clone();
+    let column = |name: &str| {
+        header.iter().position(|h| h == name).ok_or_else(|| {
+            format!(
+                "{road_typos} has no {name} column",
+                road_typos = road_typos.display()
+            )
+        })
+    };
+    let normalized_rd_column = column("normalized_rd")?;
+    let correct_rd_column = column("correct_rd")?;
+
+    let mut records = csv.records().peekable();
+    while let Some(record) = records.next() {
+        let record = record?;
+        let start = record.position().map_or(0, |p| p.byte() as usize);
+        let end = match records.peek() {
+            Some(Ok(next)) => next
+                .position()
+                .map_or(original.len(), |p| p.byte() as usize),
+            _ => original.len(),
+        };
+
+        let normalized_rd = record.get(normalized_rd_column).unwrap_or_default();
+        typos.entry(normalized_rd.to_string()).or_insert_with(|| {
+            original[start..end]
+                .trim_end_matches(['\r', '\n'])
+                .to_string()
+        });
+    }
+
+    let mut added = 0;
+    for (normalized_rd, correct_rd) in corrections {
+        if !typos.contains_key(normalized_rd) {
+            let line = (0..header.len())
+                .map(|i| {
+                    if i == normalized_rd_column {
+                        csv_field(normalized_rd)
+                    } else if i == correct_rd_column {
+                        format!(
+                            "\"{correct_rd}\"",
+                            correct_rd = correct_rd.replace('"', "\"\"")
+                        )
+                    } else {
+                        String::new()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            typos.insert(normalized_rd.clone(), line);
+            added += 1;
+        }
+    }
+
+    // the header is also kept as it was
+    let header_line = original.lines().next().unwrap_or_default();
+    let mut out = format!("{header_line}\n");
+    for line in typos.values() {
+        out.push_str(line);
+        out.push('\n');
+    }
+    fs::write(road_typos, out)?;
+
+    Ok(added)
+}
+
+/// Quote the field if it has spaces, commas or quotes, the same as the hand edited road_typos
+fn csv_field(field: &str) -> String {
+    if field.contains([' ', ',', '"']) {
+        format!("\"{field}\"", field = field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Extensions to the DB Connection to review the road corrections
+pub trait RoadsDB: NewDB {
+    /// All the normalized road names in the overlay's jurisdictions which have no correction, and aren't in the
+    ///   overlay's road_typos, most frequent first
+    fn unresolved_roads(
+        &self,
+        overlay: &Overlay,
+    ) -> Result<Vec<UnresolvedRoad>, Box<dyn std::error::Error>> {
+        let connection = self.connection();
+        let cnty_city_locs = sql_list(&overlay.jurisdictions.resolve(connection)?);
+
+        let mut stmt = connection.prepare(&format!(
+            "SELECT road, min(original), count(*) AS count FROM (
+                SELECT n.primary_rd AS road, c.primary_rd AS original
+                FROM normalized_roads AS n
+                JOIN collisions AS c ON c.case_id = n.case_id AND c.cnty_city_loc IN ({cnty_city_locs})
+                LEFT JOIN corrected_roads AS cr ON cr.case_id = n.case_id AND cr.overlay = :overlay
+                WHERE coalesce(cr.primary_rd, '') = ''
+                UNION ALL
+                SELECT n.secondary_rd AS road, c.secondary_rd AS original
+                FROM normalized_roads AS n
+                JOIN collisions AS c ON c.case_id = n.case_id AND c.cnty_city_loc IN ({cnty_city_locs})
+                LEFT JOIN corrected_roads AS cr ON cr.case_id = n.case_id AND cr.overlay = :overlay
+                WHERE coalesce(cr.secondary_rd, '') = ''
+            )
+            WHERE coalesce(road, '') != ''
+                AND road NOT IN (SELECT normalized_rd FROM road_typos WHERE overlay = :overlay)
+            GROUP BY road
+            ORDER BY count DESC, road"
+        ))?;
+
+        let unresolved = stmt
+            .query_map(named_params! {":overlay": overlay.name()}, |row| {
+                Ok(UnresolvedRoad {
+                    normalized_rd: row.get(0)?,
+                    original_rd: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    count: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(unresolved)
+    }
+
+    /// All the `(normalized_rd, correct_rd)` pairs in the overlay's road_typos, sorted
+    fn known_roads(
+        &self,
+        overlay: &Overlay,
+    ) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+        let mut stmt = self.connection().prepare(
+            "SELECT DISTINCT normalized_rd, correct_rd FROM road_typos
+            WHERE overlay = ? AND coalesce(normalized_rd, '') != '' AND coalesce(correct_rd, '') != ''
+            ORDER BY normalized_rd, correct_rd",
+        )?;
+        let roads = stmt
+            .query_map([overlay.name()], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(roads)
+    }
+}
+
+impl RoadsDB for Connection {}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, process};
+
+    use super::*;
+    use crate::schema;
+
+    /// Create the road tables, with a few collisions in Berkeley missing corrections
+    fn test_db(overlay: &Overlay) -> Connection {
+        let connection = schema::test_db(&[
+            "road_typos",
+            "corrected_roads",
+            "normalized_roads",
+            "collisions",
+        ]);
+        connection
+            .load_overlay_data("road_typos", overlay)
+            .expect("failed to load road_typos");
+
+        connection
+            .execute_batch(
+                "INSERT INTO collisions (case_id, cnty_city_loc, primary_rd, secondary_rd) VALUES
+                    ('1', '0103', 'SHATUCK AVE', 'CEDAR ST'),
+                    ('2', '0103', 'SHATUCK', 'UNIVERSITY AV'),
+                    ('3', '0103', 'CEDAR', 'SHATUCK AV'),
+                    ('4', '0104', 'SHATUCK', 'CEDAR');
+                INSERT INTO normalized_roads (case_id, primary_rd, secondary_rd) VALUES
+                    ('1', 'SHATUCK', 'CEDAR'),
+                    ('2', 'SHATUCK', 'UNIVERSITY'),
+                    ('3', 'CEDAR', 'SHATUCK'),
+                    ('4', 'SHATUCK', 'CEDAR');
+                INSERT INTO corrected_roads (case_id, primary_rd, secondary_rd, overlay) VALUES
+                    ('1', '', 'CEDAR ST', 'berkeley'),
+                    ('2', '', 'UNIVERSITY AVE', 'berkeley'),
+                    ('3', 'CEDAR ST', '', 'berkeley');",
+            )
+            .expect("failed to insert");
+
+        connection
+    }
+
+    #[test]
+    fn test_unresolved_roads() {
+        let overlay = Overlay::from_dir(Path::new("berkeley-tables")).expect("bad overlay");
+        let connection = test_db(&overlay);
+
+        // case 4 is outside of Berkeley
+        let unresolved = connection
+            .unresolved_roads(&overlay)
+            .expect("failed to find roads");
+        assert_eq!(
+            vec![UnresolvedRoad {
+                normalized_rd: "SHATUCK".to_string(),
+                original_rd: "SHATUCK".to_string(),
+                count: 3,
+            }],
+            unresolved
+        );
+
+        let known = connection.known_roads(&overlay).expect("no known roads");
+        assert!(known.contains(&("SHATTUCK".to_string(), "SHATTUCK AVE".to_string())));
+        assert_eq!(
+            Some(&"SHATTUCK AVE"),
+            suggest_roads("SHATUCK", &known)
+                .first()
+                .map(|(road, _)| road)
+        );
+    }
+
+    #[test]
+    fn test_review_roads() {
+        let known = [
+            ("CEDAR", "CEDAR ST"),
+            ("SHATTUCK", "SHATTUCK AVE"),
+            ("SHATTUCK AV", "SHATTUCK AVE"),
+            ("SHATTUCK PL", "SHATTUCK PL"),
+        ]
+        .map(|(normalized_rd, correct_rd)| (normalized_rd.to_string(), correct_rd.to_string()));
+        let unresolved = ["SHATUCK", "CEDDAR", "OXFORD", "HEARST"].map(|road| UnresolvedRoad {
+            normalized_rd: road.to_string(),
+            original_rd: road.to_string(),
+            count: 1,
+        });
+
+        let mut output = Vec::new();
+        let corrections = review_roads(
+            &unresolved,
+            &known,
+            Cursor::new("1\n\noxford st\nq\n"),
+            &mut output,
+        )
+        .expect("failed to review");
+        assert_eq!(
+            vec![
+                ("SHATUCK".to_string(), "SHATTUCK AVE".to_string()),
+                ("OXFORD".to_string(), "OXFORD ST".to_string())
+            ],
+            corrections
+        );
+
+        let output = String::from_utf8(output).expect("not utf8");
+        assert!(output.contains("[1/4] SHATUCK"), "{output}");
+        assert!(output.contains("1) SHATTUCK AVE"), "{output}");
+        assert!(
+            output.contains("[2/4] CEDDAR (1 collisions, e.g. 'CEDDAR')\n  1) CEDAR ST"),
+            "{output}"
+        );
+        assert!(
+            !output.contains("HEARST (1 collisions, e.g. 'HEARST')\n  1)"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_add_road_typos() {
+        let road_typos = std::env::temp_dir().join(format!(
+            "switrs-db-road-typos-{pid}.csv",
+            pid = process::id()
+        ));
+        fs::write(
+            &road_typos,
+            "normalized_rd,correct_rd\n\"10TH AV\",\"TENTH ST\"\nI-80,I-80\n",
+        )
+        .expect("failed to write");
+
+        let added = add_road_typos(
+            &road_typos,
+            &[
+                ("I-80".to_string(), "I-880".to_string()),
+                ("ASHBY".to_string(), "ASHBY AVE".to_string()),
+                ("I 80".to_string(), "I-80".to_string()),
+            ],
+        )
+        .expect("failed to add");
+        assert_eq!(2, added);
+        assert_eq!(
+            "normalized_rd,correct_rd\n\"10TH AV\",\"TENTH ST\"\nASHBY,\"ASHBY AVE\"\n\"I 80\",\"I-80\"\nI-80,I-80\n",
+            fs::read_to_string(&road_typos).expect("failed to read")
+        );
+
+        // the columns of the new entries are in the order of the header
+        fs::write(
+            &road_typos,
+            "correct_rd,normalized_rd\n\"TENTH ST\",\"10TH AV\"\n",
+        )
+        .expect("failed to write");
+        add_road_typos(
+            &road_typos,
+            &[("ASHBY".to_string(), "ASHBY AVE".to_string())],
+        )
+        .expect("failed to add");
+        assert_eq!(
+            "correct_rd,normalized_rd\n\"TENTH ST\",\"10TH AV\"\n\"ASHBY AVE\",ASHBY\n",
+            fs::read_to_string(&road_typos).expect("failed to read")
+        );
+
+        // without a correct_rd there is nowhere to add the correction
+        fs::write(&road_typos, "normalized_rd,road\nI-80,I-80\n").expect("failed to write");
+        assert!(add_road_typos(&road_typos, &[("I 80".to_string(), "I-80".to_string())]).is_err());
+
+        fs::remove_file(&road_typos).ok();
+    }
+
+    #[test]
+    fn test_road_matcher() {
+        let known = [
+            ("CEDAR", "CEDAR ST"),
+            ("10TH", "TENTH ST"),
+            ("SHATTUCK", "SHATTUCK AVE"),
+            ("SHATTUCK PL", "SHATTUCK PL"),
+            ("MARTIN LUTHER KING JR", "MARTIN LUTHER KING JR WAY"),
+            ("ASHBY", "ASHBY AVE"),
+        ]
+        .map(|(normalized_rd, correct_rd)| (normalized_rd.to_string(), correct_rd.to_string()));
+        let matcher = RoadMatcher::new(&known);
+
+        // abbreviations and ordinals
+        assert_eq!(Some(("CEDAR ST", 0.95)), matcher.match_road("CEDAR STREET"));
+        assert_eq!(Some(("TENTH ST", 0.95)), matcher.match_road("10TH ST"));
+        assert_eq!(
+            Some(("ASHBY AVE", 0.95)),
+            matcher.match_road("ASHBY AVENUE")
+        );
+
+        // token reordering
+        assert_eq!(
+            Some(("MARTIN LUTHER KING JR WAY", 0.9)),
+            matcher.match_road("KING JR MARTIN LUTHER")
+        );
+
+        // the street type is wrong
+        assert_eq!(Some(("CEDAR ST", 0.85)), matcher.match_road("CEDAR AV"));
+
+        // edit distance
+        let (road, confidence) = matcher.match_road("SHATUCK AVE").expect("no match");
+        assert_eq!("SHATTUCK AVE", road);
+        assert!(
+            confidence > MIN_CONFIDENCE && confidence < 0.85,
+            "{confidence}"
+        );
+
+        // SHATTUCK AVE and SHATTUCK PL are both SHATTUCK without the street type
+        assert_eq!(None, matcher.match_road("SHATTUCK BLVD"));
+        assert_eq!(None, matcher.match_road("OXFORD ST"));
+        assert_eq!(None, matcher.match_road(""));
+    }
+
+    #[test]
+    fn test_choose_correction() {
+        let no_fuzzy = || -> Option<(String, f64)> { panic!("only exact corrections needed") };
+        assert_eq!(
+            CorrectionSource::Manual,
+            RoadCorrection::choose(Some("CEDAR ST"), Some("CEDAR"), None, no_fuzzy).source
+        );
+        assert_eq!(
+            CorrectionSource::Typo,
+            RoadCorrection::choose(Some(""), None, Some("CEDAR ST"), no_fuzzy).source
+        );
+
+        let fuzzy =
+            RoadCorrection::choose(None, None, None, || Some(("CEDAR ST".to_string(), 0.85)));
+        assert_eq!(CorrectionSource::Fuzzy, fuzzy.source);
+        assert_eq!(Some(0.85), fuzzy.confidence);
+
+        let unresolved = RoadCorrection::choose(None, None, None, || None);
+        assert_eq!(CorrectionSource::Unresolved, unresolved.source);
+        assert_eq!("", unresolved.road);
+        assert_eq!(None, unresolved.confidence);
+    }
+}
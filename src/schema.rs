@@ -1,24 +1,38 @@
 //! Schema operations for the SWITRS sqlite DB creation
 
 use std::{
-    borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeSet, HashMap, HashSet},
     fs,
     io::Write,
     path::{Path, PathBuf},
-    sync::OnceLock,
+    time::Instant,
 };
 
 use new_string_template::template::Template;
-use regex::Regex;
-use rusqlite::{params_from_iter, Connection};
+use rusqlite::{named_params, params, params_from_iter, Connection, Row};
 use serde::Deserialize;
 
+use crate::{
+    address::{normalize_road, NormalizedRoad},
+    geocode::GeocodeDB,
+    geopackage::GeoPackageDB,
+    migrations::MigrateDB,
+    overlay::Overlay,
+    roads::{CorrectionSource, RoadCorrection, RoadMatcher, RoadsDB},
+    spatial::SpatialDB,
+};
+
+/// Number of rows between each progress report while loading a table
+const PROGRESS_ROWS: usize = 100_000;
+
+/// Header of the corrected_roads CSV
+const CORRECTED_ROADS_HEADER: &str = "case_id,primary_rd,secondary_rd,primary_rd_confidence,secondary_rd_confidence,primary_rd_source,secondary_rd_source,original_primary_rd,original_secondary_rd";
+
 /// Specifies which schema and data should be used for creating a table
 #[derive(Debug, Deserialize)]
 pub struct LookupTable {
     pk_type: String,
-    data: PathBuf,
+    pub(crate) data: PathBuf,
     schema: Option<PathBuf>,
 }
 
@@ -30,6 +44,8 @@ pub enum DataPath {
     RawData(PathBuf),
     /// Path relative to the application
     Path(PathBuf),
+    /// Load the data from each of the overlays which has data for the table
+    Overlay,
     /// Create the table as empty
     Empty,
 }
@@ -38,32 +54,216 @@ pub enum DataPath {
 #[derive(Debug, Deserialize)]
 pub struct PrimaryTable {
     /// Path to the schema file for the table, like collisions.sql
-    schema: PathBuf,
+    pub(crate) schema: PathBuf,
 
     /// Path to the data to load into the table
     #[serde(flatten)]
-    data: DataPath,
+    pub(crate) data: DataPath,
+}
+
+/// Jurisdictions to include in the views, each entry is matched against lookup-tables/CNTY_CITY_LOC.csv
+#[derive(Debug, Default, Deserialize)]
+pub struct Jurisdictions {
+    /// County City Location codes, like "0102"
+    #[serde(default)]
+    codes: Vec<String>,
+    /// County names, all the cities in the county are included
+    #[serde(default)]
+    counties: Vec<String>,
+    /// City names, some cities have more than one code
+    #[serde(default)]
+    cities: Vec<String>,
+}
+
+impl Jurisdictions {
+    /// The jurisdictions of a Schemas TOML without `[jurisdictions]`, the codes of Berkeley in Alameda County,
+    ///   which were the only ones in the views before they were configurable
+    fn berkeley() -> Self {
+        Self {
+            codes: vec![String::from("0102"), String::from("0103")],
+            ..Self::default()
+        }
+    }
+
+    /// Resolve all the codes, counties and cities into County City Location codes,
+    ///   this requires the cnty_city_loc lookup table to already be loaded.
+    pub fn resolve(
+        &self,
+        connection: &Connection,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut cnty_city_locs = BTreeSet::new();
+
+        for (kind, column, names) in [
+            ("code", "id", &self.codes),
+            ("county", "county", &self.counties),
+            ("city", "city", &self.cities),
+        ] {
+            let mut select = connection.prepare(&format!(
+                "SELECT id FROM cnty_city_loc WHERE lower({column}) = lower(?)"
+            ))?;
+
+            for name in names {
+                let found = select
+                    .query_map([name], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if found.is_empty() {
+                    return Err(format!("unknown {kind} in jurisdictions: {name}").into());
+                }
+                cnty_city_locs.extend(found);
+            }
+        }
+
+        if cnty_city_locs.is_empty() {
+            return Err("no codes, counties or cities declared in jurisdictions".into());
+        }
+
+        Ok(cnty_city_locs.into_iter().collect())
+    }
+}
+
+/// Where the rebuilt corrected_roads of each overlay are written. By default nothing is written, and the new and
+///   changed corrections are only shown as a diff
+#[derive(Debug, Default, Deserialize)]
+pub struct Corrections {
+    /// Directory to write each overlay's corrections to, as `{overlay}_CORRECTED_ROADS.csv`. There is no default,
+    ///   so that the overlay's own corrected_roads CSV, which is tracked in the repo, is never overwritten
+    dir: Option<PathBuf>,
+    /// Write the corrections, instead of only showing the diff
+    #[serde(default)]
+    write: bool,
+}
+
+impl Corrections {
+    /// Path the corrections of the overlay are written to, if a directory was set
+    pub fn path(&self, overlay: &Overlay) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| {
+            dir.join(format!(
+                "{overlay}_CORRECTED_ROADS.csv",
+                overlay = overlay.name()
+            ))
+        })
+    }
+
+    /// Writing the corrections requires a directory to write them to
+    fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.write && self.dir.is_none() {
+            return Err("writing the road corrections needs a directory, set `dir` in [corrections] or pass --corrections-dir".into());
+        }
+
+        Ok(())
+    }
 }
 
 /// Schema defenition as loaded from the Toml
 #[derive(Debug, Deserialize)]
 pub struct Schema {
     #[serde(alias = "table-order")]
-    table_order: Vec<String>,
-    tables: HashMap<String, PrimaryTable>,
+    pub(crate) table_order: Vec<String>,
+    pub(crate) tables: HashMap<String, PrimaryTable>,
     #[serde(alias = "lookup-schema")]
-    lookup_schema: PathBuf,
+    pub(crate) lookup_schema: PathBuf,
     #[serde(alias = "lookup-tables")]
-    lookup_tables: HashMap<String, LookupTable>,
+    pub(crate) lookup_tables: HashMap<String, LookupTable>,
+    /// Jurisdictions included in the views, see `Jurisdictions::berkeley` when missing
+    #[serde(default = "Jurisdictions::berkeley")]
+    pub(crate) jurisdictions: Jurisdictions,
+    /// Where the road corrections are written, see `Corrections`
+    #[serde(default)]
+    pub(crate) corrections: Corrections,
+    /// Also write the collisions as a GeoPackage point layer, see `geopackage`
+    #[serde(default)]
+    pub(crate) geopackage: bool,
+    /// Directories of the city overlays, each has an overlay.toml manifest
+    #[serde(default, rename = "overlays")]
+    overlay_dirs: Vec<PathBuf>,
+    #[serde(skip)]
+    pub(crate) overlays: Vec<Overlay>,
 }
 
 impl Schema {
-    /// Loads the Schema definition from the Toml at the given path
+    /// Loads the Schema definition from the Toml at the given path, and all the overlays it references. The paths
+    ///   in the Toml are relative to its directory, except for the raw data which is relative to the data directory
     pub fn from_toml_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
-        let schema = basic_toml::from_slice(&fs::read(path)?)?;
+        let mut schema: Self = basic_toml::from_slice(
+            &fs::read(path)
+                .map_err(|e| format!("failed to read {path}: {e}", path = path.display()))?,
+        )?;
+        schema.resolve_paths(path.parent().unwrap_or_else(|| Path::new("")));
+        schema.corrections.validate()?;
+
+        let mut names = HashSet::new();
+        for dir in &schema.overlay_dirs {
+            let overlay = Overlay::from_dir(dir)?;
+            if !names.insert(overlay.name().to_string()) {
+                return Err(
+                    format!("duplicate overlay name: {name}", name = overlay.name()).into(),
+                );
+            }
+
+            schema.overlays.push(overlay);
+        }
 
         Ok(schema)
     }
+
+    /// Make all the paths from the Toml relative to `dir`, the directory of the Toml
+    fn resolve_paths(&mut self, dir: &Path) {
+        self.lookup_schema = dir.join(&self.lookup_schema);
+        for table in self.lookup_tables.values_mut() {
+            table.data = dir.join(&table.data);
+            table.schema = table.schema.as_ref().map(|schema| dir.join(schema));
+        }
+        for table in self.tables.values_mut() {
+            table.schema = dir.join(&table.schema);
+            if let DataPath::Path(path) = &mut table.data {
+                *path = dir.join(&path);
+            }
+        }
+        for overlay_dir in &mut self.overlay_dirs {
+            *overlay_dir = dir.join(&overlay_dir);
+        }
+        self.corrections.dir = self.corrections.dir.as_ref().map(|path| dir.join(path));
+    }
+
+    /// Override where the road corrections are written, `dir` replaces the directory from the Toml when set,
+    ///   and `write` enables writing them
+    pub fn set_corrections(
+        &mut self,
+        dir: Option<PathBuf>,
+        write: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if dir.is_some() {
+            self.corrections.dir = dir;
+        }
+        self.corrections.write |= write;
+        self.corrections.validate()
+    }
+
+    /// Enable writing the GeoPackage layer, it can only be enabled and not disabled from the Toml
+    pub fn set_geopackage(&mut self, geopackage: bool) {
+        self.geopackage |= geopackage;
+    }
+
+    /// All the overlays referenced by the Schema
+    pub fn overlays(&self) -> &[Overlay] {
+        &self.overlays
+    }
+
+    /// Total size in bytes of all the raw data files in the directory `data`
+    pub fn raw_data_size(&self, data: &Path) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut size = 0;
+        for table in self.tables.values() {
+            if let DataPath::RawData(path) = &table.data {
+                let path = data.join(path);
+                size += fs::metadata(&path)
+                    .map_err(|e| format!("failed to read {path}: {e}", path = path.display()))?
+                    .len();
+            }
+        }
+
+        Ok(size)
+    }
 }
 
 /// Extensions to the DB Connection to initialize the DB
@@ -77,6 +277,15 @@ pub trait NewDB {
         name: &str,
         pk_type: &str,
         table_schema: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.create_table_with_params(table_schema, &[("table", name), ("pk_type", pk_type)])
+    }
+
+    /// Create a table (and views) where all the params are passed into the sql as template parameters
+    fn create_table_with_params(
+        &self,
+        table_schema: &Path,
+        params: &[(&str, &str)],
     ) -> Result<(), Box<dyn std::error::Error>> {
         // build the DDL expression
         let ddl = fs::read_to_string(table_schema).map_err(|e| {
@@ -86,14 +295,14 @@ pub trait NewDB {
             )
         })?;
         let ddl = Template::new(ddl);
-        let data = {
-            let mut map = HashMap::new();
-            map.insert("table", name);
-            map.insert("pk_type", pk_type);
-            map
-        };
+        let data = params.iter().copied().collect::<HashMap<_, _>>();
 
-        let ddl = ddl.render(&data)?;
+        let ddl = ddl.render(&data).map_err(|e| {
+            format!(
+                "failed to render {table_schema}: {e}",
+                table_schema = table_schema.display()
+            )
+        })?;
         self.connection().execute_batch(&ddl)?;
         Ok(())
     }
@@ -158,11 +367,19 @@ pub trait NewDB {
             return Ok(0);
         }
 
+        // load all the rows in a single transaction, unless the caller already has one open
+        let tx = if self.connection().is_autocommit() {
+            Some(self.connection().unchecked_transaction()?)
+        } else {
+            None
+        };
+
         let mut insert_stmt = self
             .connection()
             .prepare(&format!("INSERT INTO {name} ({fields}) VALUES({values})"))?;
 
         // collect all the data
+        let start = Instant::now();
         let mut count = 0;
         for record in csv.into_records() {
             let record = record?;
@@ -201,11 +418,127 @@ pub trait NewDB {
                 })?;
 
             count += 1;
+            if count % PROGRESS_ROWS == 0 {
+                print!(
+                    "\r    {count} rows, {rate} rows/sec",
+                    rate = rows_per_sec(count, start)
+                );
+                std::io::stdout().flush()?;
+            }
+        }
+
+        drop(insert_stmt);
+        if let Some(tx) = tx {
+            tx.commit()?;
+        }
+
+        if count >= PROGRESS_ROWS {
+            println!(
+                "\r    {count} rows in {elapsed:.1?}, {rate} rows/sec",
+                elapsed = start.elapsed(),
+                rate = rows_per_sec(count, start)
+            );
         }
 
         Ok(count)
     }
 
+    /// Drop all the indexes on the named table, returning the SQL to recreate them with `create_indexes`.
+    ///
+    /// Building the indexes once after the table is loaded is much faster than updating them on every insert.
+    fn drop_indexes(&self, name: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        // indexes without sql are the automatic ones for PRIMARY KEY and UNIQUE constraints, those can't be dropped
+        let mut select = self.connection().prepare(
+            "SELECT name, sql FROM sqlite_master WHERE type = 'index' AND tbl_name = ? AND sql IS NOT NULL",
+        )?;
+        let indexes = select
+            .query_map([name], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(String, String)>, _>>()?;
+
+        for (index, _) in &indexes {
+            self.connection()
+                .execute(&format!("DROP INDEX {index}"), [])?;
+        }
+
+        Ok(indexes.into_iter().map(|(_, sql)| sql).collect())
+    }
+
+    /// Recreate the indexes dropped by `drop_indexes`
+    fn create_indexes(&self, indexes: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        for index in indexes {
+            self.connection().execute(index, [])?;
+        }
+
+        Ok(())
+    }
+
+    /// Tune the DB for bulk loading, the journal is kept in memory and writes are not synced to disk.
+    ///
+    /// This is only safe for building a new DB, where a failed build is discarded. The returned `BulkLoad`
+    ///   restores the defaults when it's dropped, call `BulkLoad::end` once everything is loaded.
+    fn begin_bulk_load(&self) -> Result<BulkLoad<'_>, Box<dyn std::error::Error>> {
+        let connection = self.connection();
+
+        // journal_mode returns the new mode, in memory DBs always stay in "memory"
+        connection.pragma_update_and_check(None, "journal_mode", "MEMORY", |_| Ok(()))?;
+        connection.pragma_update(None, "synchronous", "OFF")?;
+        connection.pragma_update(None, "temp_store", "MEMORY")?;
+        // negative is in KiB, 256MiB
+        connection.pragma_update(None, "cache_size", -262_144)?;
+        // the bundled sqlite enforces foreign keys by default, unknown codes would fail the insert. Those are
+        //   reported by the integrity check after loading instead, see `IntegrityDB`
+        connection.pragma_update(None, "foreign_keys", "OFF")?;
+
+        Ok(BulkLoad {
+            connection,
+            ended: false,
+        })
+    }
+
+    /// Restore the pragmas changed by `begin_bulk_load` to the defaults
+    fn end_bulk_load(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let connection = self.connection();
+
+        connection.pragma_update_and_check(None, "journal_mode", "DELETE", |_| Ok(()))?;
+        connection.pragma_update(None, "synchronous", "FULL")?;
+        connection.pragma_update(None, "temp_store", "DEFAULT")?;
+        connection.pragma_update(None, "cache_size", -2_000)?;
+        connection.pragma_update(None, "foreign_keys", "ON")?;
+
+        Ok(())
+    }
+
+    /// Load the overlay's data for the named table, if it has any, and tag all the rows with the overlay name
+    fn load_overlay_data(
+        &self,
+        name: &str,
+        overlay: &Overlay,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let Some(table_data) = overlay.table_data(name) else {
+            return Ok(0);
+        };
+
+        println!("LOADING {name} from {overlay}", overlay = overlay.name());
+        let count = self.load_data(name, &table_data)?;
+        self.set_overlay(name, overlay)?;
+
+        Ok(count)
+    }
+
+    /// Tag all the newly loaded rows in the named table, those without an overlay, with the overlay name
+    fn set_overlay(
+        &self,
+        name: &str,
+        overlay: &Overlay,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let count = self.connection().execute(
+            &format!("UPDATE {name} SET overlay = ? WHERE overlay IS NULL"),
+            [overlay.name()],
+        )?;
+
+        Ok(count)
+    }
+
     /// Initialize all the lookup tables in lookup_tables
     fn init_lookup_tables(
         &self,
@@ -228,10 +561,20 @@ pub trait NewDB {
         schemas: &Schema,
         data: &Path,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        let bulk_load = self.connection().begin_bulk_load()?;
+
         // initialize lookup tables
         self.connection()
             .init_lookup_tables(&schemas.lookup_tables, &schemas.lookup_schema)?;
 
+        // the views are restricted to the configured jurisdictions
+        let cnty_city_locs = schemas.jurisdictions.resolve(self.connection())?;
+        println!(
+            "JURISDICTIONS {cnty_city_locs}",
+            cnty_city_locs = cnty_city_locs.join(", ")
+        );
+        let cnty_city_locs = sql_list(&cnty_city_locs);
+
         // Build all the standard tables
         for table_name in &schemas.table_order {
             let table: &PrimaryTable = schemas
@@ -242,34 +585,91 @@ pub trait NewDB {
             let data = match &table.data {
                 DataPath::RawData(path) => Some(data.join(path)),
                 DataPath::Path(path) => Some(path.clone()),
-                DataPath::Empty => None,
+                DataPath::Overlay | DataPath::Empty => None,
             };
 
             println!("LOADING {table_name}");
-            self.connection()
-                .create_table(table_name, "", &table.schema)?;
+            self.connection().create_table_with_params(
+                &table.schema,
+                &[
+                    ("table", table_name),
+                    ("pk_type", ""),
+                    ("cnty_city_locs", &cnty_city_locs),
+                ],
+            )?;
 
             if let Some(data) = data {
+                let indexes = self.connection().drop_indexes(table_name)?;
                 self.connection().load_data(table_name, &data)?;
+                self.connection().create_indexes(&indexes)?;
+            }
+
+            if let DataPath::Overlay = table.data {
+                for overlay in &schemas.overlays {
+                    self.connection().load_overlay_data(table_name, overlay)?;
+                }
             }
         }
 
         // build fixup tables
-        self.fixup_tables()?;
+        self.fixup_tables(schemas)?;
+
+        // the DB was created from the latest schema, so no migrations need to be applied to it
+        self.connection().mark_migrations_applied()?;
+        bulk_load.end()?;
 
         Ok(())
     }
 
     /// Run tasks to fill fixup tables, or produce csv's which add lookup tables to cleanup data
-    fn fixup_tables(&self) -> Result<(), Box<dyn std::error::Error>> {
-        self.fixup_roads()?;
+    fn fixup_tables(&self, schemas: &Schema) -> Result<(), Box<dyn std::error::Error>> {
+        self.fixup_roads(schemas)?;
+        self.connection().geocode_collisions()?;
+        self.connection().check_coordinates(schemas)?;
+        self.connection().index_collision_points()?;
+        self.connection().update_geopackage(schemas)?;
 
         Ok(())
     }
 
-    /// This uses the Berkeley Road Typos and the Corrected Roads to construct a lookup table with correct road names
-    ///   for each Case ID
-    fn fixup_roads(&self) -> Result<(), Box<dyn std::error::Error>> {
+    /// This uses the Road Typos and the Corrected Roads of each overlay to construct a lookup table with correct
+    ///   road names for each Case ID
+    fn fixup_roads(&self, schemas: &Schema) -> Result<(), Box<dyn std::error::Error>> {
+        self.fixup_roads_for(schemas, None)
+    }
+
+    /// Rerun the road fixups of an existing DB, after the road_typos, corrected_roads or intersections of an
+    ///   overlay were edited.
+    ///
+    /// The road tables are reloaded from the overlays, and the normalized and corrected roads are rebuilt for
+    ///   every collision, then the collisions are geocoded, checked and indexed again.
+    fn refix_roads(&self, schemas: &Schema) -> Result<(), Box<dyn std::error::Error>> {
+        self.connection().execute_batch(
+            "DELETE FROM normalized_roads;
+            DELETE FROM corrected_roads;
+            DELETE FROM road_typos;
+            DELETE FROM intersections;",
+        )?;
+
+        for overlay in &schemas.overlays {
+            self.load_overlay_data("road_typos", overlay)?;
+            self.load_overlay_data("corrected_roads", overlay)?;
+            self.load_overlay_data("intersections", overlay)?;
+        }
+
+        self.fixup_tables(schemas)
+    }
+
+    /// Same as `fixup_roads`, but only normalizes and corrects the roads of the given cases when `case_ids` is set,
+    ///   this is used when merging a newer export into an existing DB.
+    fn fixup_roads_for(
+        &self,
+        schemas: &Schema,
+        case_ids: Option<&HashSet<String>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let case_filter = case_filter(self.connection(), "case_id", case_ids)?;
+        let tx = self.connection().unchecked_transaction()?;
+
         // when processing collision data, we will cleanup some data,
         //   for that we have some custom insert and one off tables
         let mut insert_road_stmt = self.connection().prepare(
@@ -282,7 +682,8 @@ pub trait NewDB {
                 secondary_rd,
                 secondary_rd_address,
                 secondary_rd_block,
-                seconardy_rd_direction
+                seconardy_rd_direction,
+                secondary_rd_inferred
             ) VALUES(
                 ?,
                 ?,
@@ -292,46 +693,85 @@ pub trait NewDB {
                 ?,
                 ?,
                 ?,
+                ?,
                 ?
             )",
         )?;
 
-        let mut select_roads = self
-            .connection()
-            .prepare("SELECT case_id, primary_rd, secondary_rd FROM collisions")?;
+        let mut select_roads = self.connection().prepare(&format!(
+            "SELECT case_id, primary_rd, secondary_rd FROM collisions WHERE 1 {case_filter}"
+        ))?;
 
         let mut roads = select_roads.query([])?;
         while let Some(road) = roads.next()? {
             // add normalized roads from the collisions table
             let case_id = road.get_ref("case_id")?.as_str()?;
-            let primary_rd = road.get_ref("primary_rd")?.as_str()?;
-            let secondary_rd = road.get_ref("secondary_rd")?.as_str()?;
+            let primary_rd = road.get_ref("primary_rd")?.as_str_or_null()?;
+            let secondary_rd = road.get_ref("secondary_rd")?.as_str_or_null()?;
 
-            let primary_rd = normalize_road(primary_rd);
-            let secondary_rd = normalize_road(secondary_rd);
+            let (primary_rd, secondary_rd, secondary_rd_inferred) = normalize_roads(
+                primary_rd.unwrap_or_default(),
+                secondary_rd.unwrap_or_default(),
+            );
 
-            insert_road_stmt.insert([
-                Some(case_id),
-                Some(&primary_rd.road),
+            insert_road_stmt.insert(params![
+                case_id,
+                primary_rd.road,
                 primary_rd.address,
                 primary_rd.block,
                 primary_rd.direction,
-                Some(&secondary_rd.road),
+                secondary_rd.road,
                 secondary_rd.address,
                 secondary_rd.block,
                 secondary_rd.direction,
+                secondary_rd_inferred,
             ])
             .inspect_err(|e| {
                 println!("error on insert into normalized_roadcase_id={case_id},primary={primary_rd:?},secondary={secondary_rd:?}: {e}");
             })?;
         }
 
+        drop(roads);
+        drop(select_roads);
+        drop(insert_road_stmt);
+        tx.commit()?;
+
+        for overlay in &schemas.overlays {
+            self.fixup_overlay_roads(overlay, &schemas.corrections, case_ids)?;
+        }
+
+        Ok(())
+    }
+
+    /// Correct the normalized roads of all the collisions in the overlay's jurisdictions, or only of the given
+    ///   cases when `case_ids` is set, and update any new or changed corrections in corrected_roads. The changes
+    ///   are shown as a diff, and the rebuilt corrections are only written when enabled in `corrections`
+    fn fixup_overlay_roads(
+        &self,
+        overlay: &Overlay,
+        corrections_output: &Corrections,
+        case_ids: Option<&HashSet<String>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (Some(road_typos), Some(_)) = (
+            overlay.table_data("road_typos"),
+            overlay.table_data("corrected_roads"),
+        ) else {
+            println!(
+                "SKIPPING road fixups for {overlay}, it has no road_typos or corrected_roads",
+                overlay = overlay.name()
+            );
+            return Ok(());
+        };
+
+        let cnty_city_locs = sql_list(&overlay.jurisdictions.resolve(self.connection())?);
+        let case_filter = case_filter(self.connection(), "n.case_id", case_ids)?;
+
         //
         // find all roads not in our known roads list
         //   we will prefer names that match the "known list", the move to the normalized name matching, then typos
         let mut select_roads = self
             .connection()
-            .prepare("
+            .prepare(&format!("
                 SELECT 
                 n.case_id as case_id,
                 n.primary_rd as normal_primary_rd,
@@ -346,39 +786,62 @@ pub trait NewDB {
                 c.secondary_rd as original_secondary_rd,
                 cr.primary_rd as correct_primary_rd,
                 cr.secondary_rd as correct_secondary_rd,
+                cr.case_id as correct_case_id,
+                cr.primary_rd_confidence as correct_primary_rd_confidence,
+                cr.secondary_rd_confidence as correct_secondary_rd_confidence,
+                cr.primary_rd_source as correct_primary_rd_source,
+                cr.secondary_rd_source as correct_secondary_rd_source,
+                cr.original_primary_rd as correct_original_primary_rd,
+                cr.original_secondary_rd as correct_original_secondary_rd,
                 cp.primary_rd as verified_primary_rd,
                 cs.secondary_rd as verified_secondary_rd,
                 tp.correct_rd as suggest_primary_rd,
                 ts.correct_rd as suggest_secondary_rd
                 FROM 
                 normalized_roads as n
-                LEFT JOIN collisions_view as c ON c.case_id = n.case_id
-                LEFT JOIN corrected_roads as cr ON cr.case_id = n.case_id
-                LEFT JOIN collisions as cp ON cp.case_id = n.case_id AND cp.primary_rd in (SELECT DISTINCT correct_rd FROM berkeley_road_typos)
-                LEFT JOIN collisions as cs ON cs.case_id = n.case_id AND cs.secondary_rd in (SELECT DISTINCT correct_rd FROM berkeley_road_typos)
-                LEFT JOIN berkeley_road_typos as tp ON tp.normalized_rd = n.primary_rd
-                LEFT JOIN berkeley_road_typos as ts ON ts.normalized_rd = n.secondary_rd
+                JOIN collisions as c ON c.case_id = n.case_id AND c.cnty_city_loc IN ({cnty_city_locs})
+                LEFT JOIN corrected_roads as cr ON cr.case_id = n.case_id AND cr.overlay = :overlay
+                LEFT JOIN collisions as cp ON cp.case_id = n.case_id AND cp.primary_rd in (SELECT DISTINCT correct_rd FROM road_typos WHERE overlay = :overlay)
+                LEFT JOIN collisions as cs ON cs.case_id = n.case_id AND cs.secondary_rd in (SELECT DISTINCT correct_rd FROM road_typos WHERE overlay = :overlay)
+                LEFT JOIN road_typos as tp ON tp.normalized_rd = n.primary_rd AND tp.overlay = :overlay
+                LEFT JOIN road_typos as ts ON ts.normalized_rd = n.secondary_rd AND ts.overlay = :overlay
+                WHERE 1 {case_filter}
                 ORDER BY case_id
-            ")?;
-        let mut corrections = select_roads.query([])?;
-
-        // we will always rebuild the corrections file.
-        let mut corrected_roads = fs::OpenOptions::new()
-            .truncate(true)
-            .write(true)
-            .open("berkeley-tables/CORRECTED_ROADS.csv")?;
-        writeln!(corrected_roads, "case_id,primary_rd,secondary_rd")?;
+            "))?;
+        let mut corrections = select_roads.query(named_params! {":overlay": overlay.name()})?;
+
+        // roads without an exact correction are matched fuzzily against the known roads, most names are
+        //   repeated many times so each is only matched once
+        let matcher = RoadMatcher::new(&self.connection().known_roads(overlay)?);
+        let mut fuzzy_matches = HashMap::<String, Option<(String, f64)>>::new();
+        let mut fuzzy_match = |road: &str| {
+            fuzzy_matches
+                .entry(road.to_string())
+                .or_insert_with(|| {
+                    matcher
+                        .match_road(road)
+                        .map(|(correct_rd, confidence)| (correct_rd.to_string(), confidence))
+                })
+                .clone()
+        };
+
+        // the corrections file is always rebuilt, but only written when asked to
+        let mut corrected_roads = format!("{CORRECTED_ROADS_HEADER}\n");
+        let mut changed = Vec::new();
+        let mut diffs = 0;
         while let Some(correction) = corrections.next()? {
             let case_id = correction.get_ref("case_id")?.as_str()?;
             let normal_primary_rd = correction.get_ref("normal_primary_rd")?.as_str()?;
             let normal_secondary_rd = correction.get_ref("normal_secondary_rd")?.as_str()?;
             let original_primary_rd = correction.get_ref("original_primary_rd")?.as_str()?;
-            let original_secondary_rd = correction.get_ref("original_secondary_rd")?.as_str()?;
+            let original_secondary_rd = correction
+                .get_ref("original_secondary_rd")?
+                .as_str_or_null()?
+                .unwrap_or_default();
 
-            let correct_primary_rd = correction.get_ref("correct_primary_rd")?.as_str_or_null()?;
-            let correct_secondary_rd = correction
-                .get_ref("correct_secondary_rd")?
-                .as_str_or_null()?;
+            // the existing correction, as it was loaded from the corrected_roads file
+            let existing_primary_rd = CorrectedRoad::from_row(correction, "primary_rd")?;
+            let existing_secondary_rd = CorrectedRoad::from_row(correction, "secondary_rd")?;
 
             let verified_primary_rd = correction
                 .get_ref("verified_primary_rd")?
@@ -390,40 +853,170 @@ pub trait NewDB {
             let suggest_secondary_rd = correction
                 .get_ref("suggest_secondary_rd")?
                 .as_str_or_null()?;
+            let manual_primary_rd =
+                existing_primary_rd.manual(verified_primary_rd, suggest_primary_rd);
+            let manual_secondary_rd =
+                existing_secondary_rd.manual(verified_secondary_rd, suggest_secondary_rd);
+
+            let primary_rd = RoadCorrection::choose(
+                manual_primary_rd,
+                verified_primary_rd,
+                suggest_primary_rd,
+                || fuzzy_match(normal_primary_rd),
+            );
+            let secondary_rd = RoadCorrection::choose(
+                manual_secondary_rd,
+                verified_secondary_rd,
+                suggest_secondary_rd,
+                || fuzzy_match(normal_secondary_rd),
+            );
+
+            let row = corrected_roads_row(
+                case_id,
+                &CorrectedRoad::new(&primary_rd, original_primary_rd),
+                &CorrectedRoad::new(&secondary_rd, original_secondary_rd),
+            );
+            corrected_roads.push_str(&row);
+            corrected_roads.push('\n');
+
+            // the changed corrections are replaced in corrected_roads
+            let is_new = correction
+                .get_ref("correct_case_id")?
+                .as_str_or_null()?
+                .is_none();
+            let existing_row =
+                corrected_roads_row(case_id, &existing_primary_rd, &existing_secondary_rd);
+            // corrections from before the confidence and source were recorded have only changed when their
+            //   roads did, they are still replaced to record the confidence and source
+            let is_legacy = !is_new
+                && existing_primary_rd.source.is_empty()
+                && existing_secondary_rd.source.is_empty();
+            let is_changed = if is_legacy {
+                existing_primary_rd.road != primary_rd.road
+                    || existing_secondary_rd.road != secondary_rd.road
+            } else {
+                row != existing_row
+            };
+            if is_new || is_changed {
+                if diffs == 0 {
+                    println!(
+                        "DIFF corrected_roads for {overlay}",
+                        overlay = overlay.name()
+                    );
+                }
+                if !is_new {
+                    println!("  - {existing_row}");
+                }
+                println!("  + {row}");
+                diffs += 1;
+            }
+            if is_new || is_changed || is_legacy {
+                changed.push((
+                    case_id.to_string(),
+                    primary_rd.clone(),
+                    secondary_rd.clone(),
+                    original_primary_rd.to_string(),
+                    original_secondary_rd.to_string(),
+                ));
+            }
+
+            match primary_rd.source {
+                CorrectionSource::Unresolved => {
+                    println!("WARNING {case_id} has unknown primary_rd: {original_primary_rd}");
+                    println!("  to get of this warning add '{normal_primary_rd}' as 'normalized_rd' to {road_typos} and the 'correct_rd' entry", road_typos = road_typos.display());
+                    println!("  or add the original name '{original_primary_rd}' as 'normalized_rd' to {road_typos} and the 'correct_rd' entry", road_typos = road_typos.display());
+                }
+                CorrectionSource::Fuzzy => {
+                    println!("FUZZY {case_id} primary_rd: '{original_primary_rd}' matched '{road}' ({confidence})", road = primary_rd.road, confidence = format_confidence(primary_rd.confidence));
+                }
+                _ => (),
+            }
 
-            let primary_rd = correct_primary_rd
-                .or(verified_primary_rd.or(suggest_primary_rd))
-                .unwrap_or("");
-            let secondary_rd = correct_secondary_rd
-                .or(verified_secondary_rd.or(suggest_secondary_rd))
-                .unwrap_or("");
+            match secondary_rd.source {
+                CorrectionSource::Unresolved => {
+                    println!("WARNING {case_id} has unknown secondary_rd: {original_secondary_rd}");
+                    println!("  to get of this warning add '{normal_secondary_rd}' as 'normalized_rd' to {road_typos} and the 'correct_rd' entry", road_typos = road_typos.display());
+                    println!("  or add the original name '{original_secondary_rd}' as 'normalized_rd' to {road_typos} and the 'correct_rd' entry", road_typos = road_typos.display());
+                }
+                CorrectionSource::Fuzzy => {
+                    println!("FUZZY {case_id} secondary_rd: '{original_secondary_rd}' matched '{road}' ({confidence})", road = secondary_rd.road, confidence = format_confidence(secondary_rd.confidence));
+                }
+                _ => (),
+            }
+        }
 
-            writeln!(
-                corrected_roads,
-                "{case_id},\"{primary_rd}\",\"{secondary_rd}\""
+        drop(corrections);
+        let tx = self.connection().unchecked_transaction()?;
+        {
+            let mut delete =
+                tx.prepare("DELETE FROM corrected_roads WHERE overlay = ? AND case_id = ?")?;
+            let mut insert = tx.prepare(
+                "INSERT INTO corrected_roads (
+                    case_id,
+                    primary_rd,
+                    secondary_rd,
+                    overlay,
+                    primary_rd_confidence,
+                    secondary_rd_confidence,
+                    primary_rd_source,
+                    secondary_rd_source,
+                    original_primary_rd,
+                    original_secondary_rd
+                ) VALUES(?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             )?;
 
-            if primary_rd.is_empty() {
-                println!("WARNING {case_id} has unknown primary_rd: {original_primary_rd}");
-                println!("  to get of this warning add '{normal_primary_rd}' as 'normalized_rd' to berkeley-tables/BERKELEY_ROAD_TYPOS.csv and the 'correct_rd' entry");
-                println!("  or add the original name '{original_primary_rd}' as 'normalized_rd' to berkeley-tables/BERKELEY_ROAD_TYPOS.csv and the 'correct_rd' entry");
+            // unresolved roads are NULL, the same as when they are loaded from the CSV
+            let road = |road: &str| Some(road.to_string()).filter(|road| !road.is_empty());
+            for (case_id, primary_rd, secondary_rd, original_primary_rd, original_secondary_rd) in
+                &changed
+            {
+                delete.execute([overlay.name(), case_id])?;
+                insert.execute(params![
+                    case_id,
+                    road(&primary_rd.road),
+                    road(&secondary_rd.road),
+                    overlay.name(),
+                    primary_rd.confidence,
+                    secondary_rd.confidence,
+                    primary_rd.source.as_str(),
+                    secondary_rd.source.as_str(),
+                    road(original_primary_rd),
+                    road(original_secondary_rd),
+                ])?;
             }
+        }
+        tx.commit()?;
 
-            if secondary_rd.is_empty() {
-                println!("WARNING {case_id} has unknown secondary_rd: {original_secondary_rd}");
-                println!("  to get of this warning add '{normal_secondary_rd}' as 'normalized_rd' to berkeley-tables/BERKELEY_ROAD_TYPOS.csv and the 'correct_rd' entry");
-                println!("  or add the original name '{original_secondary_rd}' as 'normalized_rd' to berkeley-tables/BERKELEY_ROAD_TYPOS.csv and the 'correct_rd' entry");
-            }
+        // only some of the cases were corrected, the others are written as they were loaded
+        if case_ids.is_some() {
+            corrected_roads = overlay_corrected_roads(self.connection(), overlay)?;
         }
 
-        // reload data from the CORRECTED_ROADS
-        println!("RELOADING corrected_roads with any new roads");
-        self.load_data_with_options(
-            "corrected_roads",
-            Path::new("berkeley-tables/CORRECTED_ROADS.csv"),
-            true,
-            true,
-        )?;
+        let path = corrections_output
+            .path(overlay)
+            .filter(|_| corrections_output.write);
+        if let Some(path) = path {
+            println!(
+                "WRITING corrected_roads for {overlay} to {path}, {count} new or changed corrections",
+                overlay = overlay.name(),
+                count = diffs,
+                path = path.display()
+            );
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&path, corrected_roads)?;
+        } else if diffs == 0 {
+            println!(
+                "UNCHANGED corrected_roads for {overlay}",
+                overlay = overlay.name()
+            );
+        } else {
+            println!(
+                "DRY RUN {count} new or changed corrections were not written, use --write-corrections with --corrections-dir to write them",
+                count = diffs,
+            );
+        }
 
         Ok(())
     }
@@ -435,72 +1028,368 @@ impl NewDB for Connection {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct NormalizedRoad<'a> {
-    road: Cow<'a, str>,
-    address: Option<&'a str>,
-    block: Option<&'a str>,
-    direction: Option<&'a str>,
+/// Restores the pragmas changed by `begin_bulk_load` when dropped, unless loading was ended with `end`
+pub struct BulkLoad<'a> {
+    connection: &'a Connection,
+    ended: bool,
 }
 
-/// Takes Road names and removes address information, or block information
-fn normalize_road(road: &str) -> NormalizedRoad<'_> {
-    static ADDRESS_MATCHER: OnceLock<Regex> = OnceLock::new();
-    static REPLACE_SPACES: OnceLock<Regex> = OnceLock::new();
+impl BulkLoad<'_> {
+    /// Restore the pragmas once everything is loaded, see `end_bulk_load`
+    pub fn end(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.ended = true;
+        self.connection.end_bulk_load()
+    }
+}
 
-    let address_matcher: &Regex = ADDRESS_MATCHER.get_or_init(|| {
-        Regex::new(
-            r"(^(?<address_pre>\d+) +)?((?<direction_pre>NORTHBOUND|EASTBOUND|WESTBOUND|SOUTHBOUND|NORTH|EAST|WEST|SOUTH|N/B|E/B|W/B|S/B|NB|EB|WB|SB|N|E|W|S) +)?(?<street>(I-\d+)|(RT +\d+)|(\w+[ \w]+[[:alpha:]]+))([\.,])*(/B)?( +(?<direction_post>NORTHBOUND|EASTBOUND|WESTBOUND|SOUTHBOUND|NORTH|EAST|WEST|SOUTH|N/B|E/B|W/B|S/B|NB|EB|WB|SB|N|E|W|S)[\.,/]*)?( +((?<address_post>\d+)|(\(?(?<block>\d+) BLOCK\)?))$)?",
-        )
-        .expect("bad regular expression")
-    });
+impl Drop for BulkLoad<'_> {
+    fn drop(&mut self) {
+        if !self.ended {
+            // loading failed, the error from loading is the one reported
+            self.connection.end_bulk_load().ok();
+        }
+    }
+}
 
-    let replace_spaces: &Regex =
-        REPLACE_SPACES.get_or_init(|| Regex::new(r"( +)").expect("bad regular expression"));
+/// Rate of rows loaded since start
+fn rows_per_sec(count: usize, start: Instant) -> usize {
+    (count as f64 / start.elapsed().as_secs_f64()) as usize
+}
 
-    let caps = address_matcher.captures(road);
+/// One of the roads of a row in corrected_roads, NULLs are empty as in the CSV
+struct CorrectedRoad<'a> {
+    road: &'a str,
+    confidence: Option<f64>,
+    source: &'a str,
+    original: &'a str,
+}
 
-    if let Some(caps) = caps {
-        let road = caps.name("street").map(|m| m.as_str()).unwrap_or(road);
-        let road = replace_spaces.replace_all(road, " ");
+impl<'a> CorrectedRoad<'a> {
+    /// The correction of a road, made from the original road
+    fn new(correction: &'a RoadCorrection, original: &'a str) -> Self {
+        Self {
+            road: &correction.road,
+            confidence: correction.confidence,
+            source: correction.source.as_str(),
+            original,
+        }
+    }
 
-        NormalizedRoad {
-            road,
-            address: caps
-                .name("address_pre")
-                .or_else(|| caps.name("address_post"))
-                .map(|s| s.as_str()),
-            block: caps.name("block").map(|m| m.as_str()),
-            direction: caps
-                .name("direction_post")
-                .or_else(|| caps.name("direction_pre"))
-                .map(|m| m.as_str()),
+    /// Read the `correct_{road}` columns of the row, `road` is primary_rd or secondary_rd
+    fn from_row(row: &'a Row<'_>, road: &str) -> Result<Self, rusqlite::Error> {
+        let text = |column: String| -> Result<&'a str, rusqlite::Error> {
+            Ok(row
+                .get_ref(column.as_str())?
+                .as_str_or_null()?
+                .unwrap_or(""))
+        };
+
+        Ok(Self {
+            road: text(format!("correct_{road}"))?,
+            confidence: row.get(format!("correct_{road}_confidence").as_str())?,
+            source: text(format!("correct_{road}_source"))?,
+            original: text(format!("correct_original_{road}"))?,
+        })
+    }
+
+    /// The road if it was corrected by hand. Corrections without a source are from before sources were
+    ///   recorded, their source is derived again: they are only manual when the road differs from the
+    ///   `verified` road and the `typo` correction, otherwise they are rebuilt from those sources
+    fn manual(&self, verified: Option<&str>, typo: Option<&str>) -> Option<&'a str> {
+        let manual = if self.source.is_empty() {
+            self.confidence.is_none_or(|c| c >= 1.0)
+                && verified != Some(self.road)
+                && typo != Some(self.road)
+        } else {
+            self.source == CorrectionSource::Manual.as_str()
+        };
+        Some(self.road).filter(|road| manual && !road.is_empty())
+    }
+}
+
+/// Normalize the primary and secondary roads of a collision. When the secondary road is missing it is taken from
+///   a primary road which describes two roads, e.g. `UNIVERSITY AVE AT SAN PABLO`, and the bool is true
+fn normalize_roads(primary_rd: &str, secondary_rd: &str) -> (NormalizedRoad, NormalizedRoad, bool) {
+    let primary_rd = normalize_road(primary_rd);
+    let secondary_rd = normalize_road(secondary_rd);
+
+    match &primary_rd.secondary_rd {
+        Some(cross_rd) if secondary_rd.road.is_empty() => {
+            let secondary_rd = normalize_road(cross_rd);
+            (primary_rd, secondary_rd, true)
         }
-    } else {
-        let road = replace_spaces.replace_all(road, " ");
+        _ => (primary_rd, secondary_rd, false),
+    }
+}
 
-        NormalizedRoad {
-            road,
-            address: None,
-            block: None,
-            direction: None,
+/// A row of the corrected_roads CSV
+fn corrected_roads_row(
+    case_id: &str,
+    primary_rd: &CorrectedRoad<'_>,
+    secondary_rd: &CorrectedRoad<'_>,
+) -> String {
+    let quote = |s: &str| format!("\"{s}\"", s = s.replace('"', "\"\""));
+
+    format!(
+        "{case_id},{primary_rd},{secondary_rd},{primary_rd_confidence},{secondary_rd_confidence},{primary_rd_source},{secondary_rd_source},{original_primary_rd},{original_secondary_rd}",
+        primary_rd = quote(primary_rd.road),
+        secondary_rd = quote(secondary_rd.road),
+        primary_rd_confidence = format_confidence(primary_rd.confidence),
+        secondary_rd_confidence = format_confidence(secondary_rd.confidence),
+        primary_rd_source = primary_rd.source,
+        secondary_rd_source = secondary_rd.source,
+        original_primary_rd = quote(primary_rd.original),
+        original_secondary_rd = quote(secondary_rd.original),
+    )
+}
+
+/// The corrected_roads CSV of all the corrections of the overlay in the DB
+fn overlay_corrected_roads(
+    connection: &Connection,
+    overlay: &Overlay,
+) -> Result<String, rusqlite::Error> {
+    let mut select = connection.prepare(
+        "SELECT
+        case_id,
+        primary_rd as correct_primary_rd,
+        secondary_rd as correct_secondary_rd,
+        primary_rd_confidence as correct_primary_rd_confidence,
+        secondary_rd_confidence as correct_secondary_rd_confidence,
+        primary_rd_source as correct_primary_rd_source,
+        secondary_rd_source as correct_secondary_rd_source,
+        original_primary_rd as correct_original_primary_rd,
+        original_secondary_rd as correct_original_secondary_rd
+        FROM corrected_roads
+        WHERE overlay = ?
+        ORDER BY case_id",
+    )?;
+
+    let mut corrected_roads = format!("{CORRECTED_ROADS_HEADER}\n");
+    let mut rows = select.query([overlay.name()])?;
+    while let Some(row) = rows.next()? {
+        corrected_roads.push_str(&corrected_roads_row(
+            row.get_ref("case_id")?.as_str()?,
+            &CorrectedRoad::from_row(row, "primary_rd")?,
+            &CorrectedRoad::from_row(row, "secondary_rd")?,
+        ));
+        corrected_roads.push('\n');
+    }
+
+    Ok(corrected_roads)
+}
+
+/// Restrict a query to the given cases, as a condition on `column` to append to its `WHERE`, or no condition
+///   for all the cases. The cases are kept in `temp.fixup_cases`
+fn case_filter(
+    connection: &Connection,
+    column: &str,
+    case_ids: Option<&HashSet<String>>,
+) -> Result<String, rusqlite::Error> {
+    let Some(case_ids) = case_ids else {
+        return Ok(String::new());
+    };
+
+    connection.execute_batch(
+        "DROP TABLE IF EXISTS temp.fixup_cases;
+        CREATE TEMP TABLE fixup_cases (case_id VARCHAR2(19) PRIMARY KEY);",
+    )?;
+    let mut insert_case = connection.prepare("INSERT INTO temp.fixup_cases (case_id) VALUES(?)")?;
+    for case_id in case_ids {
+        insert_case.execute([case_id])?;
+    }
+
+    Ok(format!(
+        "AND {column} IN (SELECT case_id FROM temp.fixup_cases)"
+    ))
+}
+
+/// Format the confidence of a road correction for the corrected_roads CSV, empty when unresolved
+fn format_confidence(confidence: Option<f64>) -> String {
+    confidence.map_or_else(String::new, |c| format!("{c:.2}"))
+}
+
+/// Format the values as a list of quoted SQL strings, e.g. `'0102', '0103'`
+pub(crate) fn sql_list(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| format!("'{value}'", value = value.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Open a DB in memory with the lookup tables and the `tables` created from `schema/{table}.sql`, for the tests.
+///   The collisions, parties and victims tables are loaded with the test data in `tests/data/{table}.csv`, with the
+///   foreign keys off like in a build.
+#[cfg(test)]
+pub(crate) fn test_db(tables: &[&str]) -> Connection {
+    let connection = Connection::open_in_memory().expect("failed to open in memory DB");
+    connection
+        .pragma_update(None, "foreign_keys", "OFF")
+        .expect("failed to disable foreign keys");
+
+    let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+    connection
+        .init_lookup_tables(&schemas.lookup_tables, &schemas.lookup_schema)
+        .expect("failed to init lookup tables");
+    for table in tables {
+        connection
+            .create_table_with_params(
+                Path::new(&format!("schema/{table}.sql")),
+                &[("table", table), ("cnty_city_locs", "'0102', '0103'")],
+            )
+            .expect("failed to create table");
+        if ["collisions", "parties", "victims"].contains(table) {
+            connection
+                .load_data(table, Path::new(&format!("tests/data/{table}.csv")))
+                .expect("failed to load data");
         }
     }
+
+    connection
 }
 
 #[cfg(test)]
 mod tests {
+    use std::process;
+
     use super::*;
 
     #[test]
     fn test_toml() {
         let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
 
-        assert_eq!(schemas.table_order[1], "parties");
+        assert_eq!(schemas.table_order[3], "parties");
         assert_eq!(
             schemas.tables["parties"].schema,
             Path::new("schema/parties.sql")
         );
+        assert_eq!(schemas.overlays()[0].name(), "berkeley");
+    }
+
+    #[test]
+    fn test_toml_relative_paths() {
+        // the paths are relative to the Toml, not the current directory
+        let dir = std::env::current_dir().expect("no current dir");
+        let schemas = Schema::from_toml_file(&dir.join("Schemas.toml")).expect("toml is bad");
+
+        assert_eq!(dir.join("schema/pk_table.sql"), schemas.lookup_schema);
+        assert_eq!(
+            dir.join("schema/parties.sql"),
+            schemas.tables["parties"].schema
+        );
+        assert_eq!(
+            dir.join("lookup-tables/DAY_OF_WEEK.csv"),
+            schemas.lookup_tables["day_of_week"].data
+        );
+        assert_eq!(
+            Some(dir.join("berkeley-tables/BERKELEY_ROAD_TYPOS.csv")),
+            schemas.overlays()[0].table_data("road_typos")
+        );
+
+        // the raw data stays relative to the data directory
+        assert!(matches!(
+            &schemas.tables["collisions"].data,
+            DataPath::RawData(path) if path == Path::new("CollisionRecords.txt")
+        ));
+    }
+
+    #[test]
+    fn test_corrections() {
+        let mut schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        let overlay = &schemas.overlays()[0];
+
+        // by default the corrections are only a dry run, and never written over the overlay's own file
+        assert!(!schemas.corrections.write);
+        assert_eq!(None, schemas.corrections.path(overlay));
+        assert!(schemas.set_corrections(None, true).is_err());
+
+        schemas
+            .set_corrections(Some(PathBuf::from("target/corrections")), true)
+            .expect("bad corrections");
+        let overlay = &schemas.overlays()[0];
+        assert!(schemas.corrections.write);
+        assert_eq!(
+            Some(PathBuf::from(
+                "target/corrections/berkeley_CORRECTED_ROADS.csv"
+            )),
+            schemas.corrections.path(overlay)
+        );
+    }
+
+    #[test]
+    fn test_jurisdictions() {
+        let connection = Connection::open_in_memory().expect("failed to open in memory DB");
+        connection
+            .create_table(
+                "cnty_city_loc",
+                "VARCHAR2(4)",
+                Path::new("schema/cnty_city_loc.sql"),
+            )
+            .expect("failed to create table");
+        connection
+            .load_data(
+                "cnty_city_loc",
+                Path::new("lookup-tables/CNTY_CITY_LOC.csv"),
+            )
+            .expect("failed to load table");
+
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        assert_eq!(
+            vec!["0102", "0103"],
+            schemas
+                .jurisdictions
+                .resolve(&connection)
+                .expect("failed to resolve")
+        );
+
+        let jurisdictions = Jurisdictions {
+            codes: vec![String::from("0101")],
+            counties: vec![],
+            cities: vec![String::from("oakland"), String::from("Hayward")],
+        };
+        assert_eq!(
+            vec!["0101", "0106", "0109", "0110", "0713"],
+            jurisdictions
+                .resolve(&connection)
+                .expect("failed to resolve")
+        );
+
+        let jurisdictions = Jurisdictions {
+            cities: vec![String::from("Oaklnd")],
+            ..Jurisdictions::default()
+        };
+        assert!(jurisdictions.resolve(&connection).is_err());
+        assert!(Jurisdictions::default().resolve(&connection).is_err());
+    }
+
+    #[test]
+    fn test_failed_load_restores_pragmas() {
+        let connection = Connection::open_in_memory().expect("failed to open in memory DB");
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+
+        // there is no raw data to load
+        assert!(connection
+            .load_from_schema(&schemas, Path::new("target/missing-data"))
+            .is_err());
+
+        let pragma = |name: &str| -> i64 {
+            connection
+                .pragma_query_value(None, name, |row| row.get(0))
+                .expect("failed to query pragma")
+        };
+        assert_eq!(1, pragma("foreign_keys"));
+        assert_eq!(2, pragma("synchronous"));
+        assert_eq!(-2_000, pragma("cache_size"));
+    }
+
+    #[test]
+    fn test_baseline_schemas() {
+        // a Schemas.toml from before the jurisdictions were configurable
+        let schemas = Schema::from_toml_file(Path::new("tests/data/baseline/Schemas.toml"))
+            .expect("toml is bad");
+        assert_eq!(vec!["0102", "0103"], schemas.jurisdictions.codes);
+        assert!(schemas.overlays().is_empty());
+        assert!(!schemas.geopackage);
     }
 
     #[test]
@@ -616,7 +1505,13 @@ mod tests {
 
         connection
             .connection()
-            .create_table("collisions", "", Path::new("schema/collisions.sql"))
+            .create_table_with_params(
+                Path::new("schema/collisions.sql"),
+                &[
+                    ("table", "collisions"),
+                    ("cnty_city_locs", "'0102', '0103'"),
+                ],
+            )
             .expect("failed to create table");
 
         connection
@@ -655,7 +1550,13 @@ mod tests {
         // load test data into the collisions table
         connection
             .connection()
-            .create_table("collisions", "", Path::new("schema/collisions.sql"))
+            .create_table_with_params(
+                Path::new("schema/collisions.sql"),
+                &[
+                    ("table", "collisions"),
+                    ("cnty_city_locs", "'0102', '0103'"),
+                ],
+            )
             .expect("failed to create table");
         connection
             .connection()
@@ -704,7 +1605,13 @@ mod tests {
         // load test data into the collisions table
         connection
             .connection()
-            .create_table("collisions", "", Path::new("schema/collisions.sql"))
+            .create_table_with_params(
+                Path::new("schema/collisions.sql"),
+                &[
+                    ("table", "collisions"),
+                    ("cnty_city_locs", "'0102', '0103'"),
+                ],
+            )
             .expect("failed to create table");
         connection
             .connection()
@@ -740,104 +1647,189 @@ mod tests {
     }
 
     #[test]
-    fn test_normalize_road() {
-        let test = |raw, road, address, block, direction| {
-            assert_eq!(
-                NormalizedRoad {
-                    road: Cow::Borrowed(road),
-                    address,
-                    block,
-                    direction,
-                },
-                normalize_road(raw)
-            )
+    fn test_deferred_indexes() {
+        let connection = Connection::open_in_memory().expect("failed to open in memory DB");
+
+        let index_names = |connection: &Connection| {
+            let mut select = connection
+                .prepare("SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = 'victims' AND sql IS NOT NULL ORDER BY name")
+                .expect("bad select");
+            select
+                .query_map([], |row| row.get::<_, String>(0))
+                .expect("failed to query")
+                .collect::<Result<Vec<_>, _>>()
+                .expect("failed to read names")
         };
 
-        test("GRANT", "GRANT", None, None, None);
-        test("1201 2ND ST", "2ND ST", Some("1201"), None, None);
-        test("WARD 1403", "WARD", Some("1403"), None, None);
-        test("RT 123", "RT 123", None, None, None);
-        test("RT 13", "RT 13", None, None, None);
-        test("RT 80 E", "RT 80", None, None, Some("E"));
-        test("RT1805", "RT1805", None, None, None);
-        test("SAN PABLO 1229", "SAN PABLO", Some("1229"), None, None);
-        test("6TH  ST", "6TH ST", None, None, None);
-        test("7 GAUSS WAY", "GAUSS WAY", Some("7"), None, None);
-        test("ASHBY AVE.", "ASHBY AVE", None, None, None);
-        test(
-            "EUCLID AVE (600 BLOCK)",
-            "EUCLID AVE",
-            None,
-            Some("600"),
-            None,
-        );
-        test("RT 80 E/B", "RT 80", None, None, Some("E/B"));
-        test("I-80 WB TO UNIVERSITY AVE", "I-80", None, None, Some("WB"));
-        test("I-80 E/B TO I-580 W/B", "I-80", None, None, Some("E/B"));
-        test(
-            "1313 NINTH STREET (PARKING LOT)",
-            "NINTH STREET",
-            Some("1313"),
-            None,
-            None,
-        );
-        test(
-            "85 EL CAMINO REAL RD",
-            "EL CAMINO REAL RD",
-            Some("85"),
-            None,
-            None,
-        );
-        test(
-            "8TH STREET, 1400 BLOCK",
-            "8TH STREET",
-            None,
-            Some("1400"),
-            None,
-        );
-        test(
-            "ADDISON ST. WESTBOUND, 1500 BLOCK",
-            "ADDISON ST",
-            None,
-            Some("1500"),
-            Some("WESTBOUND"),
-        );
-        test(
-            "CEDAR ST. (2200 BLOCK)",
-            "CEDAR ST",
-            None,
-            Some("2200"),
-            None,
-        );
-        test(
-            "CEDAR STREET, 1800 BLOCK",
-            "CEDAR STREET",
-            None,
-            Some("1800"),
-            None,
-        );
-        test(
-            "CHANNING WAY E/B  800 BLOCK",
-            "CHANNING WAY E",
-            None,
-            Some("800"),
-            None,
+        connection
+            .create_table("victims", "", Path::new("schema/victims.sql"))
+            .expect("failed to create table");
+        let names = index_names(&connection);
+        assert_eq!(
+            vec!["idx_victims_case_id", "idx_victims_case_id_party_number"],
+            names
         );
-        test(
-            "CAMPUS DR, 1400 BLOCK",
-            "CAMPUS DR",
-            None,
-            Some("1400"),
-            None,
+
+        let indexes = connection
+            .drop_indexes("victims")
+            .expect("failed to drop indexes");
+        assert_eq!(2, indexes.len());
+        assert!(index_names(&connection).is_empty());
+
+        connection
+            .create_indexes(&indexes)
+            .expect("failed to create indexes");
+        assert_eq!(names, index_names(&connection));
+    }
+
+    #[test]
+    fn test_overlays_share_a_case() {
+        // two overlays for Berkeley, each with its own correction of the same collision
+        let dir =
+            std::env::temp_dir().join(format!("switrs-db-overlays-{pid}", pid = process::id()));
+        let overlays = [("north", "SHATTUCK AVE"), ("south", "SHATTUCK PL")].map(|(name, road)| {
+            let overlay_dir = dir.join(name);
+            fs::create_dir_all(&overlay_dir).expect("failed to create dir");
+            fs::write(
+                overlay_dir.join("overlay.toml"),
+                format!(
+                    "name = \"{name}\"\n[jurisdictions]\ncities = [\"Berkeley\"]\n[tables]\nroad_typos = \"ROAD_TYPOS.csv\"\ncorrected_roads = \"CORRECTED_ROADS.csv\"\n"
+                ),
+            )
+            .expect("failed to write manifest");
+            fs::write(
+                overlay_dir.join("ROAD_TYPOS.csv"),
+                "normalized_rd,correct_rd\nCEDAR,\"CEDAR ST\"\n",
+            )
+            .expect("failed to write road_typos");
+            fs::write(
+                overlay_dir.join("CORRECTED_ROADS.csv"),
+                format!("case_id,primary_rd,secondary_rd\n1,\"{road}\",\n"),
+            )
+            .expect("failed to write corrected_roads");
+
+            Overlay::from_dir(&overlay_dir).expect("bad overlay")
+        });
+
+        let connection = test_db(&[
+            "road_typos",
+            "corrected_roads",
+            "normalized_roads",
+            "collisions",
+        ]);
+        connection
+            .execute_batch(
+                "INSERT INTO collisions (case_id, cnty_city_loc, primary_rd, secondary_rd) VALUES
+                    ('1', '0103', 'SHATUCK', 'CEDAR');
+                INSERT INTO normalized_roads (case_id, primary_rd, secondary_rd) VALUES
+                    ('1', 'SHATUCK', 'CEDAR');",
+            )
+            .expect("failed to insert");
+        for overlay in &overlays {
+            connection
+                .load_overlay_data("road_typos", overlay)
+                .expect("failed to load road_typos");
+            connection
+                .load_overlay_data("corrected_roads", overlay)
+                .expect("failed to load corrected_roads");
+        }
+
+        // each overlay replaces its own correction, the secondary road is now the typo
+        for overlay in &overlays {
+            connection
+                .fixup_overlay_roads(overlay, &Corrections::default(), None)
+                .expect("failed to fix roads");
+        }
+
+        let mut select = connection
+            .prepare("SELECT overlay, primary_rd, secondary_rd FROM corrected_roads WHERE case_id = '1' ORDER BY overlay")
+            .expect("bad select");
+        let corrections = select
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .expect("failed to query")
+            .collect::<Result<Vec<(String, String, String)>, _>>()
+            .expect("failed to read corrections");
+        assert_eq!(
+            vec![
+                (
+                    "north".to_string(),
+                    "SHATTUCK AVE".to_string(),
+                    "CEDAR ST".to_string()
+                ),
+                (
+                    "south".to_string(),
+                    "SHATTUCK PL".to_string(),
+                    "CEDAR ST".to_string()
+                ),
+            ],
+            corrections
         );
-        test(
-            "CAMPUS DR., 1400 BLOCK",
-            "CAMPUS DR",
-            None,
-            Some("1400"),
-            None,
+
+        // collisions_view only uses the correction of the first overlay
+        let corrected: Vec<String> = connection
+            .prepare("SELECT corrected_primary_rd FROM collisions_view WHERE case_id = '1'")
+            .expect("bad select")
+            .query_map([], |row| row.get(0))
+            .expect("failed to query")
+            .collect::<Result<_, _>>()
+            .expect("failed to read collisions_view");
+        assert_eq!(vec!["SHATTUCK AVE".to_string()], corrected);
+
+        fs::remove_dir_all(&dir).expect("failed to remove dir");
+    }
+
+    #[test]
+    fn test_legacy_correction_source() {
+        let legacy = |road| CorrectedRoad {
+            road,
+            confidence: None,
+            source: "",
+            original: "",
+        };
+
+        // legacy corrections which match the verified road or the typo are rebuilt from those
+        assert_eq!(None, legacy("CEDAR ST").manual(Some("CEDAR ST"), None));
+        assert_eq!(None, legacy("CEDAR ST").manual(None, Some("CEDAR ST")));
+        assert_eq!(
+            Some("CEDAR ST"),
+            legacy("CEDAR ST").manual(None, Some("CEDAR AVE"))
         );
-        test("W COLUSA AV", "COLUSA AV", None, None, Some("W"));
-        test("EAST ASHBY AVE", "ASHBY AVE", None, None, Some("EAST"));
+        assert_eq!(None, legacy("").manual(None, None));
+
+        // recorded sources are kept as they are
+        let manual = CorrectedRoad {
+            source: "manual",
+            ..legacy("CEDAR ST")
+        };
+        assert_eq!(Some("CEDAR ST"), manual.manual(Some("CEDAR ST"), None));
+        let typo = CorrectedRoad {
+            confidence: Some(1.0),
+            source: "typo",
+            ..legacy("CEDAR ST")
+        };
+        assert_eq!(None, typo.manual(None, None));
+    }
+
+    #[test]
+    fn test_normalize_roads() {
+        let (primary_rd, secondary_rd, inferred) =
+            normalize_roads("UNIVERSITY AVE AT SAN PABLO", "");
+        assert_eq!("UNIVERSITY AVE", primary_rd.road);
+        assert_eq!("SAN PABLO", secondary_rd.road);
+        assert!(inferred);
+
+        let (primary_rd, secondary_rd, inferred) = normalize_roads("I-80 E/B TO I-580 W/B", " ");
+        assert_eq!("I-80", primary_rd.road);
+        assert_eq!("I-580", secondary_rd.road);
+        assert!(inferred);
+
+        // the secondary road as reported is kept when there is one
+        let (_, secondary_rd, inferred) = normalize_roads("I-80 WB TO UNIVERSITY AVE", "GILMAN ST");
+        assert_eq!("GILMAN ST", secondary_rd.road);
+        assert!(!inferred);
+
+        let (_, secondary_rd, inferred) = normalize_roads("CEDAR ST", "");
+        assert_eq!("", secondary_rd.road);
+        assert!(!inferred);
     }
 }
@@ -0,0 +1,285 @@
+//! Spatial index of the collision points, for finding the collisions near a point or within a bounding box
+//!
+//! The `collision_points` R*Tree is filled from the `collision_coordinates` of the collisions, reported or
+//!   imputed, on every build and update. The distances are approximated on a flat projection around the point,
+//!   which is accurate to well under a meter at the scale of a city block.
+//!
+//! The boundaries of the cities are read from GeoJSON, only the Polygon and MultiPolygon geometries are used.
+
+use std::{fs, path::Path};
+
+use rusqlite::Connection;
+use serde_json::Value as JsonValue;
+
+use crate::schema::NewDB;
+
+/// Meters in one degree of latitude, on a sphere of the mean radius of the Earth
+pub const METERS_PER_DEGREE: f64 = 6_371_008.8 * std::f64::consts::PI / 180.0;
+
+/// Meters in one foot, SWITRS measures the distance from the secondary road in feet
+pub const METERS_PER_FOOT: f64 = 0.3048;
+
+/// Meters in one degree of longitude at the latitude
+pub fn meters_per_degree_longitude(latitude: f64) -> f64 {
+    METERS_PER_DEGREE * latitude.to_radians().cos()
+}
+
+/// Approximate distance in meters between two points, in degrees of latitude and longitude
+pub fn distance_meters(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let north = (to.0 - from.0) * METERS_PER_DEGREE;
+    let east = (to.1 - from.1) * meters_per_degree_longitude(from.0);
+    north.hypot(east)
+}
+
+/// The point `north` and `east` meters from the point, negative to go south or west
+pub fn offset_meters(from: (f64, f64), north: f64, east: f64) -> (f64, f64) {
+    (
+        from.0 + north / METERS_PER_DEGREE,
+        from.1 + east / meters_per_degree_longitude(from.0),
+    )
+}
+
+/// Outline of a jurisdiction, from the polygons of a GeoJSON file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Boundary {
+    /// Every ring of every polygon, outer rings and holes, as latitude and longitude
+    rings: Vec<Vec<(f64, f64)>>,
+}
+
+impl Boundary {
+    /// Loads the boundary from a GeoJSON FeatureCollection, Feature, Polygon or MultiPolygon
+    pub fn from_geojson_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let geojson: JsonValue = serde_json::from_slice(
+            &fs::read(path)
+                .map_err(|e| format!("failed to read {path}: {e}", path = path.display()))?,
+        )?;
+
+        let mut boundary = Self::default();
+        boundary.add_geojson(&geojson)?;
+        if boundary.rings.is_empty() {
+            return Err(format!("no polygons in {path}", path = path.display()).into());
+        }
+        Ok(boundary)
+    }
+
+    /// Add the rings of all the polygons in the GeoJSON object
+    fn add_geojson(&mut self, geojson: &JsonValue) -> Result<(), Box<dyn std::error::Error>> {
+        match geojson["type"].as_str() {
+            Some("FeatureCollection") => {
+                for feature in geojson["features"]
+                    .as_array()
+                    .ok_or("FeatureCollection has no features")?
+                {
+                    self.add_geojson(feature)?;
+                }
+            }
+            Some("Feature") => self.add_geojson(&geojson["geometry"])?,
+            Some("Polygon") => self.add_polygon(&geojson["coordinates"])?,
+            Some("MultiPolygon") => {
+                for polygon in geojson["coordinates"]
+                    .as_array()
+                    .ok_or("MultiPolygon has no coordinates")?
+                {
+                    self.add_polygon(polygon)?;
+                }
+            }
+            other => return Err(format!("unsupported GeoJSON type: {other:?}").into()),
+        }
+
+        Ok(())
+    }
+
+    /// Add the rings of the polygon's coordinates, the positions are longitude then latitude
+    fn add_polygon(&mut self, polygon: &JsonValue) -> Result<(), Box<dyn std::error::Error>> {
+        for ring in polygon.as_array().ok_or("Polygon has no coordinates")? {
+            let ring = ring
+                .as_array()
+                .ok_or("Polygon ring is not an array")?
+                .iter()
+                .map(
+                    |position| match (position[0].as_f64(), position[1].as_f64()) {
+                        (Some(longitude), Some(latitude)) => Ok((latitude, longitude)),
+                        _ => Err(format!("bad GeoJSON position: {position}")),
+                    },
+                )
+                .collect::<Result<Vec<_>, _>>()?;
+            self.rings.push(ring);
+        }
+
+        Ok(())
+    }
+
+    /// True if the point, as latitude and longitude, is inside the boundary. The rings are counted with the
+    ///   even-odd rule, so points in a hole are outside
+    pub fn contains(&self, point: (f64, f64)) -> bool {
+        let (latitude, longitude) = point;
+        let mut inside = false;
+        for ring in &self.rings {
+            for (from, to) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                if (from.0 > latitude) != (to.0 > latitude) {
+                    let crossing = from.1 + (latitude - from.0) / (to.0 - from.0) * (to.1 - from.1);
+                    if longitude < crossing {
+                        inside = !inside;
+                    }
+                }
+            }
+        }
+
+        inside
+    }
+
+    /// Approximate distance in meters from the point, as latitude and longitude, to the boundary. Points inside
+    ///   the boundary are 0 meters away
+    pub fn distance_meters(&self, point: (f64, f64)) -> f64 {
+        if self.contains(point) {
+            return 0.0;
+        }
+
+        // each edge is projected to meters north and east of the point
+        let east_per_degree = meters_per_degree_longitude(point.0);
+        let project = |(latitude, longitude): (f64, f64)| {
+            (
+                (latitude - point.0) * METERS_PER_DEGREE,
+                (longitude - point.1) * east_per_degree,
+            )
+        };
+
+        let mut distance = f64::INFINITY;
+        for ring in &self.rings {
+            for (&from, &to) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                let (from, to) = (project(from), project(to));
+                let edge = (to.0 - from.0, to.1 - from.1);
+                let length = edge.0 * edge.0 + edge.1 * edge.1;
+                // the closest point on the edge to the origin, which is the point
+                let t = if length > 0.0 {
+                    (-(from.0 * edge.0 + from.1 * edge.1) / length).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                distance = distance.min((from.0 + t * edge.0).hypot(from.1 + t * edge.1));
+            }
+        }
+
+        distance
+    }
+}
+
+/// Extensions to the DB Connection for the spatial index
+pub trait SpatialDB: NewDB {
+    /// Rebuild the spatial index of all the collisions in collision_coordinates, returns the number of points
+    ///
+    /// The rowids of the collisions change when they are merged, so the index is always rebuilt in full.
+    fn index_collision_points(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        println!("INDEXING collision_points");
+        self.connection()
+            .execute("DELETE FROM collision_points", [])?;
+        let count = self.connection().execute(
+            "INSERT INTO collision_points (id, min_latitude, max_latitude, min_longitude, max_longitude)
+            SELECT c.rowid, cc.latitude, cc.latitude, cc.longitude, cc.longitude FROM collision_coordinates AS cc
+            JOIN collisions AS c ON c.case_id = cc.case_id",
+            [],
+        )?;
+
+        Ok(count)
+    }
+}
+
+impl SpatialDB for Connection {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{geocode::GeocodeDB, schema::test_db};
+
+    #[test]
+    fn test_distance() {
+        // one thousandth of a degree is about 111m north, and 88m east in Berkeley
+        let berkeley = (37.8716, -122.2727);
+        let north = distance_meters(berkeley, (37.8726, -122.2727));
+        assert!((north - 111.2).abs() < 0.1, "{north}");
+        let east = distance_meters(berkeley, (37.8716, -122.2717));
+        assert!((east - 87.8).abs() < 0.1, "{east}");
+        assert_eq!(0.0, distance_meters(berkeley, berkeley));
+
+        let moved = offset_meters(berkeley, 100.0, -50.0);
+        assert!((distance_meters(berkeley, moved) - 100f64.hypot(50.0)).abs() < 0.01);
+        assert!(moved.0 > berkeley.0 && moved.1 < berkeley.1);
+    }
+
+    #[test]
+    fn test_boundary() {
+        let berkeley =
+            Boundary::from_geojson_file(Path::new("berkeley-tables/BERKELEY_BOUNDARY.geojson"))
+                .expect("bad boundary");
+        assert!(berkeley.contains((37.8716, -122.2727)));
+        // the longitude is missing its sign, and the latitude and longitude are swapped
+        assert!(!berkeley.contains((37.8716, 122.2727)));
+        assert!(!berkeley.contains((-122.2727, 37.8716)));
+        // Oakland, just south of the border on Shattuck Ave
+        assert!(!berkeley.contains((37.8400, -122.2650)));
+        assert_eq!(0.0, berkeley.distance_meters((37.8716, -122.2727)));
+        let oakland = berkeley.distance_meters((37.8400, -122.2650));
+        assert!((700.0..800.0).contains(&oakland), "{oakland}");
+
+        // a square with a square hole
+        let donut: JsonValue = serde_json::from_str(
+            r#"{"type": "MultiPolygon", "coordinates": [[
+                [[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]],
+                [[1, 1], [3, 1], [3, 3], [1, 3], [1, 1]]
+            ]]}"#,
+        )
+        .expect("bad json");
+        let mut boundary = Boundary::default();
+        boundary.add_geojson(&donut).expect("bad geojson");
+        assert!(boundary.contains((0.5, 2.0)));
+        assert!(!boundary.contains((2.0, 2.0)));
+        assert!(!boundary.contains((5.0, 2.0)));
+        // in the hole the nearest edge is of the inner ring, half a degree south
+        let distance = boundary.distance_meters((1.5, 2.0));
+        assert!(
+            (distance - METERS_PER_DEGREE / 2.0).abs() < 1.0,
+            "{distance}"
+        );
+
+        assert!(boundary
+            .add_geojson(&serde_json::json!({"type": "Point", "coordinates": [0, 0]}))
+            .is_err());
+    }
+
+    #[test]
+    fn test_index_collision_points() {
+        let connection = test_db(&[
+            "collisions",
+            "corrected_roads",
+            "intersections",
+            "collision_coordinates",
+            "collision_points",
+        ]);
+        connection.geocode_collisions().expect("failed to geocode");
+
+        // 2 of the collisions are missing their latitude and longitude
+        assert_eq!(
+            38,
+            connection
+                .index_collision_points()
+                .expect("failed to index")
+        );
+        assert_eq!(
+            38,
+            connection
+                .index_collision_points()
+                .expect("failed to reindex")
+        );
+
+        let (latitude, longitude): (f64, f64) = connection
+            .query_row(
+                "SELECT p.min_latitude, p.min_longitude FROM collision_points AS p
+                JOIN collisions AS c ON c.rowid = p.id WHERE c.case_id = '9629920'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("failed to query");
+        assert!(latitude > 37.0 && latitude < 38.0);
+        assert!(longitude < -122.0 && longitude > -123.0);
+    }
+}
@@ -0,0 +1,403 @@
+//! Incremental import of newer SWITRS exports into an existing DB
+
+use std::{collections::HashSet, fmt, path::Path};
+
+use rusqlite::Connection;
+
+use crate::{
+    build::check_integrity,
+    geocode::GeocodeDB,
+    geopackage::GeoPackageDB,
+    schema::{DataPath, NewDB, Schema},
+    spatial::SpatialDB,
+};
+
+/// Counts of the cases seen while merging a newer export into an existing DB
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct UpdateReport {
+    /// Cases that did not exist in the DB before the update
+    pub added: usize,
+    /// Cases that already existed, but where the collision, a party or a victim was revised
+    pub updated: usize,
+    /// Cases that already existed and are identical in the newer export
+    pub unchanged: usize,
+}
+
+impl fmt::Display for UpdateReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{added} cases added, {updated} cases updated, {unchanged} cases unchanged",
+            added = self.added,
+            updated = self.updated,
+            unchanged = self.unchanged
+        )
+    }
+}
+
+/// Extensions to the DB Connection to merge newer raw data into an already built DB
+pub trait UpdateDB: NewDB {
+    /// Load the CSV at table_data into a temporary copy of the named table, `temp.{name}_update`
+    fn stage_data(
+        &self,
+        name: &str,
+        table_data: &Path,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let staged = format!("{name}_update");
+        self.connection().execute_batch(&format!(
+            "DROP TABLE IF EXISTS temp.{staged};
+            CREATE TEMP TABLE {staged} AS SELECT * FROM main.{name} WHERE 0;"
+        ))?;
+
+        self.load_data(&format!("temp.{staged}"), table_data)
+    }
+
+    /// Merge all the staged tables (see `stage_data`) into the main tables, keyed by case_id.
+    ///
+    /// `tables` must be in load order, i.e. collisions before parties before victims. Every row for a case
+    ///   is replaced when any of its rows were added, removed or changed in the staged data. Rows in the
+    ///   later tables for cases missing from the first table are ignored.
+    ///   The foreign keys are checked before the merge is committed, with `strict_foreign_keys` any violation
+    ///   rolls back the whole merge. Returns the counts of cases and the set of case_ids that were added or updated.
+    fn merge_staged(
+        &self,
+        tables: &[&str],
+        schemas: &Schema,
+        strict_foreign_keys: bool,
+    ) -> Result<(UpdateReport, HashSet<String>), Box<dyn std::error::Error>> {
+        let connection = self.connection();
+        let tx = connection.unchecked_transaction()?;
+
+        // all the cases in the newer export, every case has exactly one row in the first (collisions) table
+        let root = tables.first().ok_or("no tables to merge")?;
+        tx.execute_batch(&format!(
+            "DROP TABLE IF EXISTS temp.staged_cases;
+            CREATE TEMP TABLE staged_cases (case_id VARCHAR2(19) PRIMARY KEY);
+            INSERT INTO temp.staged_cases SELECT case_id FROM temp.{root}_update;"
+        ))?;
+
+        let added = case_set(
+            &tx,
+            &format!(
+                "SELECT case_id FROM temp.staged_cases WHERE case_id NOT IN (SELECT case_id FROM main.{root})"
+            ),
+        )?;
+
+        // any row that differs in either direction marks the case as changed, this catches removed parties
+        //   and victims as well as revised ones
+        let mut changed = HashSet::new();
+        for table in tables {
+            changed.extend(case_set(
+                &tx,
+                &format!(
+                    "SELECT case_id FROM (
+                        SELECT * FROM temp.{table}_update WHERE case_id IN (SELECT case_id FROM temp.staged_cases)
+                        EXCEPT SELECT * FROM main.{table}
+                    )
+                    UNION
+                    SELECT case_id FROM (
+                        SELECT * FROM main.{table} WHERE case_id IN (SELECT case_id FROM temp.staged_cases)
+                        EXCEPT SELECT * FROM temp.{table}_update
+                    )"
+                ),
+            )?);
+        }
+
+        let staged_count: usize =
+            tx.query_row("SELECT count(*) FROM temp.staged_cases", [], |row| {
+                row.get(0)
+            })?;
+        let updated = changed.difference(&added).count();
+        let report = UpdateReport {
+            added: added.len(),
+            updated,
+            unchanged: staged_count - added.len() - updated,
+        };
+
+        // replace all rows of the affected cases
+        changed.extend(added);
+        tx.execute_batch(
+            "DROP TABLE IF EXISTS temp.affected_cases;
+            CREATE TEMP TABLE affected_cases (case_id VARCHAR2(19) PRIMARY KEY);",
+        )?;
+        {
+            let mut insert_case =
+                tx.prepare("INSERT INTO temp.affected_cases (case_id) VALUES(?)")?;
+            for case_id in &changed {
+                insert_case.execute([case_id])?;
+            }
+        }
+
+        for table in tables.iter().rev() {
+            tx.execute(
+                &format!(
+                    "DELETE FROM main.{table} WHERE case_id IN (SELECT case_id FROM temp.affected_cases)"
+                ),
+                [],
+            )?;
+        }
+        tx.execute(
+            "DELETE FROM main.normalized_roads WHERE case_id IN (SELECT case_id FROM temp.affected_cases)",
+            [],
+        )?;
+        for table in tables {
+            tx.execute(
+                &format!(
+                    "INSERT INTO main.{table} SELECT * FROM temp.{table}_update
+                    WHERE case_id IN (SELECT case_id FROM temp.affected_cases)"
+                ),
+                [],
+            )?;
+        }
+
+        // the transaction is rolled back when the check fails
+        check_integrity(&tx, schemas, strict_foreign_keys)?;
+
+        tx.commit()?;
+        Ok((report, changed))
+    }
+
+    /// Merge the raw data tables from a newer export at `data` into the existing DB.
+    ///
+    /// Only the `raw_data` tables in the Schema are merged, the lookup and overlay tables are left as is.
+    ///   The road fixups are rerun for the cases which were added or updated, and the geocoding, coordinate checks,
+    ///   spatial index and GeoPackage layer are rebuilt. With `strict_foreign_keys` nothing is merged when the
+    ///   newer export has codes missing from the lookup tables.
+    fn update_from_schema(
+        &self,
+        schemas: &Schema,
+        data: &Path,
+        strict_foreign_keys: bool,
+    ) -> Result<UpdateReport, Box<dyn std::error::Error>> {
+        // like a new build, unknown codes are reported by the integrity check rather than failing the merge
+        self.connection()
+            .pragma_update(None, "foreign_keys", "OFF")?;
+
+        let mut tables = Vec::new();
+        for table_name in &schemas.table_order {
+            let table = schemas
+                .tables
+                .get(table_name)
+                .ok_or_else(|| format!("table missing from [tables]: {table_name}"))?;
+
+            if let DataPath::RawData(path) = &table.data {
+                println!("STAGING {table_name}");
+                self.stage_data(table_name, &data.join(path))?;
+                tables.push(table_name.as_str());
+            }
+        }
+
+        println!("MERGING {tables}", tables = tables.join(", "));
+        let (report, affected) = self.merge_staged(&tables, schemas, strict_foreign_keys)?;
+
+        self.fixup_roads_for(schemas, Some(&affected))?;
+        self.connection().geocode_collisions()?;
+        self.connection().check_coordinates(schemas)?;
+        self.connection().index_collision_points()?;
+        self.connection().update_geopackage(schemas)?;
+
+        Ok(report)
+    }
+}
+
+impl UpdateDB for Connection {}
+
+/// Collect the first column, a case_id, of every row in the query
+fn case_set(connection: &Connection, query: &str) -> Result<HashSet<String>, rusqlite::Error> {
+    let mut stmt = connection.prepare(query)?;
+    let case_ids = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<HashSet<String>, _>>()?;
+
+    Ok(case_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::test_db;
+
+    #[test]
+    fn test_merge_unchanged() {
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        let connection = test_db(&["normalized_roads", "collisions", "parties"]);
+
+        connection
+            .stage_data("collisions", Path::new("tests/data/collisions.csv"))
+            .expect("failed to stage collisions");
+        connection
+            .stage_data("parties", Path::new("tests/data/parties.csv"))
+            .expect("failed to stage parties");
+
+        let (report, affected) = connection
+            .merge_staged(&["collisions", "parties"], &schemas, true)
+            .expect("failed to merge");
+
+        assert_eq!(
+            UpdateReport {
+                added: 0,
+                updated: 0,
+                unchanged: 40
+            },
+            report
+        );
+        assert!(affected.is_empty());
+    }
+
+    #[test]
+    fn test_merge_revised() {
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        let connection = test_db(&["normalized_roads", "collisions", "parties"]);
+
+        // one collision is revised, one party is revised, and a new case is added
+        connection
+            .stage_data("collisions", Path::new("tests/data/update/collisions.csv"))
+            .expect("failed to stage collisions");
+        connection
+            .stage_data("parties", Path::new("tests/data/update/parties.csv"))
+            .expect("failed to stage parties");
+
+        let (report, affected) = connection
+            .merge_staged(&["collisions", "parties"], &schemas, true)
+            .expect("failed to merge");
+
+        assert_eq!(
+            UpdateReport {
+                added: 1,
+                updated: 2,
+                unchanged: 1
+            },
+            report
+        );
+        assert_eq!(3, affected.len());
+
+        let count: usize = connection
+            .query_row("SELECT count(*) FROM collisions", [], |row| row.get(0))
+            .expect("failed to count");
+        assert_eq!(41, count);
+
+        let severity: String = connection
+            .query_row(
+                "SELECT collision_severity FROM collisions WHERE case_id = '9629920'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("failed to query");
+        assert_eq!("4", severity);
+    }
+
+    #[test]
+    fn test_merge_strict_foreign_keys() {
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        let connection = test_db(&["normalized_roads", "collisions", "parties"]);
+
+        // the revised collision has a weather code missing from the lookup table
+        connection
+            .stage_data("collisions", Path::new("tests/data/update/collisions.csv"))
+            .expect("failed to stage collisions");
+        connection
+            .stage_data("parties", Path::new("tests/data/update/parties.csv"))
+            .expect("failed to stage parties");
+        connection
+            .execute(
+                "UPDATE temp.collisions_update SET weather_1 = 'Z' WHERE case_id = '9629920'",
+                [],
+            )
+            .expect("failed to revise collision");
+
+        assert!(connection
+            .merge_staged(&["collisions", "parties"], &schemas, true)
+            .is_err());
+
+        // nothing was merged
+        let count: usize = connection
+            .query_row("SELECT count(*) FROM collisions", [], |row| row.get(0))
+            .expect("failed to count");
+        assert_eq!(40, count);
+        let weather: String = connection
+            .query_row(
+                "SELECT weather_1 FROM collisions WHERE case_id = '9629920'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("failed to query");
+        assert_eq!("A", weather);
+
+        // without strict foreign keys the violation is only reported
+        let (report, _) = connection
+            .merge_staged(&["collisions", "parties"], &schemas, false)
+            .expect("failed to merge");
+        assert_eq!(1, report.added);
+    }
+
+    #[test]
+    fn test_update_replaces_correction() {
+        let connection = test_db(&[
+            "road_typos",
+            "corrected_roads",
+            "normalized_roads",
+            "collisions",
+            "parties",
+        ]);
+        let schemas = Schema::from_toml_file(Path::new("Schemas.toml")).expect("toml is bad");
+        for overlay in &schemas.overlays {
+            connection
+                .load_overlay_data("road_typos", overlay)
+                .expect("failed to load road_typos");
+            connection
+                .load_overlay_data("corrected_roads", overlay)
+                .expect("failed to load corrected_roads");
+        }
+        connection
+            .fixup_roads_for(&schemas, None)
+            .expect("failed to fix roads");
+
+        // the revised collision is now on another road
+        connection
+            .stage_data("collisions", Path::new("tests/data/update/collisions.csv"))
+            .expect("failed to stage collisions");
+        connection
+            .stage_data("parties", Path::new("tests/data/update/parties.csv"))
+            .expect("failed to stage parties");
+        connection
+            .execute(
+                "UPDATE temp.collisions_update SET primary_rd = 'SHATTUCK' WHERE case_id = '9629920'",
+                [],
+            )
+            .expect("failed to revise collision");
+
+        let (_, affected) = connection
+            .merge_staged(&["collisions", "parties"], &schemas, true)
+            .expect("failed to merge");
+        connection
+            .fixup_roads_for(&schemas, Some(&affected))
+            .expect("failed to fix roads");
+
+        let primary_rd = |case_id: &str| -> (String, String) {
+            connection
+                .query_row(
+                    "SELECT primary_rd, primary_rd_source FROM corrected_roads WHERE overlay = 'berkeley' AND case_id = ?",
+                    [case_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .expect("failed to query corrected_roads")
+        };
+        assert_eq!(
+            ("SHATTUCK AVE".to_string(), "typo".to_string()),
+            primary_rd("9629920")
+        );
+        assert_eq!(
+            ("SAN PABLO AVE".to_string(), "typo".to_string()),
+            primary_rd("9631228")
+        );
+
+        let normalized: String = connection
+            .query_row(
+                "SELECT primary_rd FROM normalized_roads WHERE case_id = '9629920'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("failed to query normalized_roads");
+        assert_eq!("SHATTUCK", normalized);
+    }
+}